@@ -4,7 +4,8 @@ use std::path::{Path, PathBuf};
 
 /// Звук.
 ///
-/// Можно одновременно воспроизводить несколько разных звуков.
+/// Можно одновременно воспроизводить несколько разных звуков, а также один и тот же звук
+/// несколько раз внахлёст — каждое воспроизведение получает свой [голос](Voice).
 ///
 /// Звуки грузятся и декодируются в память целиком, поэтому слишком длинными звуками лучше
 /// не злоупотреблять.
@@ -38,3 +39,27 @@ impl Music {
         &self.path
     }
 }
+
+/// Голос — одно конкретное воспроизведение звука. Возвращается из
+/// [`Context::play_sound`](crate::context::Context::play_sound) и позволяет остановить
+/// именно это воспроизведение или изменить его громкость и зацикленность.
+///
+/// Когда звук доиграл или был остановлен, голос становится недействительным, и вызовы
+/// с ним просто ничего не делают.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Voice {
+    pub(crate) id: u64,
+}
+
+/// Что делать, если заняты все голоса (см. [`Conf::max_voices`](crate::conf::Conf::max_voices)),
+/// а воспроизвести нужно ещё один звук.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VoiceStealing {
+    /// Остановить голос, который играет дольше всех.
+    #[default]
+    Oldest,
+    /// Остановить самый тихий голос (из одинаково тихих — самый старый).
+    Quietest,
+    /// Не воспроизводить новый звук. Возвращённый голос сразу считается остановленным.
+    Skip,
+}
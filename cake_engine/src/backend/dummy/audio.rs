@@ -0,0 +1,67 @@
+use crate::{
+    audio::{Music, Sound, Voice},
+    context::PlaySoundParams,
+    globals::Globals,
+    voices::Voices,
+};
+use std::rc::Rc;
+
+/// Звук бэкендов, которые ничего не воспроизводят (пустышки и программного): запоминается
+/// только, какая музыка сейчас «играет» и какие голоса заняты.
+#[derive(Default)]
+pub(crate) struct DummyAudio {
+    current_music_id: Option<usize>,
+    previous_music_id: Option<usize>,
+}
+
+impl DummyAudio {
+    pub fn play_music(&mut self, music: &Music) {
+        self.previous_music_id = None;
+        self.current_music_id = Some(music.id);
+    }
+
+    pub fn stop_music(&mut self) {
+        self.current_music_id = None;
+        self.previous_music_id = None;
+    }
+
+    pub fn play_music_keeping_previous(&mut self, music: &Music) {
+        self.previous_music_id = self.current_music_id.filter(|id| *id != music.id);
+        self.current_music_id = Some(music.id);
+    }
+
+    pub fn stop_previous_music(&mut self) {
+        self.previous_music_id = None;
+    }
+
+    pub fn get_playing_music(&self, globals: &Globals) -> Option<Rc<Music>> {
+        self.current_music_id
+            .and_then(|id| globals.music.get(&id).cloned())
+    }
+
+    pub fn play_sound_ex(
+        &mut self,
+        voices: &mut Voices,
+        sound: &Sound,
+        params: PlaySoundParams,
+    ) -> Voice {
+        // Звук никуда не выводится, так что одноразовые звуки доигрывают мгновенно
+        voices.remove_finished(|v| v.params.looping);
+        voices.allocate(sound.id, params).voice
+    }
+
+    pub fn update_voice<F: FnOnce(&mut PlaySoundParams)>(
+        &mut self,
+        voices: &mut Voices,
+        voice: Voice,
+        f: F,
+    ) {
+        if let Some(v) = voices.get_mut(voice) {
+            f(&mut v.params);
+        }
+    }
+
+    pub fn is_voice_playing(&self, voices: &Voices, voice: Voice) -> bool {
+        voices.get(voice).is_some_and(|v| v.params.looping)
+    }
+}
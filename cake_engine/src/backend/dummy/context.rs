@@ -1,14 +1,18 @@
 use crate::{
-    audio::{Music, Sound},
+    audio::{Music, Sound, Voice},
+    backend::dummy::DummyAudio,
     color::Color,
     conf::Conf,
-    context::{Context, DrawTextureParams},
+    context::{Context, DrawTextureParams, PlaySoundParams},
     font::Font,
     gametime::GameTime,
     globals::Globals,
+    hot_reload::HotReload,
+    image::Image,
     input::Input,
     rect::Rect,
     texture::{Texture, TextureOptions, TextureSource},
+    transform::Transform,
     vec::Vec2,
     view::View,
 };
@@ -18,15 +22,15 @@ use std::{path::Path, rc::Rc};
 pub struct DummyContext {
     pub conf: Conf,
     globals: Globals,
-    current_music_id: Option<usize>,
+    audio: DummyAudio,
 }
 
 impl DummyContext {
     pub fn new(conf: &Conf) -> DummyContext {
         DummyContext {
             conf: conf.clone(),
-            globals: Globals::new(conf.logical_size),
-            current_music_id: None,
+            globals: Globals::new(conf.logical_size, conf),
+            audio: DummyAudio::default(),
         }
     }
 
@@ -66,6 +70,20 @@ impl Context for DummyContext {
         &mut self.globals.view
     }
 
+    // transform
+
+    fn push_transform(&mut self, transform: Transform) {
+        self.globals.push_transform(transform);
+    }
+
+    fn pop_transform(&mut self) -> Result<()> {
+        self.globals.pop_transform()
+    }
+
+    fn get_transform(&self) -> Transform {
+        self.globals.get_transform()
+    }
+
     // window
 
     fn get_dpi_scale(&self) -> Vec2 {
@@ -132,10 +150,22 @@ impl Context for DummyContext {
         Ok(())
     }
 
+    fn fill_polygon(&mut self, _points: &[Vec2]) -> Result<()> {
+        Ok(())
+    }
+
     fn draw_texture_ex(&mut self, _texture: &Texture, _params: DrawTextureParams) -> Result<()> {
         Ok(())
     }
 
+    fn draw_texture_batch(
+        &mut self,
+        _texture: &Texture,
+        _params: &[DrawTextureParams],
+    ) -> Result<()> {
+        Ok(())
+    }
+
     fn draw_text_to_texture(
         &mut self,
         _text: &str,
@@ -158,31 +188,114 @@ impl Context for DummyContext {
         Ok(())
     }
 
+    fn capture_screenshot(&mut self) -> Result<Image> {
+        // Рисовать Dummy не умеет, поэтому скриншот всегда пустой
+        let (w, h) = self.get_physical_window_size();
+        Ok(Image::new(w, h))
+    }
+
+    fn begin_target(&mut self, target: &Texture) -> Result<()> {
+        if !self.is_texture_valid(target) {
+            return Err(anyhow::anyhow!("Texture not loaded"));
+        }
+        self.globals.begin_target(target)
+    }
+
+    fn end_target(&mut self) -> Result<()> {
+        self.globals.end_target()?;
+        Ok(())
+    }
+
     // audio
 
     fn play_music(&mut self, music: &Music, _volume: f32, _looping: bool) -> Result<()> {
-        self.current_music_id = Some(music.id);
+        self.audio.play_music(music);
+        Ok(())
+    }
+
+    fn set_music_volume(&mut self, _volume: f32) -> Result<()> {
         Ok(())
     }
 
     fn stop_music(&mut self) -> Result<()> {
-        self.current_music_id = None;
+        self.audio.stop_music();
+        Ok(())
+    }
+
+    fn play_music_keeping_previous(
+        &mut self,
+        music: &Music,
+        _volume: f32,
+        _looping: bool,
+    ) -> Result<()> {
+        self.audio.play_music_keeping_previous(music);
+        Ok(())
+    }
+
+    fn set_previous_music_volume(&mut self, _volume: f32) -> Result<()> {
+        Ok(())
+    }
+
+    fn stop_previous_music(&mut self) -> Result<()> {
+        self.audio.stop_previous_music();
         Ok(())
     }
 
     fn get_playing_music(&self) -> Result<Option<Rc<Music>>> {
-        if let Some(id) = self.current_music_id.as_ref() {
-            Ok(self.globals.music.get(id).cloned())
-        } else {
-            Ok(None)
-        }
+        Ok(self.audio.get_playing_music(&self.globals))
+    }
+
+    fn play_sound_ex(&mut self, sound: &Sound, params: PlaySoundParams) -> Result<Voice> {
+        Ok(self
+            .audio
+            .play_sound_ex(&mut self.globals.voices, sound, params))
+    }
+
+    fn stop_sound(&mut self, sound: &Sound) -> Result<()> {
+        self.globals.voices.remove_sound(sound.id);
+        Ok(())
+    }
+
+    fn stop_voice(&mut self, voice: Voice) -> Result<()> {
+        self.globals.voices.remove(voice);
+        Ok(())
+    }
+
+    fn set_voice_volume(&mut self, voice: Voice, volume: f32) -> Result<()> {
+        self.audio
+            .update_voice(&mut self.globals.voices, voice, |p| p.volume = volume);
+        Ok(())
+    }
+
+    fn set_voice_looping(&mut self, voice: Voice, looping: bool) -> Result<()> {
+        self.audio
+            .update_voice(&mut self.globals.voices, voice, |p| p.looping = looping);
+        Ok(())
     }
 
-    fn play_sound(&mut self, _sound: &Sound, _volume: f32, _looping: bool) -> Result<()> {
+    fn set_voice_pan(&mut self, voice: Voice, pan: f32) -> Result<()> {
+        self.audio
+            .update_voice(&mut self.globals.voices, voice, |p| p.pan = pan);
         Ok(())
     }
 
-    fn stop_sound(&mut self, _sound: &Sound) -> Result<()> {
+    fn is_voice_playing(&self, voice: Voice) -> bool {
+        self.audio.is_voice_playing(&self.globals.voices, voice)
+    }
+
+    // resources - hot reload
+
+    fn hot_reload(&self) -> &HotReload {
+        &self.globals.hot_reload
+    }
+
+    fn hot_reload_mut(&mut self) -> &mut HotReload {
+        &mut self.globals.hot_reload
+    }
+
+    fn reload_changed_resources(&mut self) -> Result<()> {
+        // Перезагружать нечего, но список изменившихся ассетов всё равно надо обновить
+        self.globals.poll_hot_reload();
         Ok(())
     }
 
@@ -201,6 +314,17 @@ impl Context for DummyContext {
         Ok(t)
     }
 
+    fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+    ) -> Result<Rc<Texture>> {
+        Ok(self
+            .globals
+            .add_texture(TextureSource::None, width, height, options))
+    }
+
     fn drop_texture_if_unused(&mut self, texture: Rc<Texture>) -> bool {
         let id = texture.id;
         drop(texture);
@@ -270,6 +394,7 @@ impl Context for DummyContext {
 
     fn drop_unused_sounds(&mut self) {
         for s_id in self.globals.get_unused_sound_ids() {
+            self.globals.voices.remove_sound(s_id);
             self.globals.sounds.remove(&s_id);
         }
     }
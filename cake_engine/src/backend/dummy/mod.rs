@@ -7,59 +7,73 @@
 pub mod fs;
 pub mod log;
 
+mod audio;
 mod context;
+mod script;
 
-pub use self::context::*;
+pub(crate) use self::audio::DummyAudio;
+pub use self::{context::*, script::*};
 
 use crate::{
     context::Context,
     input::Event,
-    scene::{InitialScene, Scene, SceneResult},
+    scene::{InitialScene, SceneBuilder, SceneStack, StackResult},
 };
 use anyhow::Result;
 
 use crate::conf::Conf;
 
-pub fn main_dummy(
+pub fn main_dummy(conf: Conf, scene_builder: &'static SceneBuilder) -> Result<()> {
+    main_dummy_script(conf, scene_builder, EventScript::default())
+}
+
+/// Запускает игру, передавая сценам события из сценария. Позволяет, например, прокликать
+/// меню и дойти до конца игры без участия человека.
+pub fn main_dummy_script(
     conf: Conf,
-    scene_builder: &'static dyn Fn(&mut dyn Context) -> Result<Box<dyn Scene>>,
+    scene_builder: &'static SceneBuilder,
+    mut script: EventScript,
 ) -> Result<()> {
     let mut events: Vec<Event> = Vec::new();
-    let mut scene: Box<dyn Scene> = Box::new(InitialScene::new(scene_builder));
-    let mut initial = true;
+    let mut scenes = SceneStack::new(Box::new(InitialScene::new(scene_builder)));
     let mut ctx = DummyContext::new(&conf);
     let mut dt = 0.0;
+    let mut frame: u64 = 0;
 
     ctx.view_mut().set(conf.view);
+    ctx.input_mut().reset();
 
-    'mainloop: loop {
-        ctx.input_mut().reset();
+    loop {
+        if script.max_frames.is_some_and(|m| frame >= m) {
+            break;
+        }
 
-        loop {
-            ctx.input_mut().handle_events(&events);
+        let uptime = ctx.time().uptime().as_secs_f32();
+        script.take_events(frame, uptime, &mut events);
+        ctx.input_mut().handle_events(&events);
 
-            let scene_result = scene.process(&mut ctx, dt, &events)?;
+        if let Some(logical_size) = ctx.input().is_window_just_resized() {
+            ctx.conf.logical_size = logical_size;
+            ctx.view_mut().set_target_size(logical_size);
+        }
 
-            match scene_result {
-                SceneResult::Normal => {
-                    scene.render(&mut ctx)?;
-                    dt = ctx.time_mut().tick();
-                }
-                SceneResult::Switch(next_scene) => {
-                    let mut prev_scene = scene;
-                    scene = next_scene;
-                    prev_scene.stop(&mut ctx)?;
-                    scene.start(&mut ctx, if !initial { Some(prev_scene) } else { None })?;
-                    ctx.drop_unused_resources();
-                    initial = false;
-                }
-                SceneResult::Quit => {
-                    break 'mainloop;
-                }
+        match scenes.process(&mut ctx, dt, &events)? {
+            StackResult::Render => {
+                scenes.render(&mut ctx)?;
+                dt = match script.dt {
+                    Some(script_dt) => ctx.time_mut().tick_by(script_dt),
+                    None => ctx.time_mut().tick(),
+                };
+            }
+            StackResult::Changed => {}
+            StackResult::Quit => {
+                break;
             }
-            events.clear();
-            ctx.view_mut().clear_changed_flag();
         }
+        events.clear();
+        ctx.input_mut().clear();
+        ctx.view_mut().clear_changed_flag();
+        frame += 1;
     }
 
     Ok(())
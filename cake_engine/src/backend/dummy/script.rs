@@ -0,0 +1,63 @@
+use crate::input::Event;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Момент, в который событие из сценария будет передано сцене.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ScriptTime {
+    /// Номер кадра, начиная с нуля. Переключение сцены тоже считается кадром.
+    Frame(u64),
+    /// Время в секундах с момента запуска. Событие будет передано в первом кадре, в котором
+    /// прошло не меньше указанного времени.
+    Time(f32),
+}
+
+/// Сценарий для [`main_dummy_script`](super::main_dummy_script): события, которые будут
+/// переданы сценам в заданные моменты, и управление временем кадра.
+///
+/// ```
+/// # use cake_engine::{dummy::{EventScript, ScriptTime}, input::{Event, ScanCode, KeyMods}};
+/// let script = EventScript {
+///     dt: Some(1.0 / 60.0),
+///     events: vec![(
+///         ScriptTime::Time(2.0),
+///         Event::KeyDown { scancode: ScanCode::Escape, repeat: false, mods: KeyMods::default() },
+///     )],
+///     max_frames: Some(600),
+/// };
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct EventScript {
+    /// Фиксированное время кадра в секундах. Если не указано, используется реальное время.
+    pub dt: Option<f32>,
+    /// События вместе с моментом, в который они будут переданы сцене. Порядок событий,
+    /// приходящихся на один кадр, сохраняется.
+    pub events: Vec<(ScriptTime, Event)>,
+    /// Через сколько кадров завершить работу, если сцена не завершит её раньше. Если
+    /// не указано, работа продолжается, пока сцена не вернёт `SceneResult::Quit`.
+    pub max_frames: Option<u64>,
+}
+
+impl EventScript {
+    /// Возвращает события, которые нужно передать в указанном кадре. Время — сколько секунд
+    /// прошло с момента запуска.
+    pub(crate) fn take_events(&mut self, frame: u64, time: f32, events: &mut Vec<Event>) {
+        let mut i = 0;
+        while i < self.events.len() {
+            let ready = match self.events[i].0 {
+                ScriptTime::Frame(f) => f <= frame,
+                ScriptTime::Time(t) => t <= time,
+            };
+            if ready {
+                events.push(self.events.remove(i).1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
@@ -1,24 +1,28 @@
 use crate::{
-    audio::{Music, Sound},
+    audio::{Music, Sound, Voice},
     color::Color,
     conf::Conf,
-    context::{Context, DrawTextureParams},
+    context::{Context, DrawTextureParams, PlaySoundParams},
     font::Font,
     gametime::{FPSLimiter, GameTime},
     globals::Globals,
+    hot_reload::HotReload,
+    image::Image,
     input::Input,
     rect::Rect,
     texture::{Texture, TextureOptions, TextureSource},
+    transform::Transform,
     vec::Vec2,
     view::View,
 };
 use anyhow::Result;
-use macroquad::{audio::PlaySoundParams, miniquad::FilterMode};
+use macroquad::miniquad::FilterMode;
 use std::{collections::HashMap, path::Path, rc::Rc};
 
 pub struct MacroquadContext<'glob> {
     pub(super) globals: &'glob mut Globals,
     pub(super) native_textures: &'glob mut HashMap<usize, macroquad::prelude::Texture2D>,
+    pub(super) native_render_targets: &'glob mut HashMap<usize, macroquad::prelude::RenderTarget>,
     pub(super) native_fonts: &'glob mut HashMap<usize, macroquad::prelude::Font>,
     pub(super) native_music: &'glob mut HashMap<usize, macroquad::audio::Sound>,
     pub(super) native_sounds: &'glob mut HashMap<usize, macroquad::audio::Sound>,
@@ -26,6 +30,7 @@ pub struct MacroquadContext<'glob> {
     pub(super) fps_limiter: FPSLimiter,
     pub(super) current_color: macroquad::prelude::Color,
     pub(super) current_music_id: &'glob mut Option<usize>,
+    pub(super) previous_music_id: &'glob mut Option<usize>,
 }
 
 impl MacroquadContext<'_> {
@@ -66,6 +71,69 @@ impl MacroquadContext<'_> {
             macroquad::audio::load_sound_from_bytes(&data),
         )?)
     }
+
+    fn start_mq_music(&mut self, music: &Music, volume: f32, looping: bool) -> Result<()> {
+        let mq_music = match self.native_music.get(&music.id) {
+            Some(m) => m,
+            None => return Err(anyhow::anyhow!("Music not loaded")),
+        };
+
+        macroquad::audio::play_sound(
+            mq_music,
+            macroquad::audio::PlaySoundParams {
+                looped: looping,
+                volume,
+            },
+        );
+
+        *self.current_music_id = Some(music.id);
+
+        Ok(())
+    }
+
+    /// Macroquad умеет останавливать звук только целиком, со всеми его воспроизведениями.
+    /// Поэтому, чтобы остановить один голос, звук останавливается, а оставшиеся голоса
+    /// запускаются заново. Одноразовые голоса при этом просто забываются (кроме `keep`),
+    /// а повторяющиеся начинают играть с начала.
+    fn restart_mq_sound(&mut self, sound_id: usize, keep: Option<Voice>) {
+        let mq_sound = match self.native_sounds.get(&sound_id) {
+            Some(s) => s,
+            None => return,
+        };
+        macroquad::audio::stop_sound(mq_sound);
+
+        let mut replay = Vec::new();
+        self.globals.voices.remove_finished(|v| {
+            if v.sound_id != sound_id {
+                return true;
+            }
+            if v.params.looping || Some(v.voice) == keep {
+                replay.push(*v);
+                return true;
+            }
+            false
+        });
+
+        for v in replay {
+            macroquad::audio::play_sound(
+                mq_sound,
+                macroquad::audio::PlaySoundParams {
+                    looped: v.params.looping,
+                    volume: v.params.volume,
+                },
+            );
+        }
+    }
+}
+
+// Направляет рисование в текстуру. OpenGL хранит текстуры снизу вверх, поэтому камеру
+// переворачиваем, чтобы потом текстура рисовалась не вверх ногами
+fn set_target_camera(rt: macroquad::prelude::RenderTarget) {
+    let (w, h) = (rt.texture.width(), rt.texture.height());
+    let mut camera =
+        macroquad::camera::Camera2D::from_display_rect(macroquad::math::Rect::new(0.0, h, w, -h));
+    camera.render_target = Some(rt);
+    macroquad::camera::set_camera(&camera);
 }
 
 impl<'glob> Context for MacroquadContext<'glob> {
@@ -99,6 +167,20 @@ impl<'glob> Context for MacroquadContext<'glob> {
         &mut self.globals.view
     }
 
+    // transform
+
+    fn push_transform(&mut self, transform: Transform) {
+        self.globals.push_transform(transform);
+    }
+
+    fn pop_transform(&mut self) -> Result<()> {
+        self.globals.pop_transform()
+    }
+
+    fn get_transform(&self) -> Transform {
+        self.globals.get_transform()
+    }
+
     // window
 
     fn get_dpi_scale(&self) -> Vec2 {
@@ -181,7 +263,15 @@ impl<'glob> Context for MacroquadContext<'glob> {
     }
 
     fn fill_rect(&mut self, rect: Rect) -> Result<()> {
-        let mut rect_projected = self.globals.view.rect_to_target(rect);
+        let transform = self.globals.get_transform();
+        if !transform.is_axis_aligned() {
+            // Повёрнутый прямоугольник рисуем многоугольником
+            return self.fill_polygon(&rect.get_corners());
+        }
+        let mut rect_projected = self
+            .globals
+            .view
+            .rect_to_target(transform.apply_to_rect(rect));
         rect_projected.normalize();
         if !rect_projected.is_degenerate() {
             macroquad::prelude::draw_rectangle(
@@ -195,11 +285,33 @@ impl<'glob> Context for MacroquadContext<'glob> {
         Ok(())
     }
 
+    fn fill_polygon(&mut self, points: &[Vec2]) -> Result<()> {
+        let transform = self.globals.get_transform();
+        let projected: Vec<macroquad::prelude::Vec2> = points
+            .iter()
+            .map(|p| {
+                let p = self.globals.view.point_to_target(transform.apply(*p));
+                macroquad::prelude::Vec2::new(p.x, p.y)
+            })
+            .collect();
+        // Многоугольник выпуклый, так что достаточно веера треугольников из первой точки
+        for i in 1..projected.len().saturating_sub(1) {
+            macroquad::prelude::draw_triangle(
+                projected[0],
+                projected[i],
+                projected[i + 1],
+                self.current_color,
+            );
+        }
+        Ok(())
+    }
+
     fn draw_texture_ex(&mut self, texture: &Texture, params: DrawTextureParams) -> Result<()> {
         let mq_texture = match self.native_textures.get(&texture.id) {
             Some(t) => t,
             None => return Err(anyhow::anyhow!("Texture not loaded")),
         };
+        let params = self.globals.get_transform().apply_to_params(&params);
 
         let src = match params.src {
             Some(src) => src,
@@ -241,6 +353,53 @@ impl<'glob> Context for MacroquadContext<'glob> {
         Ok(())
     }
 
+    fn draw_texture_batch(
+        &mut self,
+        texture: &Texture,
+        params: &[DrawTextureParams],
+    ) -> Result<()> {
+        // Сколько текстур отправлять за раз, чтобы не превысить размер буферов macroquad
+        const CHUNK_SIZE: usize = 512;
+
+        let mq_texture = match self.native_textures.get(&texture.id) {
+            Some(t) => t,
+            None => return Err(anyhow::anyhow!("Texture not loaded")),
+        };
+
+        let transform = self.globals.get_transform();
+        let gl = unsafe { macroquad::window::get_internal_gl() }.quad_gl;
+        gl.texture(Some(mq_texture));
+        gl.draw_mode(macroquad::models::DrawMode::Triangles);
+
+        let mut vertices = Vec::with_capacity(CHUNK_SIZE.min(params.len()) * 4);
+        let mut indices = Vec::with_capacity(CHUNK_SIZE.min(params.len()) * 6);
+        for chunk in params.chunks(CHUNK_SIZE) {
+            vertices.clear();
+            indices.clear();
+            for p in chunk {
+                let base = vertices.len() as u16;
+                let color = p.color.into();
+                for (pos, tex) in p.corners(texture) {
+                    // Углы преобразуем напрямую — так точнее, чем через apply_to_params
+                    let pos = self.globals.view.point_to_target(transform.apply(pos));
+                    vertices.push(macroquad::models::Vertex::new(
+                        pos.x,
+                        pos.y,
+                        0.0,
+                        tex.x / texture.width as f32,
+                        tex.y / texture.height as f32,
+                        color,
+                    ));
+                }
+                indices.extend([0, 1, 2, 0, 2, 3].map(|i| base + i));
+            }
+            gl.geometry(&vertices, &indices);
+        }
+
+        gl.texture(None);
+        Ok(())
+    }
+
     fn draw_text_to_texture(
         &mut self,
         _text: &str,
@@ -275,6 +434,16 @@ impl<'glob> Context for MacroquadContext<'glob> {
         // TODO: сделать опцию для выбора выравнивания?
         let (ascent, _) = self.get_font_metrics(font)?;
 
+        // Поворачивать текст macroquad не умеет, так что от преобразования берём только
+        // позицию и масштаб
+        let transform = self.globals.get_transform();
+        let params = transform.apply_to_params(&DrawTextureParams {
+            position,
+            scale,
+            ..Default::default()
+        });
+        let (position, scale) = (params.position, params.scale);
+
         let pos_projected = self.globals.view.point_to_target(position);
         let view_scale = self.globals.view.get_scale();
 
@@ -294,28 +463,61 @@ impl<'glob> Context for MacroquadContext<'glob> {
         Ok(())
     }
 
-    // audio
+    fn capture_screenshot(&mut self) -> Result<Image> {
+        let mq_image = macroquad::texture::get_screen_data();
+        let w = mq_image.width as usize;
+        let h = mq_image.height as usize;
 
-    fn play_music(&mut self, music: &Music, volume: f32, looping: bool) -> Result<()> {
-        let mq_music = match self.native_music.get(&music.id) {
-            Some(m) => m,
-            None => return Err(anyhow::anyhow!("Music not loaded")),
+        // OpenGL отдаёт строки снизу вверх, переворачиваем
+        let mut pixels = Vec::with_capacity(w * h * 4);
+        for row in mq_image.bytes.chunks_exact(w * 4).rev() {
+            pixels.extend_from_slice(row);
+        }
+        Image::from_rgba(w as u32, h as u32, pixels)
+    }
+
+    fn begin_target(&mut self, target: &Texture) -> Result<()> {
+        let rt = match self.native_render_targets.get(&target.id) {
+            Some(rt) => rt.clone(),
+            None => return Err(anyhow::anyhow!("Texture is not a render target")),
         };
+        self.globals.begin_target(target)?;
+        set_target_camera(rt);
+        Ok(())
+    }
 
-        macroquad::audio::play_sound(
-            mq_music,
-            PlaySoundParams {
-                looped: looping,
-                volume,
-            },
-        );
+    fn end_target(&mut self) -> Result<()> {
+        self.globals.end_target()?;
+        // Возвращаемся к внешней текстуре, если рисование в неё ещё не закончено, иначе к окну
+        match self
+            .globals
+            .target_id()
+            .and_then(|id| self.native_render_targets.get(&id))
+        {
+            Some(rt) => set_target_camera(rt.clone()),
+            None => macroquad::camera::set_default_camera(),
+        }
+        Ok(())
+    }
 
-        *self.current_music_id = Some(music.id);
+    // audio
+
+    fn play_music(&mut self, music: &Music, volume: f32, looping: bool) -> Result<()> {
+        self.stop_previous_music()?;
+        self.start_mq_music(music, volume, looping)
+    }
 
+    fn set_music_volume(&mut self, volume: f32) -> Result<()> {
+        if let Some(id) = self.current_music_id.as_ref() {
+            if let Some(mq_music) = self.native_music.get(id) {
+                macroquad::audio::set_sound_volume(mq_music, volume);
+            }
+        }
         Ok(())
     }
 
     fn stop_music(&mut self) -> Result<()> {
+        self.stop_previous_music()?;
         if let Some(id) = self.current_music_id.as_ref() {
             if let Some(mq_music) = self.native_music.get(id) {
                 macroquad::audio::stop_sound(mq_music);
@@ -325,6 +527,39 @@ impl<'glob> Context for MacroquadContext<'glob> {
         Ok(())
     }
 
+    fn play_music_keeping_previous(
+        &mut self,
+        music: &Music,
+        volume: f32,
+        looping: bool,
+    ) -> Result<()> {
+        self.stop_previous_music()?;
+        if *self.current_music_id == Some(music.id) {
+            self.stop_music()?;
+        } else {
+            *self.previous_music_id = self.current_music_id.take();
+        }
+        self.start_mq_music(music, volume, looping)
+    }
+
+    fn set_previous_music_volume(&mut self, volume: f32) -> Result<()> {
+        if let Some(id) = self.previous_music_id.as_ref() {
+            if let Some(mq_music) = self.native_music.get(id) {
+                macroquad::audio::set_sound_volume(mq_music, volume);
+            }
+        }
+        Ok(())
+    }
+
+    fn stop_previous_music(&mut self) -> Result<()> {
+        if let Some(id) = self.previous_music_id.take() {
+            if let Some(mq_music) = self.native_music.get(&id) {
+                macroquad::audio::stop_sound(mq_music);
+            }
+        }
+        Ok(())
+    }
+
     fn get_playing_music(&self) -> Result<Option<Rc<Music>>> {
         if let Some(id) = self.current_music_id.as_ref() {
             Ok(self.globals.music.get(id).cloned())
@@ -333,31 +568,129 @@ impl<'glob> Context for MacroquadContext<'glob> {
         }
     }
 
-    fn play_sound(&mut self, sound: &Sound, volume: f32, looping: bool) -> Result<()> {
+    fn play_sound_ex(&mut self, sound: &Sound, params: PlaySoundParams) -> Result<Voice> {
+        if !self.native_sounds.contains_key(&sound.id) {
+            return Err(anyhow::anyhow!("Sound not loaded"));
+        }
+
+        // Панорамы и высоты тона в macroquad нет, так что они только запоминаются
+        let allocation = self.globals.voices.allocate(sound.id, params);
+        let mut started = false;
+        if let Some(stolen) = allocation.stolen {
+            // Если вытеснен голос этого же звука, новый голос запустится при перезапуске
+            self.restart_mq_sound(stolen.sound_id, Some(allocation.voice));
+            started = stolen.sound_id == sound.id;
+        }
+
+        if allocation.start && !started {
+            macroquad::audio::play_sound(
+                &self.native_sounds[&sound.id],
+                macroquad::audio::PlaySoundParams {
+                    looped: params.looping,
+                    volume: params.volume,
+                },
+            );
+        }
+
+        Ok(allocation.voice)
+    }
+
+    fn stop_sound(&mut self, sound: &Sound) -> Result<()> {
         let mq_sound = match self.native_sounds.get(&sound.id) {
             Some(s) => s,
             None => return Err(anyhow::anyhow!("Sound not loaded")),
         };
 
         macroquad::audio::stop_sound(mq_sound);
-        macroquad::audio::play_sound(
-            mq_sound,
-            PlaySoundParams {
-                looped: looping,
-                volume,
-            },
-        );
+        self.globals.voices.remove_sound(sound.id);
+        Ok(())
+    }
 
+    fn stop_voice(&mut self, voice: Voice) -> Result<()> {
+        if let Some(v) = self.globals.voices.remove(voice) {
+            self.restart_mq_sound(v.sound_id, None);
+        }
         Ok(())
     }
 
-    fn stop_sound(&mut self, sound: &Sound) -> Result<()> {
-        let mq_sound = match self.native_sounds.get(&sound.id) {
-            Some(s) => s,
-            None => return Err(anyhow::anyhow!("Sound not loaded")),
+    fn set_voice_volume(&mut self, voice: Voice, volume: f32) -> Result<()> {
+        if let Some(v) = self.globals.voices.get_mut(voice) {
+            v.params.volume = volume;
+            if let Some(mq_sound) = self.native_sounds.get(&v.sound_id) {
+                macroquad::audio::set_sound_volume(mq_sound, volume);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_voice_looping(&mut self, voice: Voice, looping: bool) -> Result<()> {
+        let sound_id = match self.globals.voices.get_mut(voice) {
+            Some(v) => {
+                v.params.looping = looping;
+                v.sound_id
+            }
+            None => return Ok(()),
         };
+        self.restart_mq_sound(sound_id, Some(voice));
+        Ok(())
+    }
 
-        macroquad::audio::stop_sound(mq_sound);
+    fn set_voice_pan(&mut self, voice: Voice, pan: f32) -> Result<()> {
+        if let Some(v) = self.globals.voices.get_mut(voice) {
+            v.params.pan = pan;
+        }
+        Ok(())
+    }
+
+    fn is_voice_playing(&self, voice: Voice) -> bool {
+        self.globals.voices.get(voice).is_some()
+    }
+
+    // resources - hot reload
+
+    fn hot_reload(&self) -> &HotReload {
+        &self.globals.hot_reload
+    }
+
+    fn hot_reload_mut(&mut self) -> &mut HotReload {
+        &mut self.globals.hot_reload
+    }
+
+    fn reload_changed_resources(&mut self) -> Result<()> {
+        let changed = self.globals.poll_hot_reload();
+        for t in changed.textures {
+            match self.load_mq_texture(&t.source, t.options) {
+                Ok(mq_tex) => {
+                    self.native_textures.insert(t.id, mq_tex);
+                }
+                Err(e) => {
+                    crate::log::error!("Failed to reload texture {:?}: {:?}", t.source, e);
+                }
+            }
+        }
+        for f in changed.fonts {
+            let mq_font = crate::fs::read_asset_to_bytes(&f.path)
+                .and_then(|data| Ok(macroquad::text::load_ttf_font_from_bytes(&data)?));
+            match mq_font {
+                Ok(mq_font) => {
+                    self.native_fonts.insert(f.id, mq_font);
+                }
+                Err(e) => {
+                    crate::log::error!("Failed to reload font {:?}: {:?}", f.path, e);
+                }
+            }
+        }
+        for s in changed.sounds {
+            match self.load_mq_sound(&s.path) {
+                Ok(mq_sound) => {
+                    self.stop_sound(&s)?;
+                    self.native_sounds.insert(s.id, mq_sound);
+                }
+                Err(e) => {
+                    crate::log::error!("Failed to reload sound {:?}: {:?}", s.path, e);
+                }
+            }
+        }
         Ok(())
     }
 
@@ -384,6 +717,33 @@ impl<'glob> Context for MacroquadContext<'glob> {
         Ok(t)
     }
 
+    fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+    ) -> Result<Rc<Texture>> {
+        let rt = macroquad::prelude::render_target(width, height);
+        rt.texture.set_filter(if options.smooth {
+            FilterMode::Linear
+        } else {
+            FilterMode::Nearest
+        });
+
+        let t = self
+            .globals
+            .add_texture(TextureSource::None, width, height, options);
+        self.native_textures.insert(t.id, rt.texture.clone());
+        self.native_render_targets.insert(t.id, rt);
+
+        // Содержимое новой текстуры не определено, так что очищаем её
+        self.begin_target(&t)?;
+        macroquad::prelude::clear_background(macroquad::prelude::Color::new(0.0, 0.0, 0.0, 0.0));
+        self.end_target()?;
+
+        Ok(t)
+    }
+
     fn drop_texture_if_unused(&mut self, texture: Rc<Texture>) -> bool {
         let id = texture.id;
         drop(texture);
@@ -396,6 +756,7 @@ impl<'glob> Context for MacroquadContext<'glob> {
 
         if unused {
             self.native_textures.remove(&id);
+            self.native_render_targets.remove(&id);
             self.globals.textures.remove(&id);
         }
         unused
@@ -404,6 +765,7 @@ impl<'glob> Context for MacroquadContext<'glob> {
     fn drop_unused_textures(&mut self) {
         for t_id in self.globals.get_unused_texture_ids() {
             self.native_textures.remove(&t_id);
+            self.native_render_targets.remove(&t_id);
             self.globals.textures.remove(&t_id);
         }
     }
@@ -486,6 +848,7 @@ impl<'glob> Context for MacroquadContext<'glob> {
 
     fn drop_unused_sounds(&mut self) {
         for s_id in self.globals.get_unused_sound_ids() {
+            self.globals.voices.remove_sound(s_id);
             macroquad::audio::stop_sound(&self.native_sounds.remove(&s_id).unwrap());
             self.globals.sounds.remove(&s_id);
         }
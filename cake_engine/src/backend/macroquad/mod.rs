@@ -12,7 +12,7 @@ pub use self::context::*;
 use crate::{
     context::Context,
     input::Event,
-    scene::{InitialScene, Scene, SceneResult},
+    scene::{InitialScene, SceneBuilder, SceneStack, StackResult},
 };
 use anyhow::Result;
 use macroquad::prelude::next_frame;
@@ -20,10 +20,7 @@ use std::collections::HashMap;
 
 use crate::{conf::Conf, gametime::FPSLimiter, globals::Globals};
 
-pub async fn main_macroquad(
-    mut conf: Conf,
-    scene_builder: &'static dyn Fn(&mut dyn Context) -> Result<Box<dyn Scene>>,
-) -> Result<()> {
+pub async fn main_macroquad(mut conf: Conf, scene_builder: &'static SceneBuilder) -> Result<()> {
     macroquad::prelude::prevent_quit();
 
     // Мы сами всё симулируем
@@ -37,16 +34,17 @@ pub async fn main_macroquad(
     macroquad::input::show_mouse(conf.mouse_cursor_visible);
 
     let mut native_textures = HashMap::new();
+    let mut native_render_targets = HashMap::new();
     let mut native_fonts = HashMap::new();
     let mut native_music = HashMap::new();
     let mut native_sounds = HashMap::new();
     let mut current_music_id = None;
+    let mut previous_music_id = None;
 
     let event_subscriber_id = macroquad::input::utils::register_input_subscriber();
     let mut events: Vec<Event> = Vec::new();
-    let mut scene: Box<dyn Scene> = Box::new(InitialScene::new(scene_builder));
-    let mut initial = true;
-    let mut globals = Globals::new(conf.logical_size);
+    let mut scenes = SceneStack::new(Box::new(InitialScene::new(scene_builder)));
+    let mut globals = Globals::new(conf.logical_size, &conf);
     let mut dt = 0.0;
 
     globals.view.set(conf.view);
@@ -55,6 +53,7 @@ pub async fn main_macroquad(
         let mut ctx = MacroquadContext {
             globals: &mut globals,
             native_textures: &mut native_textures,
+            native_render_targets: &mut native_render_targets,
             native_fonts: &mut native_fonts,
             native_music: &mut native_music,
             native_sounds: &mut native_sounds,
@@ -62,6 +61,7 @@ pub async fn main_macroquad(
             fps_limiter: FPSLimiter::new(),
             current_color: macroquad::prelude::Color::new(0.0, 0.0, 0.0, 1.0),
             current_music_id: &mut current_music_id,
+            previous_music_id: &mut previous_music_id,
         };
         ctx.fps_limiter.set_fps_limit(ctx.conf.fps_limit);
 
@@ -80,24 +80,15 @@ pub async fn main_macroquad(
             );
             ctx.globals.input.handle_events(&events);
 
-            let scene_result = scene.process(&mut ctx, dt, &events)?;
-
-            match scene_result {
-                SceneResult::Normal => {
-                    scene.render(&mut ctx)?;
+            match scenes.process(&mut ctx, dt, &events)? {
+                StackResult::Render => {
+                    scenes.render(&mut ctx)?;
                     next_frame().await;
                     ctx.fps_limiter.tick(); // TODO: async?
                     dt = ctx.globals.time.tick();
                 }
-                SceneResult::Switch(next_scene) => {
-                    let mut prev_scene = scene;
-                    scene = next_scene;
-                    prev_scene.stop(&mut ctx)?;
-                    scene.start(&mut ctx, if !initial { Some(prev_scene) } else { None })?;
-                    ctx.drop_unused_resources();
-                    initial = false;
-                }
-                SceneResult::Quit => {
+                StackResult::Changed => {}
+                StackResult::Quit => {
                     break 'mainloop;
                 }
             }
@@ -2,6 +2,9 @@
 
 pub mod dummy;
 
+#[cfg(feature = "software")]
+pub mod software;
+
 #[cfg(feature = "macroquad")]
 mod macroquad;
 
@@ -0,0 +1,151 @@
+// SDL_mixer не умеет менять скорость воспроизведения, поэтому для каждой нужной скорости звук
+// заранее пересчитывается в отдельный Chunk. Скорость округляется, чтобы немного отличающиеся
+// значения не плодили почти одинаковые копии звука
+
+use super::SdlError;
+use anyhow::Result;
+use sdl2::mixer::{Channel, Chunk, Music};
+use std::collections::HashMap;
+
+const PITCH_STEP: f32 = 0.01;
+
+pub(super) fn pitch_key(pitch: f32) -> i32 {
+    ((pitch / PITCH_STEP).round() as i32).max(1)
+}
+
+pub(super) fn is_original_pitch(pitch: f32) -> bool {
+    pitch_key(pitch) == pitch_key(1.0)
+}
+
+pub(super) struct PitchedChunk {
+    // Chunk указывает на data, поэтому объявлен первым, чтобы удаляться раньше
+    chunk: Chunk,
+    _data: Box<[i16]>,
+}
+
+impl PitchedChunk {
+    pub fn new(source: &Chunk, pitch_key: i32) -> Result<PitchedChunk> {
+        let rate = pitch_key as f32 * PITCH_STEP;
+
+        // Аудио открывается в формате AUDIO_S16LSB, и Chunk::from_file приводит звук к нему же
+        let (_, _, channels) = sdl2::mixer::query_spec().map_err(SdlError)?;
+        let channels = channels.max(1) as usize;
+        let src = unsafe {
+            let raw = &*source.raw;
+            std::slice::from_raw_parts(raw.abuf as *const i16, raw.alen as usize / 2)
+        };
+
+        let frames = src.len() / channels;
+        let out_frames = if frames > 0 {
+            (frames as f32 / rate) as usize
+        } else {
+            0
+        };
+
+        let mut data = vec![0i16; out_frames * channels].into_boxed_slice();
+        for i in 0..out_frames {
+            let pos = i as f32 * rate;
+            let j = (pos as usize).min(frames - 1);
+            let k = (j + 1).min(frames - 1);
+            let frac = pos - j as f32;
+            for c in 0..channels {
+                let a = src[j * channels + c] as f32;
+                let b = src[k * channels + c] as f32;
+                data[i * channels + c] = (a + (b - a) * frac) as i16;
+            }
+        }
+
+        // Mix_QuickLoad_RAW не копирует данные и не освобождает их, так что ими владеем мы
+        let raw = unsafe {
+            sdl2::sys::mixer::Mix_QuickLoad_RAW(
+                data.as_mut_ptr() as *mut u8,
+                (data.len() * 2) as u32,
+            )
+        };
+        if raw.is_null() {
+            return Err(SdlError(sdl2::get_error()).into());
+        }
+
+        Ok(PitchedChunk {
+            chunk: Chunk { raw, owned: true },
+            _data: data,
+        })
+    }
+
+    pub fn chunk(&self) -> &Chunk {
+        &self.chunk
+    }
+}
+
+pub(super) fn set_channel_pan(channel: Channel, pan: f32) -> Result<()> {
+    let pan = pan.clamp(-1.0, 1.0);
+    let left = (255.0 * (1.0 - pan).min(1.0)) as u8;
+    let right = (255.0 * (1.0 + pan).min(1.0)) as u8;
+    channel.set_panning(left, right).map_err(SdlError)?;
+    Ok(())
+}
+
+// SDL_mixer играет только одну музыку, поэтому плавный переход делается в два шага: старая
+// музыка затихает, и только потом плавно начинается новая
+const MUSIC_FADE_MS: i32 = 500;
+
+pub(super) struct PendingMusic {
+    id: usize,
+    looping: bool,
+}
+
+/// Что сейчас с музыкой: какая играет, какая ждёт, пока затихнет старая, и какая у неё
+/// должна быть громкость.
+#[derive(Default)]
+pub(super) struct MusicState {
+    pub current_id: Option<usize>,
+    pub pending: Option<PendingMusic>,
+    pub volume: f32,
+}
+
+impl MusicState {
+    /// Начинает затихание играющей музыки; новая запустится в [`update`](Self::update).
+    pub fn fade_to(&mut self, id: usize, volume: f32, looping: bool) -> Result<()> {
+        if self.pending.is_none() {
+            sdl2::mixer::Music::fade_out(MUSIC_FADE_MS).map_err(SdlError)?;
+        }
+        self.current_id = Some(id);
+        self.pending = Some(PendingMusic { id, looping });
+        self.volume = volume;
+        Ok(())
+    }
+
+    /// Когда старая музыка затихла, плавно запускает новую.
+    pub fn update(&mut self, native_music: &HashMap<usize, Music>) -> Result<()> {
+        let pending = match self.pending.as_ref() {
+            Some(p) if !Music::is_playing() => p,
+            _ => return Ok(()),
+        };
+        let music = match native_music.get(&pending.id) {
+            Some(m) => m,
+            None => {
+                self.pending = None;
+                return Err(anyhow::anyhow!("Music not loaded"));
+            }
+        };
+
+        Music::set_volume(music_volume(self.volume));
+        music
+            .fade_in(if pending.looping { -1 } else { 0 }, MUSIC_FADE_MS)
+            .map_err(SdlError)?;
+        self.pending = None;
+        Ok(())
+    }
+
+    /// Задаёт громкость. Пока старая музыка затихает, громкость только запоминается для новой.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        if self.pending.is_none() {
+            Music::set_volume(music_volume(volume));
+        }
+    }
+}
+
+pub(super) fn music_volume(volume: f32) -> i32 {
+    (sdl2::mixer::MAX_VOLUME as f32 * volume) as i32
+}
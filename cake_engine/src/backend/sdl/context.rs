@@ -1,23 +1,27 @@
 use crate::{
-    audio::{Music, Sound},
+    audio::{Music, Sound, Voice},
     backend::sdl::SdlError,
     color::Color,
     conf::Conf,
-    context::{Context, DrawTextureParams},
+    context::{Context, DrawTextureParams, PlaySoundParams},
     font::Font,
-    fs::asset_path,
+    fs::asset_file_path,
     gametime::{FPSLimiter, GameTime},
     globals::Globals,
+    hot_reload::HotReload,
+    image::Image,
     input::Input,
     rect::Rect,
     texture::{Texture, TextureOptions, TextureSource},
+    transform::Transform,
     vec::Vec2,
     view::View,
 };
 use anyhow::Result;
 use sdl2::{
     image::LoadTexture,
-    render::{TextureCreator, WindowCanvas},
+    pixels::PixelFormatEnum,
+    render::{BlendMode, TextureCreator, WindowCanvas},
     ttf::Sdl2TtfContext,
     video::{FullscreenType, WindowContext},
 };
@@ -36,10 +40,9 @@ pub struct SdlContext<'glob, 'win, 'ttf, 'f, 'm> {
     pub(super) conf: &'glob mut Conf,
     pub(super) fps_limiter: FPSLimiter,
     pub(super) rebuild_window: bool,
-    pub(super) current_music_id: &'glob mut Option<usize>,
-    pub(super) allocated_sound_channels: i32,
-    pub(super) sound_channels: HashMap<usize, i32>,
-    pub(super) channel_sounds: HashMap<i32, usize>,
+    pub(super) music_state: &'glob mut super::audio::MusicState,
+    pub(super) voice_channels: &'glob mut HashMap<Voice, i32>,
+    pub(super) pitched_sounds: &'glob mut HashMap<(usize, i32), super::audio::PitchedChunk>,
 }
 
 impl<'win> SdlContext<'_, 'win, '_, '_, '_> {
@@ -118,9 +121,18 @@ impl<'win> SdlContext<'_, 'win, '_, '_, '_> {
         Ok(())
     }
 
+    // Во сколько раз пиксели холста больше логических единиц окна; в текстурах HiDPI нет
+    fn canvas_scale(&self) -> Vec2 {
+        if self.globals.target_id().is_some() {
+            Vec2::new(1.0, 1.0)
+        } else {
+            self.get_dpi_scale()
+        }
+    }
+
     fn rect_to_canvas(&self, rect: Rect) -> Rect {
         let rect2 = self.globals.view.rect_to_target(rect);
-        let dpi = self.get_dpi_scale();
+        let dpi = self.canvas_scale();
         Rect::new(
             rect2.x * dpi.x,
             rect2.y * dpi.y,
@@ -129,13 +141,101 @@ impl<'win> SdlContext<'_, 'win, '_, '_, '_> {
         )
     }
 
+    fn copy_texture(
+        canvas: &mut WindowCanvas,
+        view: &View,
+        dpi: Vec2,
+        sdl_texture: &mut sdl2::render::Texture,
+        texture_size: Vec2,
+        params: &DrawTextureParams,
+    ) -> Result<()> {
+        let src = match params.src {
+            Some(src) => src,
+            None => Rect::new(0.0, 0.0, texture_size.x, texture_size.y),
+        };
+        let src_norm = src.normalized();
+
+        // SDL не поддерживает вывернутые прямоугольники
+        let dst = Rect::new(
+            params.position.x - src_norm.width * params.scale.x * params.origin.x,
+            params.position.y - src_norm.height * params.scale.y * params.origin.y,
+            src_norm.width * params.scale.x,
+            src_norm.height * params.scale.y,
+        );
+        let dst_target = view.rect_to_target(dst);
+        let dst_projected = Rect::new(
+            dst_target.x * dpi.x,
+            dst_target.y * dpi.y,
+            dst_target.width * dpi.x,
+            dst_target.height * dpi.y,
+        );
+
+        // Если dst_projected по какой-то причине оказался вывернутый,
+        // то точку вращения тоже не забываем вывернуть
+        let mut pivot_projected_norm = Vec2::new(
+            dst_projected.width * params.origin.x,
+            dst_projected.height * params.origin.y,
+        );
+        if dst_projected.width < 0.0 {
+            pivot_projected_norm.x -= dst_projected.width;
+        }
+        if dst_projected.height < 0.0 {
+            pivot_projected_norm.y -= dst_projected.height;
+        }
+
+        // Если размеры были заданы отрицательные, то преобразуем вывернутость в параметры SDL
+        // (и не забываем, что минус на минус даёт плюс и тогда ничего выворачивать не надо)
+        let flip_x = (src.width < 0.0) != (dst_projected.width < 0.0);
+        let flip_y = (src.height < 0.0) != (dst_projected.height < 0.0);
+
+        sdl_texture.set_color_mod(params.color.r, params.color.g, params.color.b);
+        sdl_texture.set_alpha_mod(params.color.a);
+
+        canvas
+            .copy_ex(
+                sdl_texture,
+                Some(src_norm.into()),
+                Some(dst_projected.normalized().into()),
+                params.rotation as f64,
+                Some(sdl2::rect::Point::new(
+                    (pivot_projected_norm.x + 0.5) as i32,
+                    (pivot_projected_norm.y + 0.5) as i32,
+                )),
+                flip_x,
+                flip_y,
+            )
+            .map_err(SdlError)?;
+
+        Ok(())
+    }
+
     fn find_free_sound_channel(&self) -> Option<i32> {
-        for i in 0..self.allocated_sound_channels {
-            if !self.channel_sounds.contains_key(&i) {
-                return Some(i);
-            }
+        // Каналов выделено ровно max_voices, так что свободный есть всегда, если голос выделен
+        (0..self.globals.voices.max_voices() as i32)
+            .find(|c| !self.voice_channels.values().any(|used| used == c))
+    }
+
+    fn get_sound_chunk(&mut self, sound_id: usize, pitch: f32) -> Result<&sdl2::mixer::Chunk> {
+        let source = match self.native_sounds.get(&sound_id) {
+            Some(s) => s,
+            None => return Err(anyhow::anyhow!("Sound not loaded")),
+        };
+        if super::audio::is_original_pitch(pitch) {
+            return Ok(source);
+        }
+
+        let key = (sound_id, super::audio::pitch_key(pitch));
+        if !self.pitched_sounds.contains_key(&key) {
+            let pitched = super::audio::PitchedChunk::new(source, key.1)?;
+            self.pitched_sounds.insert(key, pitched);
+        }
+        Ok(self.pitched_sounds[&key].chunk())
+    }
+
+    fn halt_voice_channel(&mut self, voice: Voice) {
+        if let Some(c) = self.voice_channels.remove(&voice) {
+            sdl2::mixer::Channel(c).halt();
         }
-        None
     }
 }
 
@@ -170,6 +270,20 @@ impl Context for SdlContext<'_, '_, '_, '_, '_> {
         &mut self.globals.view
     }
 
+    // transform
+
+    fn push_transform(&mut self, transform: Transform) {
+        self.globals.push_transform(transform);
+    }
+
+    fn pop_transform(&mut self) -> Result<()> {
+        self.globals.pop_transform()
+    }
+
+    fn get_transform(&self) -> Transform {
+        self.globals.get_transform()
+    }
+
     // window
 
     fn get_dpi_scale(&self) -> Vec2 {
@@ -257,7 +371,12 @@ impl Context for SdlContext<'_, '_, '_, '_, '_> {
     }
 
     fn fill_rect(&mut self, rect: Rect) -> Result<()> {
-        let mut rect_projected = self.rect_to_canvas(rect);
+        let transform = self.globals.get_transform();
+        if !transform.is_axis_aligned() {
+            // Повёрнутый прямоугольник рисуем многоугольником
+            return self.fill_polygon(&rect.get_corners());
+        }
+        let mut rect_projected = self.rect_to_canvas(transform.apply_to_rect(rect));
         rect_projected.normalize();
         if !rect_projected.is_degenerate() {
             self.canvas
@@ -267,63 +386,144 @@ impl Context for SdlContext<'_, '_, '_, '_, '_> {
         Ok(())
     }
 
-    fn draw_texture_ex(&mut self, texture: &Texture, params: DrawTextureParams) -> Result<()> {
-        let src = match params.src {
-            Some(src) => src,
-            None => Rect::new(0.0, 0.0, texture.width as f32, texture.height as f32),
-        };
-        let src_norm = src.normalized();
-
-        // SDL не поддерживает вывернутые прямоугольники
-        let dst = Rect::new(
-            params.position.x - src_norm.width * params.scale.x * params.origin.x,
-            params.position.y - src_norm.height * params.scale.y * params.origin.y,
-            src_norm.width * params.scale.x,
-            src_norm.height * params.scale.y,
-        );
-        let dst_projected = self.rect_to_canvas(dst);
-
-        // Если dst_projected по какой-то причине оказался вывернутый,
-        // то точку вращения тоже не забываем вывернуть
-        let mut pivot_projected_norm = Vec2::new(
-            dst_projected.width * params.origin.x,
-            dst_projected.height * params.origin.y,
-        );
-        if dst_projected.width < 0.0 {
-            pivot_projected_norm.x -= dst_projected.width;
+    fn fill_polygon(&mut self, points: &[Vec2]) -> Result<()> {
+        if points.len() < 3 {
+            return Ok(());
         }
-        if dst_projected.height < 0.0 {
-            pivot_projected_norm.y -= dst_projected.height;
+        let color = self.canvas.draw_color();
+        let dpi = self.canvas_scale();
+        let transform = self.globals.get_transform();
+        let vertices: Vec<sdl2::sys::SDL_Vertex> = points
+            .iter()
+            .map(|p| {
+                let p = self.globals.view.point_to_target(transform.apply(*p));
+                sdl2::sys::SDL_Vertex {
+                    position: sdl2::sys::SDL_FPoint {
+                        x: p.x * dpi.x,
+                        y: p.y * dpi.y,
+                    },
+                    color: sdl2::sys::SDL_Color {
+                        r: color.r,
+                        g: color.g,
+                        b: color.b,
+                        a: color.a,
+                    },
+                    tex_coord: sdl2::sys::SDL_FPoint { x: 0.0, y: 0.0 },
+                }
+            })
+            .collect();
+        // Многоугольник выпуклый, так что достаточно веера треугольников из первой точки
+        let indices: Vec<i32> = (1..points.len() as i32 - 1)
+            .flat_map(|i| [0, i, i + 1])
+            .collect();
+
+        // В обёртке sdl2 нет SDL_RenderGeometry, поэтому обращаемся к SDL напрямую
+        let ret = unsafe {
+            sdl2::sys::SDL_RenderGeometry(
+                self.canvas.raw(),
+                std::ptr::null_mut(),
+                vertices.as_ptr(),
+                vertices.len() as i32,
+                indices.as_ptr(),
+                indices.len() as i32,
+            )
+        };
+        if ret != 0 {
+            return Err(SdlError(sdl2::get_error()).into());
         }
+        Ok(())
+    }
 
-        // Если размеры были заданы отрицательные, то преобразуем вывернутость в параметры SDL
-        // (и не забываем, что минус на минус даёт плюс и тогда ничего выворачивать не надо)
-        let flip_x = (src.width < 0.0) != (dst_projected.width < 0.0);
-        let flip_y = (src.height < 0.0) != (dst_projected.height < 0.0);
+    fn draw_texture_ex(&mut self, texture: &Texture, params: DrawTextureParams) -> Result<()> {
+        let dpi = self.canvas_scale();
+        let transform = self.globals.get_transform();
+        let sdl_texture = match self.native_textures.get_mut(&texture.id) {
+            Some(t) => t,
+            None => return Err(SdlError("Texture not loaded".to_string()).into()),
+        };
+        SdlContext::copy_texture(
+            self.canvas,
+            &self.globals.view,
+            dpi,
+            sdl_texture,
+            texture.size_vec(),
+            &transform.apply_to_params(&params),
+        )
+    }
 
+    fn draw_texture_batch(
+        &mut self,
+        texture: &Texture,
+        params: &[DrawTextureParams],
+    ) -> Result<()> {
+        if params.is_empty() {
+            return Ok(());
+        }
+        let dpi = self.canvas_scale();
+        let transform = self.globals.get_transform();
         let sdl_texture = match self.native_textures.get_mut(&texture.id) {
             Some(t) => t,
             None => return Err(SdlError("Texture not loaded".to_string()).into()),
         };
 
-        sdl_texture.set_color_mod(params.color.r, params.color.g, params.color.b);
-        sdl_texture.set_alpha_mod(params.color.a);
+        // Все спрайты собираются в один набор треугольников и рисуются одним вызовом
+        // (SDL_RenderGeometry появился только в SDL 2.0.18)
+        let size = texture.size_vec();
+        let mut vertices = Vec::with_capacity(params.len() * 4);
+        let mut indices = Vec::with_capacity(params.len() * 6);
+        for p in params {
+            let color = sdl2::sys::SDL_Color {
+                r: p.color.r,
+                g: p.color.g,
+                b: p.color.b,
+                a: p.color.a,
+            };
+            let first = vertices.len() as i32;
+            for (position, tex_coords) in p.corners(texture) {
+                let point = self.globals.view.point_to_target(transform.apply(position));
+                vertices.push(sdl2::sys::SDL_Vertex {
+                    position: sdl2::sys::SDL_FPoint {
+                        x: point.x * dpi.x,
+                        y: point.y * dpi.y,
+                    },
+                    color,
+                    tex_coord: sdl2::sys::SDL_FPoint {
+                        x: tex_coords.x / size.x,
+                        y: tex_coords.y / size.y,
+                    },
+                });
+            }
+            indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+        }
 
-        self.canvas
-            .copy_ex(
-                &sdl_texture,
-                Some(src_norm.into()),
-                Some(dst_projected.normalized().into()),
-                params.rotation as f64,
-                Some(sdl2::rect::Point::new(
-                    (pivot_projected_norm.x + 0.5) as i32,
-                    (pivot_projected_norm.y + 0.5) as i32,
-                )),
-                flip_x,
-                flip_y,
+        // Цвет задаётся в вершинах, так что оставшийся от copy_ex цвет текстуры надо сбросить
+        sdl_texture.set_color_mod(255, 255, 255);
+        sdl_texture.set_alpha_mod(255);
+        let ret = unsafe {
+            sdl2::sys::SDL_RenderGeometry(
+                self.canvas.raw(),
+                sdl_texture.raw(),
+                vertices.as_ptr(),
+                vertices.len() as i32,
+                indices.as_ptr(),
+                indices.len() as i32,
             )
-            .map_err(SdlError)?;
+        };
+        if ret == 0 {
+            return Ok(());
+        }
 
+        // Рендерер не умеет рисовать треугольники — рисуем спрайты по одному
+        for p in params {
+            SdlContext::copy_texture(
+                self.canvas,
+                &self.globals.view,
+                dpi,
+                sdl_texture,
+                texture.size_vec(),
+                &transform.apply_to_params(p),
+            )?;
+        }
         Ok(())
     }
 
@@ -360,32 +560,71 @@ impl Context for SdlContext<'_, '_, '_, '_, '_> {
         position: Vec2,
         scale: Vec2,
     ) -> Result<()> {
-        let texture = match self.render_text_to_sdl_texture(text, font, color, smooth)? {
+        let mut texture = match self.render_text_to_sdl_texture(text, font, color, smooth)? {
             Some(t) => t,
             None => return Ok(()),
         };
 
         let info = texture.query();
 
-        let dst_projected = self.rect_to_canvas(Rect::new(
-            position.x,
-            position.y,
-            info.width as f32 * scale.x,
-            info.height as f32 * scale.y,
-        ));
+        // Рисуем как обычную текстуру, чтобы заодно учесть поворот из преобразования
+        let transform = self.globals.get_transform();
+        let params = transform.apply_to_params(&DrawTextureParams {
+            position,
+            scale,
+            ..Default::default()
+        });
+        let dpi = self.canvas_scale();
+        SdlContext::copy_texture(
+            self.canvas,
+            &self.globals.view,
+            dpi,
+            &mut texture,
+            Vec2::new(info.width as f32, info.height as f32),
+            &params,
+        )
+    }
 
-        self.canvas
-            .copy_ex(
-                &texture,
-                None,
-                Some(dst_projected.normalized().into()),
-                0.0,
-                None,
-                dst_projected.width < 0.0,
-                dst_projected.height < 0.0,
-            )
+    fn capture_screenshot(&mut self) -> Result<Image> {
+        let (w, h) = self.canvas.output_size().map_err(SdlError)?;
+        let pixels = self
+            .canvas
+            .read_pixels(None, sdl2::pixels::PixelFormatEnum::RGBA32)
             .map_err(SdlError)?;
+        Image::from_rgba(w, h, pixels)
+    }
+
+    fn begin_target(&mut self, target: &Texture) -> Result<()> {
+        let sdl_texture = match self.native_textures.get(&target.id) {
+            Some(t) => t,
+            None => return Err(SdlError("Texture not loaded".to_string()).into()),
+        };
+        self.globals.begin_target(target)?;
+
+        // Обёртка sdl2 позволяет рисовать в текстуру только внутри замыкания, а нам нужно
+        // между двумя вызовами, поэтому обращаемся к SDL напрямую
+        let ret = unsafe { sdl2::sys::SDL_SetRenderTarget(self.canvas.raw(), sdl_texture.raw()) };
+        if ret != 0 {
+            self.globals.end_target()?;
+            return Err(SdlError(sdl2::get_error()).into());
+        }
+        Ok(())
+    }
 
+    fn end_target(&mut self) -> Result<()> {
+        self.globals.end_target()?;
+        // Возвращаемся к внешней текстуре, если рисование в неё ещё не закончено, иначе к окну
+        let outer = match self.globals.target_id() {
+            Some(id) => match self.native_textures.get(&id) {
+                Some(t) => t.raw(),
+                None => return Err(SdlError("Texture not loaded".to_string()).into()),
+            },
+            None => std::ptr::null_mut(),
+        };
+        let ret = unsafe { sdl2::sys::SDL_SetRenderTarget(self.canvas.raw(), outer) };
+        if ret != 0 {
+            return Err(SdlError(sdl2::get_error()).into());
+        }
         Ok(())
     }
 
@@ -397,26 +636,61 @@ impl Context for SdlContext<'_, '_, '_, '_, '_> {
             None => return Err(anyhow::anyhow!("Music not loaded")),
         };
 
-        sdl2::mixer::Music::set_volume((sdl2::mixer::MAX_VOLUME as f32 * volume) as i32);
+        self.music_state.pending = None;
+        self.music_state.set_volume(volume);
         sdl_music
             .play(if looping { -1 } else { 0 })
             .map_err(SdlError)?;
 
-        *self.current_music_id = Some(music.id);
+        self.music_state.current_id = Some(music.id);
+        Ok(())
+    }
+
+    fn set_music_volume(&mut self, volume: f32) -> Result<()> {
+        // В SDL_mixer громкость музыки общая и не зависит от того, что играет
+        self.music_state.set_volume(volume);
         Ok(())
     }
 
     fn stop_music(&mut self) -> Result<()> {
         sdl2::mixer::Music::halt();
-        *self.current_music_id = None;
+        self.music_state.current_id = None;
+        self.music_state.pending = None;
+        Ok(())
+    }
+
+    fn play_music_keeping_previous(
+        &mut self,
+        music: &Music,
+        volume: f32,
+        looping: bool,
+    ) -> Result<()> {
+        if !self.native_music.contains_key(&music.id) {
+            return Err(anyhow::anyhow!("Music not loaded"));
+        }
+        if !sdl2::mixer::Music::is_playing() && self.music_state.pending.is_none() {
+            return self.play_music(music, volume, looping);
+        }
+
+        // SDL_mixer играет только одну музыку, так что предыдущая сначала затихает сама,
+        // а новая запустится в update_music
+        self.music_state.fade_to(music.id, volume, looping)
+    }
+
+    fn set_previous_music_volume(&mut self, _volume: f32) -> Result<()> {
+        Ok(())
+    }
+
+    fn stop_previous_music(&mut self) -> Result<()> {
         Ok(())
     }
 
     fn get_playing_music(&self) -> Result<Option<Rc<Music>>> {
-        if !sdl2::mixer::Music::is_playing() {
+        // Ждущая музыка для остальных уже играет
+        if !sdl2::mixer::Music::is_playing() && self.music_state.pending.is_none() {
             return Ok(None);
         }
-        if let Some(id) = self.current_music_id.as_ref() {
+        if let Some(id) = self.music_state.current_id.as_ref() {
             match self.globals.music.get(id) {
                 Some(m) => Ok(Some(m.clone())),
                 None => Ok(None),
@@ -426,25 +700,168 @@ impl Context for SdlContext<'_, '_, '_, '_, '_> {
         }
     }
 
-    fn play_sound(&mut self, sound: &Sound, volume: f32, looping: bool) -> Result<()> {
-        let sdl_sound = match self.native_sounds.get_mut(&sound.id) {
-            Some(s) => s,
-            None => return Err(anyhow::anyhow!("Sound not loaded")),
+    fn play_sound_ex(&mut self, sound: &Sound, params: PlaySoundParams) -> Result<Voice> {
+        if !self.native_sounds.contains_key(&sound.id) {
+            return Err(anyhow::anyhow!("Sound not loaded"));
+        }
+
+        // Доигравшие голоса освобождают свои каналы
+        let voice_channels = &*self.voice_channels;
+        let finished = self.globals.voices.remove_finished(|v| {
+            voice_channels
+                .get(&v.voice)
+                .is_some_and(|c| sdl2::mixer::Channel(*c).is_playing())
+        });
+        for v in finished {
+            self.voice_channels.remove(&v.voice);
+        }
+
+        let allocation = self.globals.voices.allocate(sound.id, params);
+        if let Some(stolen) = allocation.stolen {
+            self.halt_voice_channel(stolen.voice);
+        }
+        if !allocation.start {
+            return Ok(allocation.voice);
+        }
+
+        let cid = match self.find_free_sound_channel() {
+            Some(c) => c,
+            None => {
+                self.globals.voices.remove(allocation.voice);
+                return Err(anyhow::anyhow!("No free sound channels"));
+            }
         };
-        sdl_sound.set_volume((sdl2::mixer::MAX_VOLUME as f32 * volume) as i32);
 
-        let channel = sdl2::mixer::Channel(self.sound_channels[&sound.id]);
-        channel.halt();
+        let channel = sdl2::mixer::Channel(cid);
+        channel.set_volume((sdl2::mixer::MAX_VOLUME as f32 * params.volume) as i32);
+        super::audio::set_channel_pan(channel, params.pan)?;
+        let chunk = match self.get_sound_chunk(sound.id, params.pitch) {
+            Ok(c) => c,
+            Err(e) => {
+                self.globals.voices.remove(allocation.voice);
+                return Err(e);
+            }
+        };
         channel
-            .play(&sdl_sound, if looping { -1 } else { 0 })
+            .play(chunk, if params.looping { -1 } else { 0 })
             .map_err(SdlError)?;
-        Ok(())
+        self.voice_channels.insert(allocation.voice, cid);
+        Ok(allocation.voice)
     }
 
     fn stop_sound(&mut self, sound: &Sound) -> Result<()> {
-        if let Some(cid) = self.sound_channels.get(&sound.id) {
-            let channel = sdl2::mixer::Channel(*cid);
-            channel.halt();
+        for v in self.globals.voices.remove_sound(sound.id) {
+            self.halt_voice_channel(v.voice);
+        }
+        Ok(())
+    }
+
+    fn stop_voice(&mut self, voice: Voice) -> Result<()> {
+        self.globals.voices.remove(voice);
+        self.halt_voice_channel(voice);
+        Ok(())
+    }
+
+    fn set_voice_volume(&mut self, voice: Voice, volume: f32) -> Result<()> {
+        if let Some(v) = self.globals.voices.get_mut(voice) {
+            v.params.volume = volume;
+        }
+        if let Some(c) = self.voice_channels.get(&voice) {
+            sdl2::mixer::Channel(*c).set_volume((sdl2::mixer::MAX_VOLUME as f32 * volume) as i32);
+        }
+        Ok(())
+    }
+
+    fn set_voice_looping(&mut self, voice: Voice, looping: bool) -> Result<()> {
+        let info = match self.globals.voices.get_mut(voice) {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        info.params.looping = looping;
+        let sound_id = info.sound_id;
+        let pitch = info.params.pitch;
+
+        // SDL_mixer не умеет менять число повторов на ходу, поэтому звук на том же канале
+        // запускается заново
+        if let Some(c) = self.voice_channels.get(&voice).copied() {
+            let channel = sdl2::mixer::Channel(c);
+            if channel.is_playing() {
+                let chunk = self.get_sound_chunk(sound_id, pitch)?;
+                channel
+                    .play(chunk, if looping { -1 } else { 0 })
+                    .map_err(SdlError)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_voice_pan(&mut self, voice: Voice, pan: f32) -> Result<()> {
+        if let Some(v) = self.globals.voices.get_mut(voice) {
+            v.params.pan = pan;
+        }
+        if let Some(c) = self.voice_channels.get(&voice) {
+            super::audio::set_channel_pan(sdl2::mixer::Channel(*c), pan)?;
+        }
+        Ok(())
+    }
+
+    fn is_voice_playing(&self, voice: Voice) -> bool {
+        self.voice_channels
+            .get(&voice)
+            .is_some_and(|c| sdl2::mixer::Channel(*c).is_playing())
+    }
+
+    // resources - hot reload
+
+    fn hot_reload(&self) -> &HotReload {
+        &self.globals.hot_reload
+    }
+
+    fn hot_reload_mut(&mut self) -> &mut HotReload {
+        &mut self.globals.hot_reload
+    }
+
+    fn reload_changed_resources(&mut self) -> Result<()> {
+        let changed = self.globals.poll_hot_reload();
+        for t in changed.textures {
+            match self.load_sdl_texture(&t.source, t.options) {
+                Ok(sdl_tex) => {
+                    self.native_textures.insert(t.id, sdl_tex);
+                }
+                Err(e) => {
+                    crate::log::error!("Failed to reload texture {:?}: {:?}", t.source, e);
+                }
+            }
+        }
+        for f in changed.fonts {
+            let sdl_font = asset_file_path(&f.path).and_then(|p| {
+                self.ttf_context
+                    .load_font(&p, f.size)
+                    .map_err(|e| SdlError(e).into())
+            });
+            match sdl_font {
+                Ok(sdl_font) => {
+                    self.native_fonts.insert(f.id, sdl_font);
+                }
+                Err(e) => {
+                    crate::log::error!("Failed to reload font {:?}: {:?}", f.path, e);
+                }
+            }
+        }
+        for s in changed.sounds {
+            let sdl_sound = asset_file_path(&s.path)
+                .and_then(|p| sdl2::mixer::Chunk::from_file(&p).map_err(|e| SdlError(e).into()));
+            match sdl_sound {
+                Ok(sdl_sound) => {
+                    // Каналы ещё могут играть старый звук, так что сначала останавливаем их
+                    self.stop_sound(&s)?;
+                    self.pitched_sounds.retain(|(id, _), _| *id != s.id);
+                    self.native_sounds.insert(s.id, sdl_sound);
+                }
+                Err(e) => {
+                    crate::log::error!("Failed to reload sound {:?}: {:?}", s.path, e);
+                }
+            }
         }
         Ok(())
     }
@@ -470,6 +887,38 @@ impl Context for SdlContext<'_, '_, '_, '_, '_> {
         Ok(t)
     }
 
+    fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+    ) -> Result<Rc<Texture>> {
+        sdl2::hint::set(
+            "SDL_RENDER_SCALE_QUALITY",
+            if options.smooth { "linear" } else { "nearest" },
+        );
+        let mut sdl_tex =
+            self.texture_creator
+                .create_texture_target(PixelFormatEnum::RGBA8888, width, height)?;
+        sdl_tex.set_blend_mode(BlendMode::Blend);
+
+        // Содержимое новой текстуры не определено, так что очищаем её
+        let color = self.canvas.draw_color();
+        self.canvas
+            .with_texture_canvas(&mut sdl_tex, |c| {
+                c.set_draw_color(sdl2::pixels::Color::RGBA(0, 0, 0, 0));
+                c.clear();
+            })
+            .map_err(|e| SdlError(e.to_string()))?;
+        self.canvas.set_draw_color(color);
+
+        let t = self
+            .globals
+            .add_texture(TextureSource::None, width, height, options);
+        self.native_textures.insert(t.id, sdl_tex);
+        Ok(t)
+    }
+
     fn drop_texture_if_unused(&mut self, texture: Rc<Texture>) -> bool {
         let id = texture.id;
         drop(texture);
@@ -507,7 +956,7 @@ impl Context for SdlContext<'_, '_, '_, '_, '_> {
     fn load_ttf_file(&mut self, path: &Path, size: u16) -> Result<Rc<Font>> {
         let sdl_font = self
             .ttf_context
-            .load_font(&asset_path(path), size)
+            .load_font(&asset_file_path(path)?, size)
             .map_err(SdlError)?;
         let f = self.globals.add_font(path, size);
         self.native_fonts.insert(f.id, sdl_font);
@@ -549,7 +998,7 @@ impl Context for SdlContext<'_, '_, '_, '_, '_> {
     // resources - audio
 
     fn load_music_file(&mut self, path: &Path) -> Result<Rc<Music>> {
-        let sdl_music = sdl2::mixer::Music::from_file(&asset_path(path)).map_err(SdlError)?;
+        let sdl_music = sdl2::mixer::Music::from_file(&asset_file_path(path)?).map_err(SdlError)?;
 
         let m = self.globals.add_music(path);
         self.native_music.insert(m.id, sdl_music);
@@ -564,28 +1013,19 @@ impl Context for SdlContext<'_, '_, '_, '_, '_> {
     }
 
     fn load_sound_file(&mut self, path: &Path) -> Result<Rc<Sound>> {
-        let channel_id = match self.find_free_sound_channel() {
-            Some(i) => i,
-            None => {
-                sdl2::mixer::allocate_channels(self.allocated_sound_channels + 1);
-                self.allocated_sound_channels += 1;
-                self.allocated_sound_channels - 1
-            }
-        };
-
-        let sdl_sound = sdl2::mixer::Chunk::from_file(&asset_path(path)).map_err(SdlError)?;
+        let sdl_sound = sdl2::mixer::Chunk::from_file(&asset_file_path(path)?).map_err(SdlError)?;
 
         let s = self.globals.add_sound(path);
         self.native_sounds.insert(s.id, sdl_sound);
-        self.sound_channels.insert(s.id, channel_id);
-        self.channel_sounds.insert(channel_id, s.id);
         Ok(s)
     }
 
     fn drop_unused_sounds(&mut self) {
         for s_id in self.globals.get_unused_sound_ids() {
-            let cid = self.sound_channels.remove(&s_id).unwrap();
-            self.channel_sounds.remove(&cid);
+            for v in self.globals.voices.remove_sound(s_id) {
+                self.halt_voice_channel(v.voice);
+            }
+            self.pitched_sounds.retain(|(id, _), _| *id != s_id);
             self.native_sounds.remove(&s_id);
             self.globals.sounds.remove(&s_id);
         }
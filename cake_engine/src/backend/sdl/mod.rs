@@ -1,6 +1,7 @@
 pub mod fs;
 pub mod log;
 
+mod audio;
 mod color;
 mod context;
 mod error;
@@ -12,10 +13,10 @@ pub use self::{context::*, error::*};
 use crate::{
     conf::{Conf, WindowIcon},
     context::Context,
-    fs::asset_path,
+    fs::asset_file_path,
     gametime::FPSLimiter,
     globals::Globals,
-    scene::{InitialScene, Scene, SceneResult},
+    scene::{InitialScene, SceneBuilder, SceneStack, StackResult},
 };
 use anyhow::Result;
 use sdl2::{
@@ -59,15 +60,12 @@ fn make_window(conf: &Conf, video_subsystem: &VideoSubsystem) -> Result<WindowCa
 }
 
 fn set_window_icon(window: &mut Window, icon: &WindowIcon) -> Result<()> {
-    let sdl2_surface = Surface::from_file(&asset_path(&icon.path64)).map_err(SdlError)?;
+    let sdl2_surface = Surface::from_file(&asset_file_path(&icon.path64)?).map_err(SdlError)?;
     window.set_icon(sdl2_surface);
     Ok(())
 }
 
-pub fn main_sdl(
-    mut conf: Conf,
-    scene_builder: &'static dyn Fn(&mut dyn Context) -> Result<Box<dyn Scene>>,
-) -> Result<()> {
+pub fn main_sdl(mut conf: Conf, scene_builder: &'static SceneBuilder) -> Result<()> {
     let sdl_context = sdl2::init().map_err(SdlError)?;
     let video_subsystem = sdl_context.video().map_err(SdlError)?;
 
@@ -83,18 +81,20 @@ pub fn main_sdl(
     )
     .map_err(SdlError)?;
     let _mixer_context = sdl2::mixer::init(sdl2::mixer::InitFlag::OGG).map_err(SdlError)?;
-    sdl2::mixer::allocate_channels(4);
+    // По каналу на каждый голос
+    sdl2::mixer::allocate_channels(conf.max_voices as i32);
 
     // native_textures привязаны к текущему окну
     let mut native_fonts = HashMap::new();
     let mut native_music = HashMap::new();
     let mut native_sounds = HashMap::new();
-    let mut current_music_id = None;
+    let mut voice_channels = HashMap::new();
+    let mut pitched_sounds = HashMap::new();
+    let mut music_state = audio::MusicState::default();
 
     let mut events = Vec::new();
-    let mut scene: Box<dyn Scene> = Box::new(InitialScene::new(scene_builder));
-    let mut initial = true;
-    let mut globals = Globals::new(conf.logical_size);
+    let mut scenes = SceneStack::new(Box::new(InitialScene::new(scene_builder)));
+    let mut globals = Globals::new(conf.logical_size, &conf);
     let mut dt = 0.0;
 
     globals.view.set(conf.view);
@@ -125,10 +125,9 @@ pub fn main_sdl(
             conf: &mut conf,
             fps_limiter: FPSLimiter::new(),
             rebuild_window: false,
-            current_music_id: &mut current_music_id,
-            allocated_sound_channels: 4,
-            sound_channels: HashMap::new(),
-            channel_sounds: HashMap::new(),
+            music_state: &mut music_state,
+            voice_channels: &mut voice_channels,
+            pitched_sounds: &mut pitched_sounds,
         };
         ctx.fps_limiter.set_fps_limit(ctx.conf.fps_limit);
 
@@ -155,24 +154,19 @@ pub fn main_sdl(
                 ctx.globals.view.set_target_size(logical_size);
             }
 
-            let scene_result = scene.process(&mut ctx, dt, &events)?;
+            if let Err(e) = ctx.music_state.update(ctx.native_music) {
+                crate::log::error!("Failed to start music: {:?}", e);
+            }
 
-            match scene_result {
-                SceneResult::Normal => {
-                    scene.render(&mut ctx)?;
+            match scenes.process(&mut ctx, dt, &events)? {
+                StackResult::Render => {
+                    scenes.render(&mut ctx)?;
                     ctx.canvas.present();
                     ctx.fps_limiter.tick();
                     dt = ctx.globals.time.tick();
                 }
-                SceneResult::Switch(next_scene) => {
-                    let mut prev_scene = scene;
-                    scene = next_scene;
-                    prev_scene.stop(&mut ctx)?;
-                    scene.start(&mut ctx, if !initial { Some(prev_scene) } else { None })?;
-                    ctx.drop_unused_resources();
-                    initial = false;
-                }
-                SceneResult::Quit => {
+                StackResult::Changed => {}
+                StackResult::Quit => {
                     break 'mainloop;
                 }
             }
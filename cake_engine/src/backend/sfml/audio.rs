@@ -1,10 +1,13 @@
 // Sound в SFML не владеет звуковыми данными, которые должны храниться где-то отдельно, из-за чего
 // возникает вопрос — а где? Похоже, единственный рабочий не-static вариант — сделать
 // self-referential struct
+//
+// Каждый голос получает свой SfmlSound, а буфер с данными у голосов одного звука общий
 
 use self_cell::self_cell;
 use sfml::{
-    audio::{Sound, SoundBuffer},
+    audio::{Sound, SoundBuffer, SoundSource},
+    system::Vector3f,
     SfBox,
 };
 use std::rc::Rc;
@@ -17,3 +20,12 @@ self_cell!(
         dependent: Sound,
     }
 );
+
+/// Панорамы как таковой в SFML нет, поэтому звук ставится на единичную окружность вокруг
+/// слушателя: слева, справа или перед ним. Работает только для моно-звуков — стерео SFML
+/// не позиционирует.
+pub(super) fn set_pan(sound: &mut Sound, pan: f32) {
+    let pan = pan.clamp(-1.0, 1.0);
+    sound.set_relative_to_listener(true);
+    sound.set_position(Vector3f::new(pan, 0.0, -(1.0 - pan * pan).sqrt()));
+}
@@ -1,22 +1,28 @@
 use crate::{
-    audio::{Music, Sound},
+    audio::{Music, Sound, Voice},
     color::Color,
     conf::Conf,
-    context::{Context, DrawTextureParams},
+    context::{Context, DrawTextureParams, PlaySoundParams},
     font::Font,
-    fs::asset_path,
+    fs::asset_file_path,
     gametime::{FPSLimiter, GameTime},
     globals::Globals,
+    hot_reload::HotReload,
+    image::Image,
     input::Input,
     rect::Rect,
     texture::{Texture, TextureOptions, TextureSource},
+    transform::Transform,
     vec::Vec2,
     view::View,
 };
 use anyhow::Result;
 use sfml::{
-    audio::{SoundSource, SoundStatus},
-    graphics::{IntRect, RectangleShape, RenderTarget, RenderWindow, Shape, Sprite, Transformable},
+    audio::{SoundBuffer, SoundSource, SoundStatus},
+    graphics::{
+        ConvexShape, IntRect, PrimitiveType, RectangleShape, RenderStates, RenderTarget,
+        RenderTexture, RenderWindow, Shape, Sprite, Transformable, Vertex,
+    },
     SfBox,
 };
 use std::{collections::HashMap, path::Path, rc::Rc};
@@ -24,18 +30,70 @@ use std::{collections::HashMap, path::Path, rc::Rc};
 pub struct SfmlContext<'glob, 'win, 'snd> {
     pub(super) globals: &'glob mut Globals,
     pub(super) native_textures: &'glob mut HashMap<usize, SfBox<sfml::graphics::Texture>>,
+    pub(super) native_render_targets: &'glob mut HashMap<usize, RenderTexture>,
+    // Стек текстур, в которые идёт рисование (последняя — текущая); на это время они
+    // достаются из native_render_targets
+    pub(super) current_targets: Vec<RenderTexture>,
     pub(super) native_fonts: &'glob mut HashMap<usize, SfBox<sfml::graphics::Font>>,
     pub(super) native_music: &'glob mut HashMap<usize, sfml::audio::Music<'snd>>,
-    pub(super) native_sounds: &'glob mut HashMap<usize, super::audio::SfmlSound>,
+    pub(super) native_sounds: &'glob mut HashMap<usize, Rc<SfBox<SoundBuffer>>>,
+    pub(super) native_voices: &'glob mut HashMap<Voice, super::audio::SfmlSound>,
     pub(super) window: &'win mut RenderWindow,
     pub(super) fps_limiter: FPSLimiter,
     pub(super) rebuild_window: bool,
     pub(super) conf: &'glob mut Conf,
     pub(super) current_color: sfml::graphics::Color,
     pub(super) current_music_id: &'glob mut Option<usize>,
+    pub(super) previous_music_id: &'glob mut Option<usize>,
+}
+
+// Куда сейчас идёт рисование: в окно или в текстуру
+fn render_target<'a>(
+    window: &'a mut RenderWindow,
+    current_targets: &'a mut [RenderTexture],
+) -> &'a mut dyn RenderTarget {
+    match current_targets.last_mut() {
+        Some(rt) => rt,
+        None => window,
+    }
+}
+
+// Текущее преобразование из стека; вид (view) SFML применяет уже после него
+fn render_states(globals: &Globals) -> RenderStates<'static, 'static, 'static> {
+    RenderStates {
+        transform: globals.get_transform().into(),
+        ..Default::default()
+    }
+}
+
+// Ищет текстуру как среди обычных текстур, так и среди текстур для рисования
+fn find_texture<'a>(
+    native_textures: &'a HashMap<usize, SfBox<sfml::graphics::Texture>>,
+    native_render_targets: &'a HashMap<usize, RenderTexture>,
+    id: usize,
+) -> Result<&'a sfml::graphics::Texture> {
+    if let Some(t) = native_textures.get(&id) {
+        return Ok(t);
+    }
+    match native_render_targets.get(&id) {
+        Some(rt) => Ok(rt.texture()),
+        None => Err(anyhow::anyhow!("Texture not loaded")),
+    }
 }
 
 impl SfmlContext<'_, '_, '_> {
+    fn start_sfml_music(&mut self, music: &Music, volume: f32, looping: bool) -> Result<()> {
+        let sfml_music = match self.native_music.get_mut(&music.id) {
+            Some(m) => m,
+            None => return Err(anyhow::anyhow!("Music not loaded")),
+        };
+        sfml_music.set_looping(looping);
+        sfml_music.set_volume(volume * 100.0);
+        sfml_music.play();
+        *self.current_music_id = Some(music.id);
+        Ok(())
+    }
+
     pub(super) fn recalc_sfml_view(&mut self) {
         let wr = self.globals.view.visible_area();
         let view = sfml::graphics::View::new(
@@ -107,6 +165,20 @@ impl Context for SfmlContext<'_, '_, '_> {
         &mut self.globals.view
     }
 
+    // transform
+
+    fn push_transform(&mut self, transform: Transform) {
+        self.globals.push_transform(transform);
+    }
+
+    fn pop_transform(&mut self) -> Result<()> {
+        self.globals.pop_transform()
+    }
+
+    fn get_transform(&self) -> Transform {
+        self.globals.get_transform()
+    }
+
     // window
 
     fn get_dpi_scale(&self) -> Vec2 {
@@ -176,7 +248,7 @@ impl Context for SfmlContext<'_, '_, '_> {
     }
 
     fn clear(&mut self) -> Result<()> {
-        self.window.clear(self.current_color);
+        render_target(self.window, &mut self.current_targets).clear(self.current_color);
         Ok(())
     }
 
@@ -185,15 +257,28 @@ impl Context for SfmlContext<'_, '_, '_> {
         shape.set_position(rect.get_position());
         shape.set_size(rect.get_size());
         shape.set_fill_color(self.current_color);
-        self.window.draw(&shape);
+        render_target(self.window, &mut self.current_targets)
+            .draw_with_renderstates(&shape, &render_states(self.globals));
+        Ok(())
+    }
+
+    fn fill_polygon(&mut self, points: &[Vec2]) -> Result<()> {
+        if points.len() < 3 {
+            return Ok(());
+        }
+        let mut shape = ConvexShape::new(points.len() as _);
+        for (i, p) in points.iter().enumerate() {
+            shape.set_point(i as _, *p);
+        }
+        shape.set_fill_color(self.current_color);
+        render_target(self.window, &mut self.current_targets)
+            .draw_with_renderstates(&shape, &render_states(self.globals));
         Ok(())
     }
 
     fn draw_texture_ex(&mut self, texture: &Texture, params: DrawTextureParams) -> Result<()> {
-        let sfml_texture = match self.native_textures.get(&texture.id) {
-            Some(t) => t,
-            None => return Err(anyhow::anyhow!("Texture not loaded")),
-        };
+        let sfml_texture =
+            find_texture(self.native_textures, self.native_render_targets, texture.id)?;
 
         let src = match params.src {
             Some(src) => src,
@@ -205,14 +290,47 @@ impl Context for SfmlContext<'_, '_, '_> {
             params.origin.y * src.height.abs(),
         );
 
-        let mut sprite = Sprite::with_texture(&sfml_texture);
+        let mut sprite = Sprite::with_texture(sfml_texture);
         sprite.set_texture_rect(src.into());
         sprite.set_origin(origin_abs);
         sprite.set_position(params.position);
         sprite.set_rotation(params.rotation);
         sprite.set_scale(params.scale);
         sprite.set_color(params.color.into());
-        self.window.draw(&sprite);
+        render_target(self.window, &mut self.current_targets)
+            .draw_with_renderstates(&sprite, &render_states(self.globals));
+        Ok(())
+    }
+
+    fn draw_texture_batch(
+        &mut self,
+        texture: &Texture,
+        params: &[DrawTextureParams],
+    ) -> Result<()> {
+        let sfml_texture =
+            find_texture(self.native_textures, self.native_render_targets, texture.id)?;
+
+        // Каждая текстура — два треугольника, и всё рисуется одним вызовом
+        let mut vertices = Vec::with_capacity(params.len() * 6);
+        for p in params {
+            let color = p.color.into();
+            let corners = p
+                .corners(texture)
+                .map(|(pos, tex)| Vertex::new(pos.into(), color, tex.into()));
+            for i in [0, 1, 2, 0, 2, 3] {
+                vertices.push(corners[i]);
+            }
+        }
+
+        let states = RenderStates {
+            texture: Some(sfml_texture),
+            ..render_states(self.globals)
+        };
+        render_target(self.window, &mut self.current_targets).draw_primitives(
+            &vertices,
+            PrimitiveType::TRIANGLES,
+            &states,
+        );
         Ok(())
     }
 
@@ -306,8 +424,49 @@ impl Context for SfmlContext<'_, '_, '_> {
         sfml_text.set_fill_color(color.into());
         sfml_text.set_position(position);
         sfml_text.set_scale(scale);
-        self.window.draw(&sfml_text);
+        render_target(self.window, &mut self.current_targets)
+            .draw_with_renderstates(&sfml_text, &render_states(self.globals));
+
+        Ok(())
+    }
+
+    fn capture_screenshot(&mut self) -> Result<Image> {
+        let size = self.window.size();
+        let mut sfml_tex = match sfml::graphics::Texture::new() {
+            Some(t) => t,
+            None => return Err(anyhow::anyhow!("Failed to create texture")),
+        };
+        if !sfml_tex.create(size.x, size.y) {
+            return Err(anyhow::anyhow!("Failed to create texture"));
+        }
+        // Размер текстуры совпадает с размером окна, так что выхода за границы не будет
+        unsafe {
+            sfml_tex.update_from_render_window(self.window, 0, 0);
+        }
+        let sfml_image = match sfml_tex.copy_to_image() {
+            Some(im) => im,
+            None => return Err(anyhow::anyhow!("Failed to copy texture to Image")),
+        };
+        Image::from_rgba(size.x, size.y, sfml_image.pixel_data().to_vec())
+    }
+
+    fn begin_target(&mut self, target: &Texture) -> Result<()> {
+        if !self.native_render_targets.contains_key(&target.id) {
+            return Err(anyhow::anyhow!("Texture is not a render target"));
+        }
+        self.globals.begin_target(target)?;
+        if let Some(rt) = self.native_render_targets.remove(&target.id) {
+            self.current_targets.push(rt);
+        }
+        Ok(())
+    }
 
+    fn end_target(&mut self) -> Result<()> {
+        let id = self.globals.end_target()?;
+        if let Some(mut rt) = self.current_targets.pop() {
+            rt.display();
+            self.native_render_targets.insert(id, rt);
+        }
         Ok(())
     }
 
@@ -315,18 +474,20 @@ impl Context for SfmlContext<'_, '_, '_> {
 
     fn play_music(&mut self, music: &Music, volume: f32, looping: bool) -> Result<()> {
         self.stop_music()?;
-        let sfml_music = match self.native_music.get_mut(&music.id) {
-            Some(m) => m,
-            None => return Err(anyhow::anyhow!("Music not loaded")),
-        };
-        sfml_music.set_looping(looping);
-        sfml_music.set_volume(volume * 100.0);
-        sfml_music.play();
-        *self.current_music_id = Some(music.id);
+        self.start_sfml_music(music, volume, looping)
+    }
+
+    fn set_music_volume(&mut self, volume: f32) -> Result<()> {
+        if let Some(id) = self.current_music_id.as_ref() {
+            if let Some(m) = self.native_music.get_mut(id) {
+                m.set_volume(volume * 100.0);
+            }
+        }
         Ok(())
     }
 
     fn stop_music(&mut self) -> Result<()> {
+        self.stop_previous_music()?;
         if let Some(id) = self.current_music_id {
             if let Some(m) = self.native_music.get_mut(&id) {
                 m.stop();
@@ -336,6 +497,39 @@ impl Context for SfmlContext<'_, '_, '_> {
         Ok(())
     }
 
+    fn play_music_keeping_previous(
+        &mut self,
+        music: &Music,
+        volume: f32,
+        looping: bool,
+    ) -> Result<()> {
+        self.stop_previous_music()?;
+        if *self.current_music_id == Some(music.id) {
+            // Один sfml::audio::Music не может играть дважды одновременно
+            return self.play_music(music, volume, looping);
+        }
+        *self.previous_music_id = self.current_music_id.take();
+        self.start_sfml_music(music, volume, looping)
+    }
+
+    fn set_previous_music_volume(&mut self, volume: f32) -> Result<()> {
+        if let Some(id) = self.previous_music_id.as_ref() {
+            if let Some(m) = self.native_music.get_mut(id) {
+                m.set_volume(volume * 100.0);
+            }
+        }
+        Ok(())
+    }
+
+    fn stop_previous_music(&mut self) -> Result<()> {
+        if let Some(id) = self.previous_music_id.take() {
+            if let Some(m) = self.native_music.get_mut(&id) {
+                m.stop();
+            }
+        }
+        Ok(())
+    }
+
     fn get_playing_music(&self) -> Result<Option<Rc<Music>>> {
         if let Some(id) = self.current_music_id.as_ref() {
             if let Some(sfml_music) = self.native_music.get(id) {
@@ -353,27 +547,146 @@ impl Context for SfmlContext<'_, '_, '_> {
         Ok(None)
     }
 
-    fn play_sound(&mut self, sound: &Sound, volume: f32, looping: bool) -> Result<()> {
-        let sfml_sound = match self.native_sounds.get_mut(&sound.id) {
-            Some(s) => s,
+    fn play_sound_ex(&mut self, sound: &Sound, params: PlaySoundParams) -> Result<Voice> {
+        let buf = match self.native_sounds.get(&sound.id) {
+            Some(b) => b.clone(),
             None => return Err(anyhow::anyhow!("Sound not loaded")),
         };
-        sfml_sound.with_dependent_mut(|_, sound| {
-            sound.set_volume(volume * 100.0);
-            sound.set_looping(looping);
-            sound.play();
+
+        // Доигравшие голоса освобождают место
+        let native_voices = &*self.native_voices;
+        let finished = self.globals.voices.remove_finished(|v| {
+            native_voices
+                .get(&v.voice)
+                .is_some_and(|s| s.borrow_dependent().status() != SoundStatus::STOPPED)
         });
-        Ok(())
+        for v in finished {
+            self.native_voices.remove(&v.voice);
+        }
+
+        let allocation = self.globals.voices.allocate(sound.id, params);
+        if let Some(stolen) = allocation.stolen {
+            // Удалённый sfml::audio::Sound сам останавливается
+            self.native_voices.remove(&stolen.voice);
+        }
+
+        if allocation.start {
+            let mut sfml_sound = super::audio::SfmlSound::new(buf, |buf| {
+                let mut sound = sfml::audio::Sound::new();
+                sound.set_buffer(buf);
+                sound
+            });
+            sfml_sound.with_dependent_mut(|_, sound| {
+                sound.set_volume(params.volume * 100.0);
+                sound.set_looping(params.looping);
+                sound.set_pitch(params.pitch);
+                super::audio::set_pan(sound, params.pan);
+                sound.play();
+            });
+            self.native_voices.insert(allocation.voice, sfml_sound);
+        }
+
+        Ok(allocation.voice)
     }
 
     fn stop_sound(&mut self, sound: &Sound) -> Result<()> {
-        let sfml_sound = match self.native_sounds.get_mut(&sound.id) {
-            Some(s) => s,
-            None => return Err(anyhow::anyhow!("Sound not loaded")),
-        };
-        sfml_sound.with_dependent_mut(|_, sound| {
-            sound.stop();
-        });
+        for v in self.globals.voices.remove_sound(sound.id) {
+            self.native_voices.remove(&v.voice);
+        }
+        Ok(())
+    }
+
+    fn stop_voice(&mut self, voice: Voice) -> Result<()> {
+        self.globals.voices.remove(voice);
+        self.native_voices.remove(&voice);
+        Ok(())
+    }
+
+    fn set_voice_volume(&mut self, voice: Voice, volume: f32) -> Result<()> {
+        if let Some(v) = self.globals.voices.get_mut(voice) {
+            v.params.volume = volume;
+        }
+        if let Some(s) = self.native_voices.get_mut(&voice) {
+            s.with_dependent_mut(|_, sound| sound.set_volume(volume * 100.0));
+        }
+        Ok(())
+    }
+
+    fn set_voice_looping(&mut self, voice: Voice, looping: bool) -> Result<()> {
+        if let Some(v) = self.globals.voices.get_mut(voice) {
+            v.params.looping = looping;
+        }
+        if let Some(s) = self.native_voices.get_mut(&voice) {
+            s.with_dependent_mut(|_, sound| sound.set_looping(looping));
+        }
+        Ok(())
+    }
+
+    fn set_voice_pan(&mut self, voice: Voice, pan: f32) -> Result<()> {
+        if let Some(v) = self.globals.voices.get_mut(voice) {
+            v.params.pan = pan;
+        }
+        if let Some(s) = self.native_voices.get_mut(&voice) {
+            s.with_dependent_mut(|_, sound| super::audio::set_pan(sound, pan));
+        }
+        Ok(())
+    }
+
+    fn is_voice_playing(&self, voice: Voice) -> bool {
+        self.native_voices
+            .get(&voice)
+            .is_some_and(|s| s.borrow_dependent().status() != SoundStatus::STOPPED)
+    }
+
+    // resources - hot reload
+
+    fn hot_reload(&self) -> &HotReload {
+        &self.globals.hot_reload
+    }
+
+    fn hot_reload_mut(&mut self) -> &mut HotReload {
+        &mut self.globals.hot_reload
+    }
+
+    fn reload_changed_resources(&mut self) -> Result<()> {
+        let changed = self.globals.poll_hot_reload();
+        for t in changed.textures {
+            match self.load_sfml_texture(&t.source, t.options) {
+                Ok(sfml_tex) => {
+                    self.native_textures.insert(t.id, sfml_tex);
+                }
+                Err(e) => {
+                    crate::log::error!("Failed to reload texture {:?}: {:?}", t.source, e);
+                }
+            }
+        }
+        for f in changed.fonts {
+            let sfml_font = asset_file_path(&f.path)
+                .ok()
+                .and_then(|p| sfml::graphics::Font::from_file(p.to_str().unwrap()));
+            match sfml_font {
+                Some(sfml_font) => {
+                    self.native_fonts.insert(f.id, sfml_font);
+                }
+                None => {
+                    crate::log::error!("Failed to reload font {:?}", f.path);
+                }
+            }
+        }
+        for s in changed.sounds {
+            let buf = asset_file_path(&s.path)
+                .and_then(|p| Ok(sfml::audio::SoundBuffer::from_file(p.to_str().unwrap())?));
+            match buf {
+                Ok(buf) => {
+                    // Голоса держат ссылку на старый буфер, так что сначала останавливаем их
+                    self.stop_sound(&s)?;
+                    self.native_sounds.insert(s.id, Rc::new(buf));
+                }
+                Err(e) => {
+                    crate::log::error!("Failed to reload sound {:?}: {:?}", s.path, e);
+                }
+            }
+        }
         Ok(())
     }
 
@@ -381,6 +694,8 @@ impl Context for SfmlContext<'_, '_, '_> {
 
     fn is_texture_valid(&self, texture: &Texture) -> bool {
         self.native_textures.contains_key(&texture.id)
+            || self.native_render_targets.contains_key(&texture.id)
+            || self.globals.is_target_active(texture.id)
     }
 
     fn load_texture(
@@ -397,6 +712,27 @@ impl Context for SfmlContext<'_, '_, '_> {
         Ok(t)
     }
 
+    fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+    ) -> Result<Rc<Texture>> {
+        let mut rt = match RenderTexture::new(width, height) {
+            Some(rt) => rt,
+            None => return Err(anyhow::anyhow!("Failed to create RenderTexture")),
+        };
+        rt.set_smooth(options.smooth);
+        rt.clear(sfml::graphics::Color::TRANSPARENT);
+        rt.display();
+
+        let t = self
+            .globals
+            .add_texture(TextureSource::None, width, height, options);
+        self.native_render_targets.insert(t.id, rt);
+        Ok(t)
+    }
+
     fn drop_texture_if_unused(&mut self, texture: Rc<Texture>) -> bool {
         let id = texture.id;
         drop(texture);
@@ -409,6 +745,7 @@ impl Context for SfmlContext<'_, '_, '_> {
 
         if unused {
             self.native_textures.remove(&id);
+            self.native_render_targets.remove(&id);
             self.globals.textures.remove(&id);
         }
         unused
@@ -417,6 +754,7 @@ impl Context for SfmlContext<'_, '_, '_> {
     fn drop_unused_textures(&mut self) {
         for t_id in self.globals.get_unused_texture_ids() {
             self.native_textures.remove(&t_id);
+            self.native_render_targets.remove(&t_id);
             self.globals.textures.remove(&t_id);
         }
     }
@@ -432,10 +770,11 @@ impl Context for SfmlContext<'_, '_, '_> {
     // resources - fonts
 
     fn load_ttf_file(&mut self, path: &Path, size: u16) -> Result<Rc<Font>> {
-        let sfml_font = match sfml::graphics::Font::from_file(asset_path(path).to_str().unwrap()) {
-            Some(f) => f,
-            None => return Err(anyhow::anyhow!("Failed to load font")),
-        };
+        let sfml_font =
+            match sfml::graphics::Font::from_file(asset_file_path(path)?.to_str().unwrap()) {
+                Some(f) => f,
+                None => return Err(anyhow::anyhow!("Failed to load font")),
+            };
         let f = self.globals.add_font(path, size);
         self.native_fonts.insert(f.id, sfml_font);
         Ok(f)
@@ -479,10 +818,11 @@ impl Context for SfmlContext<'_, '_, '_> {
     // resources - audio
 
     fn load_music_file(&mut self, path: &Path) -> Result<Rc<Music>> {
-        let sfml_music = match sfml::audio::Music::from_file(asset_path(path).to_str().unwrap()) {
-            Some(m) => m,
-            None => return Err(anyhow::anyhow!("Failed to load music")),
-        };
+        let sfml_music =
+            match sfml::audio::Music::from_file(asset_file_path(path)?.to_str().unwrap()) {
+                Some(m) => m,
+                None => return Err(anyhow::anyhow!("Failed to load music")),
+            };
 
         let m = self.globals.add_music(path);
         self.native_music.insert(m.id, sfml_music);
@@ -497,20 +837,18 @@ impl Context for SfmlContext<'_, '_, '_> {
     }
 
     fn load_sound_file(&mut self, path: &Path) -> Result<Rc<Sound>> {
-        let buf = sfml::audio::SoundBuffer::from_file(asset_path(path).to_str().unwrap())?;
-        let sfml_sound = super::audio::SfmlSound::new(std::rc::Rc::new(buf), |buf| {
-            let mut sound = sfml::audio::Sound::new();
-            sound.set_buffer(buf);
-            sound
-        });
+        let buf = sfml::audio::SoundBuffer::from_file(asset_file_path(path)?.to_str().unwrap())?;
 
         let s = self.globals.add_sound(path);
-        self.native_sounds.insert(s.id, sfml_sound);
+        self.native_sounds.insert(s.id, Rc::new(buf));
         Ok(s)
     }
 
     fn drop_unused_sounds(&mut self) {
         for s_id in self.globals.get_unused_sound_ids() {
+            for v in self.globals.voices.remove_sound(s_id) {
+                self.native_voices.remove(&v.voice);
+            }
             self.native_sounds.remove(&s_id);
             self.globals.sounds.remove(&s_id);
         }
@@ -6,26 +6,29 @@ mod color;
 mod context;
 mod input;
 mod rect;
+mod transform;
 mod vec;
 
 pub use context::*;
 
 use crate::{
+    audio::Voice,
     color::Color,
     conf::{Conf, WindowIcon},
     context::Context,
     gametime::FPSLimiter,
     globals::Globals,
     input::Event,
-    scene::{InitialScene, Scene, SceneResult},
+    scene::{InitialScene, SceneBuilder, SceneStack, StackResult},
 };
 use anyhow::Result;
 use sfml::{
-    graphics::{Font, RenderTarget, RenderWindow, Texture},
+    audio::SoundBuffer,
+    graphics::{Font, RenderTarget, RenderTexture, RenderWindow, Texture},
     window::{ContextSettings, Style, VideoMode},
     SfBox,
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
 
 fn make_window(conf: &Conf) -> Result<RenderWindow> {
     let display_mode = VideoMode::desktop_mode();
@@ -80,20 +83,19 @@ fn set_window_icon(window: &mut RenderWindow, icon: &WindowIcon) -> Result<()> {
     Ok(())
 }
 
-pub fn main_sfml(
-    mut conf: Conf,
-    scene_builder: &'static dyn Fn(&mut dyn Context) -> Result<Box<dyn Scene>>,
-) -> Result<()> {
+pub fn main_sfml(mut conf: Conf, scene_builder: &'static SceneBuilder) -> Result<()> {
     let mut native_textures: HashMap<usize, SfBox<Texture>> = HashMap::new();
+    let mut native_render_targets: HashMap<usize, RenderTexture> = HashMap::new();
     let mut native_fonts: HashMap<usize, SfBox<Font>> = HashMap::new();
     let mut native_music: HashMap<usize, sfml::audio::Music> = HashMap::new();
-    let mut native_sounds: HashMap<usize, self::audio::SfmlSound> = HashMap::new();
+    let mut native_sounds: HashMap<usize, Rc<SfBox<SoundBuffer>>> = HashMap::new();
+    let mut native_voices: HashMap<Voice, self::audio::SfmlSound> = HashMap::new();
     let mut current_music_id = None;
+    let mut previous_music_id = None;
 
     let mut events: Vec<Event> = Vec::new();
-    let mut scene: Box<dyn Scene> = Box::new(InitialScene::new(scene_builder));
-    let mut initial = true;
-    let mut globals = Globals::new(conf.logical_size);
+    let mut scenes = SceneStack::new(Box::new(InitialScene::new(scene_builder)));
+    let mut globals = Globals::new(conf.logical_size, &conf);
     let mut dt = 0.0;
 
     globals.view.set(conf.view);
@@ -110,15 +112,19 @@ pub fn main_sfml(
         let mut ctx = SfmlContext {
             globals: &mut globals,
             native_textures: &mut native_textures,
+            native_render_targets: &mut native_render_targets,
+            current_targets: Vec::new(),
             native_fonts: &mut native_fonts,
             native_music: &mut native_music,
             native_sounds: &mut native_sounds,
+            native_voices: &mut native_voices,
             window: &mut window,
             rebuild_window: false,
             conf: &mut conf,
             fps_limiter: FPSLimiter::new(),
             current_color: Color::BLACK.into(),
             current_music_id: &mut current_music_id,
+            previous_music_id: &mut previous_music_id,
         };
         ctx.fps_limiter.set_fps_limit(ctx.conf.fps_limit);
 
@@ -149,27 +155,18 @@ pub fn main_sfml(
                 ctx.globals.view.set_target_size(logical_size);
             }
 
-            let scene_result = scene.process(&mut ctx, dt, &events)?;
-
-            match scene_result {
-                SceneResult::Normal => {
+            match scenes.process(&mut ctx, dt, &events)? {
+                StackResult::Render => {
                     if ctx.globals.view.is_changed() {
                         ctx.recalc_sfml_view();
                     }
-                    scene.render(&mut ctx)?;
+                    scenes.render(&mut ctx)?;
                     ctx.window.display();
                     ctx.fps_limiter.tick();
                     dt = ctx.globals.time.tick();
                 }
-                SceneResult::Switch(next_scene) => {
-                    let mut prev_scene = scene;
-                    scene = next_scene;
-                    prev_scene.stop(&mut ctx)?;
-                    scene.start(&mut ctx, if !initial { Some(prev_scene) } else { None })?;
-                    ctx.drop_unused_resources();
-                    initial = false;
-                }
-                SceneResult::Quit => {
+                StackResult::Changed => {}
+                StackResult::Quit => {
                     break 'mainloop;
                 }
             }
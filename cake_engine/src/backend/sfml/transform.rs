@@ -0,0 +1,8 @@
+use crate::transform::Transform;
+
+impl Into<sfml::graphics::Transform> for Transform {
+    fn into(self) -> sfml::graphics::Transform {
+        let [a00, a01, a02, a10, a11, a12] = self.matrix();
+        sfml::graphics::Transform::new(a00, a01, a02, a10, a11, a12, 0.0, 0.0, 1.0)
+    }
+}
@@ -0,0 +1,740 @@
+use crate::{
+    audio::{Music, Sound, Voice},
+    backend::dummy::DummyAudio,
+    color::Color,
+    conf::Conf,
+    context::{Context, DrawTextureParams, PlaySoundParams},
+    font::Font,
+    gametime::GameTime,
+    globals::Globals,
+    hot_reload::HotReload,
+    image::Image,
+    input::Input,
+    rect::Rect,
+    texture::{Texture, TextureOptions, TextureSource},
+    transform::Transform,
+    vec::Vec2,
+    view::View,
+};
+use anyhow::Result;
+use std::{collections::HashMap, path::Path, rc::Rc};
+
+pub struct SoftwareContext {
+    pub conf: Conf,
+    globals: Globals,
+    framebuffer: Image,
+    fill_color: Color,
+    native_textures: HashMap<usize, Image>,
+    native_fonts: HashMap<usize, fontdue::Font>,
+    audio: DummyAudio,
+}
+
+impl SoftwareContext {
+    /// Создаёт контекст с буфером кадра размером `conf.logical_size`.
+    pub fn new(conf: &Conf) -> SoftwareContext {
+        let width = conf.logical_size.x.max(1.0) as u32;
+        let height = conf.logical_size.y.max(1.0) as u32;
+
+        SoftwareContext {
+            conf: conf.clone(),
+            globals: Globals::new(Vec2::new(width as f32, height as f32), conf),
+            framebuffer: Image::new(width, height),
+            fill_color: Color::BLACK,
+            native_textures: HashMap::new(),
+            native_fonts: HashMap::new(),
+            audio: DummyAudio::default(),
+        }
+    }
+
+    pub fn input_mut(&mut self) -> &mut Input {
+        &mut self.globals.input
+    }
+
+    /// Содержимое буфера кадра.
+    pub fn framebuffer(&self) -> &Image {
+        &self.framebuffer
+    }
+
+    /// Сохраняет буфер кадра в PNG-файл.
+    pub fn save_png(&self, path: &Path) -> Result<()> {
+        self.framebuffer.save_png(path)
+    }
+
+    /// Изменяет размер буфера кадра (как будто изменился размер окна). Содержимое буфера
+    /// при этом теряется.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.framebuffer = Image::new(width.max(1), height.max(1));
+        self.globals
+            .view
+            .set_target_size(Vec2::new(width as f32, height as f32));
+    }
+
+    fn load_image(source: &TextureSource) -> Result<Image> {
+        match source {
+            TextureSource::None => Err(anyhow::anyhow!("TextureSource is None")),
+            TextureSource::Data(data) => Image::from_png_bytes(data),
+            TextureSource::File(path) => {
+                Image::from_png_bytes(&crate::fs::read_asset_to_bytes(path)?)
+            }
+            TextureSource::LangFile(path) => {
+                Image::from_png_bytes(&crate::fs::read_lang_asset_to_bytes(path)?)
+            }
+        }
+    }
+
+    fn load_native_font(path: &Path) -> Result<fontdue::Font> {
+        let data = crate::fs::read_asset_to_bytes(path)?;
+        fontdue::Font::from_bytes(data, fontdue::FontSettings::default())
+            .map_err(|e| anyhow::anyhow!("Failed to load font: {}", e))
+    }
+
+    fn get_font(&self, font: &Font) -> Result<&fontdue::Font> {
+        match self.native_fonts.get(&font.id) {
+            Some(f) => Ok(f),
+            None => Err(anyhow::anyhow!("Font not loaded")),
+        }
+    }
+
+    fn line_metrics(&self, font: &Font) -> Result<fontdue::LineMetrics> {
+        match self
+            .get_font(font)?
+            .horizontal_line_metrics(font.size as f32)
+        {
+            Some(m) => Ok(m),
+            None => Err(anyhow::anyhow!("Font has no horizontal metrics")),
+        }
+    }
+
+    fn render_text(
+        &self,
+        text: &str,
+        font: &Font,
+        color: Color,
+        smooth: bool,
+    ) -> Result<Option<Image>> {
+        if text.is_empty() {
+            return Ok(None);
+        }
+
+        let size = self.get_text_size(text, font)?;
+        let (ascent, _) = self.get_font_metrics(font)?;
+        let native_font = self.get_font(font)?;
+        let px = font.size as f32;
+
+        let mut image = Image::new(size.x.ceil().max(1.0) as u32, size.y.ceil().max(1.0) as u32);
+        let mut pen = 0.0;
+        for c in text.chars() {
+            let (metrics, bitmap) = native_font.rasterize(c, px);
+
+            // ymin — расстояние от базовой линии до нижнего края глифа (вверх положительно)
+            let left = (pen + metrics.xmin as f32).round() as i32;
+            let top = (ascent - metrics.ymin as f32 - metrics.height as f32).round() as i32;
+
+            for gy in 0..metrics.height {
+                for gx in 0..metrics.width {
+                    let x = left + gx as i32;
+                    let y = top + gy as i32;
+                    if x < 0 || y < 0 || x >= image.width() as i32 || y >= image.height() as i32 {
+                        continue;
+                    }
+
+                    let mut coverage = bitmap[gy * metrics.width + gx];
+                    if !smooth {
+                        coverage = if coverage >= 128 { 255 } else { 0 };
+                    }
+                    let a = (coverage as u32 * color.a as u32 / 255) as u8;
+                    image.blend_pixel(x as u32, y as u32, Color::new(color.r, color.g, color.b, a));
+                }
+            }
+
+            pen += metrics.advance_width;
+        }
+
+        Ok(Some(image))
+    }
+
+    /// Рисует изображение в буфер кадра с преобразованиями как у `draw_texture_ex`.
+    fn draw_image(framebuffer: &mut Image, view: &View, image: &Image, params: &DrawTextureParams) {
+        let src = match params.src {
+            Some(src) => src,
+            None => Rect::new(0.0, 0.0, image.width() as f32, image.height() as f32),
+        };
+        let src_norm = src.normalized();
+        let (w, h) = (src_norm.width, src_norm.height);
+        if w == 0.0 || h == 0.0 || params.scale.x == 0.0 || params.scale.y == 0.0 {
+            return;
+        }
+
+        let (sin, cos) = params.rotation.to_radians().sin_cos();
+
+        // Точка внутри источника (в его системе координат) -> точка в буфере кадра
+        let to_target = |s: f32, t: f32| {
+            let qx = (s - params.origin.x * w) * params.scale.x;
+            let qy = (t - params.origin.y * h) * params.scale.y;
+            view.point_to_target(Vec2::new(
+                params.position.x + qx * cos - qy * sin,
+                params.position.y + qx * sin + qy * cos,
+            ))
+        };
+
+        let corners = [
+            to_target(0.0, 0.0),
+            to_target(w, 0.0),
+            to_target(0.0, h),
+            to_target(w, h),
+        ];
+        let min_x = corners.iter().map(|p| p.x).fold(f32::INFINITY, f32::min);
+        let max_x = corners
+            .iter()
+            .map(|p| p.x)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let min_y = corners.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+        let max_y = corners
+            .iter()
+            .map(|p| p.y)
+            .fold(f32::NEG_INFINITY, f32::max);
+
+        let x0 = min_x.floor().max(0.0) as u32;
+        let y0 = min_y.floor().max(0.0) as u32;
+        let x1 = (max_x.ceil().max(0.0) as u32).min(framebuffer.width());
+        let y1 = (max_y.ceil().max(0.0) as u32).min(framebuffer.height());
+
+        let flip_x = src.width < 0.0;
+        let flip_y = src.height < 0.0;
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                // Обратное преобразование центра пикселя в точку внутри источника
+                let p = view.point_from_target(Vec2::new(x as f32 + 0.5, y as f32 + 0.5));
+                let dx = p.x - params.position.x;
+                let dy = p.y - params.position.y;
+                let qx = dx * cos + dy * sin;
+                let qy = -dx * sin + dy * cos;
+                let s = qx / params.scale.x + params.origin.x * w;
+                let t = qy / params.scale.y + params.origin.y * h;
+                if s < 0.0 || s >= w || t < 0.0 || t >= h {
+                    continue;
+                }
+
+                let s = if flip_x { w - s } else { s };
+                let t = if flip_y { h - t } else { t };
+                let tx = (src_norm.x + s).floor();
+                let ty = (src_norm.y + t).floor();
+                if tx < 0.0 || ty < 0.0 || tx >= image.width() as f32 || ty >= image.height() as f32
+                {
+                    continue;
+                }
+
+                let texel = image.get_pixel(tx as u32, ty as u32);
+                framebuffer.blend_pixel(x, y, modulate(texel, params.color));
+            }
+        }
+    }
+}
+
+fn modulate(a: Color, b: Color) -> Color {
+    let m = |x: u8, y: u8| ((x as u32 * y as u32 + 127) / 255) as u8;
+    Color::new(m(a.r, b.r), m(a.g, b.g), m(a.b, b.b), m(a.a, b.a))
+}
+
+impl Context for SoftwareContext {
+    fn get_backend_name(&self) -> &'static str {
+        "Software"
+    }
+
+    // time
+
+    fn time(&self) -> &GameTime {
+        &self.globals.time
+    }
+
+    fn time_mut(&mut self) -> &mut GameTime {
+        &mut self.globals.time
+    }
+
+    // input
+
+    fn input(&self) -> &Input {
+        &self.globals.input
+    }
+
+    // view
+
+    fn view(&self) -> &View {
+        &self.globals.view
+    }
+
+    fn view_mut(&mut self) -> &mut View {
+        &mut self.globals.view
+    }
+
+    // transform
+
+    fn push_transform(&mut self, transform: Transform) {
+        self.globals.push_transform(transform);
+    }
+
+    fn pop_transform(&mut self) -> Result<()> {
+        self.globals.pop_transform()
+    }
+
+    fn get_transform(&self) -> Transform {
+        self.globals.get_transform()
+    }
+
+    // window
+
+    fn get_physical_window_size(&self) -> (u32, u32) {
+        (self.framebuffer.width(), self.framebuffer.height())
+    }
+
+    fn get_fullscreen(&self) -> bool {
+        self.conf.fullscreen
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) -> Result<()> {
+        self.conf.fullscreen = fullscreen;
+        Ok(())
+    }
+
+    fn get_vsync(&self) -> bool {
+        self.conf.vsync
+    }
+
+    fn set_vsync(&mut self, vsync: bool) -> Result<()> {
+        self.conf.vsync = vsync;
+        Ok(())
+    }
+
+    fn set_fps_limit(&mut self, _value: f32) {}
+
+    fn get_mouse_cursor_visibility(&self) -> bool {
+        self.conf.mouse_cursor_visible
+    }
+
+    fn set_mouse_cursor_visibility(&mut self, visible: bool) -> Result<()> {
+        self.conf.mouse_cursor_visible = visible;
+        Ok(())
+    }
+
+    fn is_simulating_mouse_with_touch(&self) -> bool {
+        self.conf.simulate_mouse_with_touch
+    }
+
+    fn simulate_mouse_with_touch(&mut self, enabled: bool) {
+        self.conf.simulate_mouse_with_touch = enabled;
+    }
+
+    // drawing
+
+    fn set_fill_color(&mut self, color: Color) {
+        self.fill_color = color;
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.framebuffer.fill(self.fill_color);
+        Ok(())
+    }
+
+    fn fill_rect(&mut self, rect: Rect) -> Result<()> {
+        let transform = self.globals.get_transform();
+        if !transform.is_axis_aligned() {
+            // Повёрнутый прямоугольник рисуем многоугольником
+            return self.fill_polygon(&rect.get_corners());
+        }
+        let r = self
+            .globals
+            .view
+            .rect_to_target(transform.apply_to_rect(rect))
+            .normalized();
+        if r.is_degenerate() {
+            return Ok(());
+        }
+
+        // Закрашиваем пиксели, центры которых попали в прямоугольник
+        let x0 = (r.x - 0.5).ceil().max(0.0) as u32;
+        let y0 = (r.y - 0.5).ceil().max(0.0) as u32;
+        let x1 = ((r.x + r.width - 0.5).ceil().max(0.0) as u32).min(self.framebuffer.width());
+        let y1 = ((r.y + r.height - 0.5).ceil().max(0.0) as u32).min(self.framebuffer.height());
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                self.framebuffer.blend_pixel(x, y, self.fill_color);
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_polygon(&mut self, points: &[Vec2]) -> Result<()> {
+        if points.len() < 3 {
+            return Ok(());
+        }
+        let transform = self.globals.get_transform();
+        let points: Vec<Vec2> = points
+            .iter()
+            .map(|p| self.globals.view.point_to_target(transform.apply(*p)))
+            .collect();
+
+        let min_y = points.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+        let max_y = points.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max);
+        let y0 = (min_y - 0.5).ceil().max(0.0) as u32;
+        let y1 = ((max_y - 0.5).ceil().max(0.0) as u32).min(self.framebuffer.height());
+
+        // Построчно закрашиваем пиксели, центры которых попали внутрь многоугольника
+        let mut xs = Vec::new();
+        for y in y0..y1 {
+            let cy = y as f32 + 0.5;
+            xs.clear();
+            for i in 0..points.len() {
+                let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                // Пересечение считаем от нижней по y вершины, чтобы общее ребро
+                // соседних многоугольников давало одинаковый x независимо от обхода
+                let (a, b) = if a.y <= b.y { (a, b) } else { (b, a) };
+                if (a.y <= cy) != (b.y <= cy) {
+                    xs.push(a.x + (cy - a.y) / (b.y - a.y) * (b.x - a.x));
+                }
+            }
+            xs.sort_by(|a, b| a.total_cmp(b));
+            for pair in xs.chunks_exact(2) {
+                let x0 = (pair[0] - 0.5).ceil().max(0.0) as u32;
+                let x1 = ((pair[1] - 0.5).ceil().max(0.0) as u32).min(self.framebuffer.width());
+                for x in x0..x1 {
+                    self.framebuffer.blend_pixel(x, y, self.fill_color);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_texture_ex(&mut self, texture: &Texture, params: DrawTextureParams) -> Result<()> {
+        let image = match self.native_textures.get(&texture.id) {
+            Some(i) => i,
+            None => return Err(anyhow::anyhow!("Texture not loaded")),
+        };
+        let params = self.globals.get_transform().apply_to_params(&params);
+        SoftwareContext::draw_image(&mut self.framebuffer, &self.globals.view, image, &params);
+        Ok(())
+    }
+
+    fn draw_texture_batch(
+        &mut self,
+        texture: &Texture,
+        params: &[DrawTextureParams],
+    ) -> Result<()> {
+        let image = match self.native_textures.get(&texture.id) {
+            Some(i) => i,
+            None => return Err(anyhow::anyhow!("Texture not loaded")),
+        };
+        let transform = self.globals.get_transform();
+        for p in params {
+            let p = transform.apply_to_params(p);
+            SoftwareContext::draw_image(&mut self.framebuffer, &self.globals.view, image, &p);
+        }
+        Ok(())
+    }
+
+    fn draw_text_to_texture(
+        &mut self,
+        text: &str,
+        font: &Font,
+        color: Color,
+        smooth: bool,
+    ) -> Result<Option<Rc<Texture>>> {
+        let image = match self.render_text(text, font, color, smooth)? {
+            Some(i) => i,
+            None => return Ok(None),
+        };
+
+        let t = self.globals.add_texture(
+            TextureSource::None,
+            image.width(),
+            image.height(),
+            TextureOptions { smooth },
+        );
+        self.native_textures.insert(t.id, image);
+        Ok(Some(t))
+    }
+
+    fn draw_text(
+        &mut self,
+        text: &str,
+        font: &Font,
+        color: Color,
+        smooth: bool,
+        position: Vec2,
+        scale: Vec2,
+    ) -> Result<()> {
+        let image = match self.render_text(text, font, color, smooth)? {
+            Some(i) => i,
+            None => return Ok(()),
+        };
+
+        let transform = self.globals.get_transform();
+        let params = transform.apply_to_params(&DrawTextureParams {
+            position,
+            scale,
+            ..Default::default()
+        });
+        SoftwareContext::draw_image(&mut self.framebuffer, &self.globals.view, &image, &params);
+        Ok(())
+    }
+
+    fn capture_screenshot(&mut self) -> Result<Image> {
+        Ok(self.framebuffer.clone())
+    }
+
+    fn begin_target(&mut self, target: &Texture) -> Result<()> {
+        if !self.native_textures.contains_key(&target.id) {
+            return Err(anyhow::anyhow!("Texture not loaded"));
+        }
+        self.globals.begin_target(target)?;
+        // Пока идёт рисование в текстуру, кадр окна полежит на её месте
+        let image = self.native_textures.get_mut(&target.id).unwrap();
+        std::mem::swap(&mut self.framebuffer, image);
+        Ok(())
+    }
+
+    fn end_target(&mut self) -> Result<()> {
+        let id = self.globals.end_target()?;
+        if let Some(image) = self.native_textures.get_mut(&id) {
+            std::mem::swap(&mut self.framebuffer, image);
+        }
+        Ok(())
+    }
+
+    // audio
+
+    fn play_music(&mut self, music: &Music, _volume: f32, _looping: bool) -> Result<()> {
+        self.audio.play_music(music);
+        Ok(())
+    }
+
+    fn set_music_volume(&mut self, _volume: f32) -> Result<()> {
+        Ok(())
+    }
+
+    fn stop_music(&mut self) -> Result<()> {
+        self.audio.stop_music();
+        Ok(())
+    }
+
+    fn play_music_keeping_previous(
+        &mut self,
+        music: &Music,
+        _volume: f32,
+        _looping: bool,
+    ) -> Result<()> {
+        self.audio.play_music_keeping_previous(music);
+        Ok(())
+    }
+
+    fn set_previous_music_volume(&mut self, _volume: f32) -> Result<()> {
+        Ok(())
+    }
+
+    fn stop_previous_music(&mut self) -> Result<()> {
+        self.audio.stop_previous_music();
+        Ok(())
+    }
+
+    fn get_playing_music(&self) -> Result<Option<Rc<Music>>> {
+        Ok(self.audio.get_playing_music(&self.globals))
+    }
+
+    fn play_sound_ex(&mut self, sound: &Sound, params: PlaySoundParams) -> Result<Voice> {
+        Ok(self
+            .audio
+            .play_sound_ex(&mut self.globals.voices, sound, params))
+    }
+
+    fn stop_sound(&mut self, sound: &Sound) -> Result<()> {
+        self.globals.voices.remove_sound(sound.id);
+        Ok(())
+    }
+
+    fn stop_voice(&mut self, voice: Voice) -> Result<()> {
+        self.globals.voices.remove(voice);
+        Ok(())
+    }
+
+    fn set_voice_volume(&mut self, voice: Voice, volume: f32) -> Result<()> {
+        self.audio
+            .update_voice(&mut self.globals.voices, voice, |p| p.volume = volume);
+        Ok(())
+    }
+
+    fn set_voice_looping(&mut self, voice: Voice, looping: bool) -> Result<()> {
+        self.audio
+            .update_voice(&mut self.globals.voices, voice, |p| p.looping = looping);
+        Ok(())
+    }
+
+    fn set_voice_pan(&mut self, voice: Voice, pan: f32) -> Result<()> {
+        self.audio
+            .update_voice(&mut self.globals.voices, voice, |p| p.pan = pan);
+        Ok(())
+    }
+
+    fn is_voice_playing(&self, voice: Voice) -> bool {
+        self.audio.is_voice_playing(&self.globals.voices, voice)
+    }
+
+    // resources - hot reload
+
+    fn hot_reload(&self) -> &HotReload {
+        &self.globals.hot_reload
+    }
+
+    fn hot_reload_mut(&mut self) -> &mut HotReload {
+        &mut self.globals.hot_reload
+    }
+
+    fn reload_changed_resources(&mut self) -> Result<()> {
+        let changed = self.globals.poll_hot_reload();
+        for t in changed.textures {
+            match SoftwareContext::load_image(&t.source) {
+                Ok(image) => {
+                    self.native_textures.insert(t.id, image);
+                }
+                Err(e) => {
+                    crate::log::error!("Failed to reload texture {:?}: {:?}", t.source, e);
+                }
+            }
+        }
+        for f in changed.fonts {
+            match SoftwareContext::load_native_font(&f.path) {
+                Ok(native_font) => {
+                    self.native_fonts.insert(f.id, native_font);
+                }
+                Err(e) => {
+                    crate::log::error!("Failed to reload font {:?}: {:?}", f.path, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // resources - textures
+
+    fn is_texture_valid(&self, texture: &Texture) -> bool {
+        self.native_textures.contains_key(&texture.id)
+    }
+
+    fn load_texture(
+        &mut self,
+        source: TextureSource,
+        options: TextureOptions,
+    ) -> Result<Rc<Texture>> {
+        let image = SoftwareContext::load_image(&source)?;
+        let t = self
+            .globals
+            .add_texture(source, image.width(), image.height(), options);
+        self.native_textures.insert(t.id, image);
+        Ok(t)
+    }
+
+    fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+    ) -> Result<Rc<Texture>> {
+        let t = self
+            .globals
+            .add_texture(TextureSource::None, width, height, options);
+        self.native_textures.insert(t.id, Image::new(width, height));
+        Ok(t)
+    }
+
+    fn drop_texture_if_unused(&mut self, texture: Rc<Texture>) -> bool {
+        let id = texture.id;
+        drop(texture);
+
+        let unused = if let Some(t) = self.globals.textures.get(&id) {
+            Rc::strong_count(t) < 2
+        } else {
+            true
+        };
+
+        if unused {
+            self.native_textures.remove(&id);
+            self.globals.textures.remove(&id);
+        }
+        unused
+    }
+
+    fn drop_unused_textures(&mut self) {
+        for t_id in self.globals.get_unused_texture_ids() {
+            self.native_textures.remove(&t_id);
+            self.globals.textures.remove(&t_id);
+        }
+    }
+
+    fn reload_lang_textures(&mut self) -> Result<()> {
+        for t in self.globals.get_lang_textures() {
+            let image = SoftwareContext::load_image(&t.source)?;
+            self.native_textures.insert(t.id, image);
+        }
+        Ok(())
+    }
+
+    // resources - fonts
+
+    fn load_ttf_file(&mut self, path: &Path, size: u16) -> Result<Rc<Font>> {
+        let native_font = SoftwareContext::load_native_font(path)?;
+        let f = self.globals.add_font(path, size);
+        self.native_fonts.insert(f.id, native_font);
+        Ok(f)
+    }
+
+    fn drop_unused_fonts(&mut self) {
+        for f_id in self.globals.get_unused_font_ids() {
+            self.native_fonts.remove(&f_id);
+            self.globals.fonts.remove(&f_id);
+        }
+    }
+
+    fn get_text_size(&self, text: &str, font: &Font) -> Result<Vec2> {
+        let native_font = self.get_font(font)?;
+        let width: f32 = text
+            .chars()
+            .map(|c| native_font.metrics(c, font.size as f32).advance_width)
+            .sum();
+        let (ascent, descent) = self.get_font_metrics(font)?;
+        Ok(Vec2::new(width.ceil(), (ascent + descent).ceil()))
+    }
+
+    fn get_font_metrics(&self, font: &Font) -> Result<(f32, f32)> {
+        let m = self.line_metrics(font)?;
+        Ok((m.ascent.ceil(), (-m.descent).ceil()))
+    }
+
+    fn get_font_line_height(&self, font: &Font) -> Result<f32> {
+        Ok(self.line_metrics(font)?.new_line_size.ceil())
+    }
+
+    // resources - audio
+
+    fn load_music_file(&mut self, path: &Path) -> Result<Rc<Music>> {
+        let m = self.globals.add_music(path);
+        Ok(m)
+    }
+
+    fn drop_unused_music(&mut self) {
+        for m_id in self.globals.get_unused_music_ids() {
+            self.globals.music.remove(&m_id);
+        }
+    }
+
+    fn load_sound_file(&mut self, path: &Path) -> Result<Rc<Sound>> {
+        let s = self.globals.add_sound(path);
+        Ok(s)
+    }
+
+    fn drop_unused_sounds(&mut self) {
+        for s_id in self.globals.get_unused_sound_ids() {
+            self.globals.voices.remove_sound(s_id);
+            self.globals.sounds.remove(&s_id);
+        }
+    }
+}
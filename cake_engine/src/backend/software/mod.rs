@@ -0,0 +1,24 @@
+//! Программный бэкенд.
+//!
+//! Окна не создаёт, а рисует всё в буфер кадра в памяти, который потом можно прочитать
+//! или сохранить в PNG. Пригодится там, где нет видеокарты и дисплея, — например, для тестов
+//! на сервере сборки.
+//!
+//! Поддерживаются текстуры из PNG, `fill_rect` и `draw_texture_ex` со всеми параметрами, а также
+//! текст из TTF-шрифтов. Сглаживание текстур не поддерживается (пиксели берутся как есть), звука
+//! тоже нет.
+//!
+//! Используется как обычный контекст, но главного цикла у бэкенда нет — вызывать методы сцены
+//! нужно самостоятельно:
+//!
+//! ```ignore
+//! let mut ctx = SoftwareContext::new(&conf);
+//! scene.process(&mut ctx, 1.0 / 60.0, &[])?;
+//! scene.render(&mut ctx)?;
+//! ctx.save_png(Path::new("frame.png"))?;
+//! ```
+
+mod context;
+
+pub use self::context::*;
+pub use crate::image::Image;
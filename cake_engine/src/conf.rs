@@ -1,6 +1,6 @@
 //! Параметры, используемые при инициализации игры.
 
-use crate::{rect::Rect, vec::Vec2};
+use crate::{audio::VoiceStealing, rect::Rect, vec::Vec2};
 use std::path::PathBuf;
 
 /// Пути к значкам 16x16, 32x32 и 64x64 соответственно.
@@ -43,6 +43,13 @@ pub struct Conf {
     /// Преобразовывать события касания в события мыши (сами события касания при этом тоже
     /// остаются).
     pub simulate_mouse_with_touch: bool,
+    /// Сколько звуков может играть одновременно (музыка не считается).
+    pub max_voices: usize,
+    /// Что делать, когда все голоса заняты.
+    pub voice_stealing: VoiceStealing,
+    /// Следить за файлами загруженных ассетов и перезагружать изменившиеся (для разработки,
+    /// подробнее в [документации модуля](crate::hot_reload)).
+    pub hot_reload: bool,
 }
 
 impl Default for Conf {
@@ -58,6 +65,9 @@ impl Default for Conf {
             fps_limit: 0.0,
             mouse_cursor_visible: true,
             simulate_mouse_with_touch: true,
+            max_voices: 16,
+            voice_stealing: VoiceStealing::Oldest,
+            hot_reload: false,
         }
     }
 }
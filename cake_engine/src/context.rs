@@ -13,13 +13,16 @@
 //! Пример взаимодействия с контекстом смотрите в документации [`Scene`](crate::scene).
 
 use crate::{
-    audio::{Music, Sound},
+    audio::{Music, Sound, Voice},
     color::Color,
     font::Font,
     gametime::GameTime,
+    hot_reload::HotReload,
+    image::Image,
     input::Input,
     rect::Rect,
     texture::{Texture, TextureOptions, TextureSource},
+    transform::Transform,
     vec::Vec2,
     view::View,
 };
@@ -27,6 +30,7 @@ use anyhow::Result;
 use std::{path::Path, rc::Rc};
 
 /// Параметры рисования текстуры.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct DrawTextureParams {
     /// Источник — какую часть текстуры рисовать. Если не указано, то рисуется вся текстура.
     /// Можно указать отрицательные ширину и/или высоту — тогда текстура будет перевёрнута,
@@ -64,6 +68,62 @@ impl Default for DrawTextureParams {
     }
 }
 
+impl DrawTextureParams {
+    /// Углы рисуемой области текстуры по часовой стрелке, начиная с левого верхнего: позиция
+    /// в координатах игры и соответствующая ей точка на текстуре в пикселях. Нужно бэкендам,
+    /// которые рисуют текстуры треугольниками.
+    #[allow(dead_code)] // используется не всеми бэкендами
+    pub(crate) fn corners(&self, texture: &Texture) -> [(Vec2, Vec2); 4] {
+        let src = match self.src {
+            Some(src) => src,
+            None => texture.rect(),
+        };
+        let width = src.width.abs() * self.scale.x;
+        let height = src.height.abs() * self.scale.y;
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+
+        [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|(u, v)| {
+            let x = (u - self.origin.x) * width;
+            let y = (v - self.origin.y) * height;
+            let position = Vec2::new(
+                self.position.x + x * cos - y * sin,
+                self.position.y + x * sin + y * cos,
+            );
+            // Отрицательный размер источника сам собой переворачивает текстуру
+            let tex_coords = Vec2::new(src.x + u * src.width, src.y + v * src.height);
+            (position, tex_coords)
+        })
+    }
+}
+
+/// Параметры воспроизведения звука.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlaySoundParams {
+    /// Громкость от 0 до 1.
+    pub volume: f32,
+    /// Повторять ли звук бесконечно, пока его не остановят.
+    pub looping: bool,
+    /// Положение в стерео: -1.0 — только левый канал, 1.0 — только правый, 0.0 — по центру.
+    ///
+    /// В SFML панорама работает только для моно-звуков. Macroquad её не поддерживает.
+    pub pan: f32,
+    /// Скорость воспроизведения: 2.0 — в два раза быстрее и на октаву выше, 0.5 — наоборот.
+    ///
+    /// Macroquad её не поддерживает.
+    pub pitch: f32,
+}
+
+impl Default for PlaySoundParams {
+    fn default() -> Self {
+        PlaySoundParams {
+            volume: 1.0,
+            looping: false,
+            pan: 0.0,
+            pitch: 1.0,
+        }
+    }
+}
+
 /// Контекст хранит в себе всё состояние игры, и через него осуществляется всё взаимодействие
 /// с движком.
 ///
@@ -95,6 +155,25 @@ pub trait Context {
     /// вам изменить эту самую систему координат.
     fn view_mut(&mut self) -> &mut View;
 
+    // transform
+
+    /// Добавляет [преобразование](crate::transform) в стек. Оно объединяется с текущим
+    /// (сначала применяется новое, потом то, что уже было в стеке) и действует на всё
+    /// рисование, включая `Label`, `Sprite` и `Button`, до парного вызова
+    /// [`pop_transform`](Self::pop_transform). Применяется в координатах игры, до перевода
+    /// через [`view`](Self::view); на очистку окна и события ввода не влияет.
+    ///
+    /// Текстуры не умеют наклоняться, поэтому поворот вместе с неравномерным масштабированием
+    /// большинство бэкендов рисует приблизительно. Пока идёт рисование в текстуру, действует
+    /// отдельный пустой стек.
+    fn push_transform(&mut self, transform: Transform);
+
+    /// Убирает из стека последнее преобразование. Если стек пуст, возвращает ошибку.
+    fn pop_transform(&mut self) -> Result<()>;
+
+    /// Текущее преобразование — весь стек, объединённый в одно.
+    fn get_transform(&self) -> Transform;
+
     // window
 
     /// Возвращает коэффициент масштабирования экрана. Если HiDPI отсутствует или
@@ -156,6 +235,88 @@ pub trait Context {
     /// и/или высота.
     fn fill_rect(&mut self, rect: Rect) -> Result<()>;
 
+    /// Рисует выпуклый многоугольник текущим цветом рисования. Точки можно перечислять как
+    /// по часовой стрелке, так и против. Если точек меньше трёх, ничего не рисует.
+    ///
+    /// Остальные фигуры по умолчанию рисуются через этот метод.
+    fn fill_polygon(&mut self, points: &[Vec2]) -> Result<()>;
+
+    /// Рисует отрезок текущим цветом рисования. Толщина указывается в координатах игры.
+    fn draw_line(&mut self, from: Vec2, to: Vec2, thickness: f32) -> Result<()> {
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len <= 0.0 {
+            return Ok(());
+        }
+        // Сдвиг на половину толщины перпендикулярно отрезку
+        let nx = -dy / len * thickness / 2.0;
+        let ny = dx / len * thickness / 2.0;
+        self.fill_polygon(&[
+            Vec2::new(from.x + nx, from.y + ny),
+            Vec2::new(to.x + nx, to.y + ny),
+            Vec2::new(to.x - nx, to.y - ny),
+            Vec2::new(from.x - nx, from.y - ny),
+        ])
+    }
+
+    /// Рисует закрашенный круг текущим цветом рисования.
+    fn fill_circle(&mut self, center: Vec2, radius: f32) -> Result<()> {
+        let points = arc_points(center, radius, 0.0, 360.0, circle_segments(radius));
+        self.fill_polygon(&points[1..])
+    }
+
+    /// Рисует окружность текущим цветом рисования. Линия толщиной `thickness` рисуется внутрь
+    /// от `radius`.
+    fn draw_circle(&mut self, center: Vec2, radius: f32, thickness: f32) -> Result<()> {
+        let segments = circle_segments(radius);
+        let outer = arc_points(center, radius, 0.0, 360.0, segments);
+        let inner = arc_points(center, (radius - thickness).max(0.0), 0.0, 360.0, segments);
+        for i in 0..segments {
+            // Последний отрезок замыкаем на первую точку, а не на точку для 360°,
+            // чтобы у соседних отрезков рёбра совпадали точно
+            let j = (i + 1) % segments;
+            self.fill_polygon(&[outer[i], outer[j], inner[j], inner[i]])?;
+        }
+        Ok(())
+    }
+
+    /// Рисует рамку прямоугольника текущим цветом рисования. Линия толщиной `thickness`
+    /// рисуется внутрь прямоугольника.
+    fn draw_rect_outline(&mut self, rect: Rect, thickness: f32) -> Result<()> {
+        let r = rect.normalized();
+        let t = thickness.min(r.width / 2.0).min(r.height / 2.0).max(0.0);
+        self.fill_rect(Rect::new(r.x, r.y, r.width, t))?;
+        self.fill_rect(Rect::new(r.x, r.y + r.height - t, r.width, t))?;
+        self.fill_rect(Rect::new(r.x, r.y + t, t, r.height - t * 2.0))?;
+        self.fill_rect(Rect::new(r.x + r.width - t, r.y + t, t, r.height - t * 2.0))?;
+        Ok(())
+    }
+
+    /// Рисует прямоугольник со скруглёнными углами текущим цветом рисования. Радиус
+    /// скругления не может быть больше половины ширины или высоты.
+    fn fill_rounded_rect(&mut self, rect: Rect, radius: f32) -> Result<()> {
+        let r = rect.normalized();
+        let radius = radius.min(r.width / 2.0).min(r.height / 2.0);
+        if radius <= 0.0 {
+            return self.fill_rect(r);
+        }
+        let segments = circle_segments(radius) / 4;
+        let corners = [
+            (
+                Vec2::new(r.x + r.width - radius, r.y + r.height - radius),
+                0.0,
+            ),
+            (Vec2::new(r.x + radius, r.y + r.height - radius), 90.0),
+            (Vec2::new(r.x + radius, r.y + radius), 180.0),
+            (Vec2::new(r.x + r.width - radius, r.y + radius), 270.0),
+        ];
+        let mut points = Vec::with_capacity((segments + 1) * 4);
+        for (center, start) in corners {
+            points.extend(arc_points(center, radius, start, start + 90.0, segments));
+        }
+        self.fill_polygon(&points)
+    }
+
     /// Рисует текстуру в её реальном размере.
     fn draw_texture(&mut self, texture: &Texture, position: Vec2, origin: Vec2) -> Result<()> {
         let params = DrawTextureParams {
@@ -169,6 +330,21 @@ pub trait Context {
     /// Рисует текстуру с дополнительными параметрами.
     fn draw_texture_ex(&mut self, texture: &Texture, params: DrawTextureParams) -> Result<()>;
 
+    /// Рисует одну и ту же текстуру много раз. Результат тот же, что и у вызова
+    /// [`draw_texture_ex`](Self::draw_texture_ex) для каждого элемента `params` по порядку,
+    /// но бэкенды по возможности отправляют всё одним вызовом отрисовки, что заметно быстрее
+    /// для тысяч спрайтов.
+    fn draw_texture_batch(
+        &mut self,
+        texture: &Texture,
+        params: &[DrawTextureParams],
+    ) -> Result<()> {
+        for p in params {
+            self.draw_texture_ex(texture, *p)?;
+        }
+        Ok(())
+    }
+
     /// Рисует строку текста в текстуру. Если не поддерживается бэкендом, возвращает `None`.
     fn draw_text_to_texture(
         &mut self,
@@ -189,25 +365,106 @@ pub trait Context {
         scale: Vec2,
     ) -> Result<()>;
 
+    /// Возвращает то, что уже нарисовано в текущем кадре, в виде RGBA-изображения в реальном
+    /// размере окна. После показа кадра содержимое окна зависит от бэкенда, поэтому вызывать
+    /// лучше в конце `render`.
+    fn capture_screenshot(&mut self) -> Result<Image>;
+
+    /// Перенаправляет всё дальнейшее рисование в текстуру, созданную через
+    /// [`create_render_target`](Self::create_render_target), до вызова
+    /// [`end_target`](Self::end_target). Пока рисование идёт в текстуру, координаты совпадают
+    /// с её пикселями, а [`view`](Self::view) возвращает систему координат текстуры.
+    ///
+    /// Рисование может быть вложенным: пока идёт рисование в одну текстуру, можно начать
+    /// рисовать в другую, а `end_target` вернёт его в предыдущую. Рисовать текстуру в саму
+    /// себя, в том числе начинать рисование в текстуру, которая уже есть среди вложенных,
+    /// нельзя. Вызывать `begin_target` и `end_target` нужно парами в пределах одного `render`.
+    fn begin_target(&mut self, target: &Texture) -> Result<()>;
+
+    /// Завершает рисование в текстуру и возвращает его туда, куда оно шло до
+    /// [`begin_target`](Self::begin_target): во внешнюю текстуру или в окно.
+    fn end_target(&mut self) -> Result<()>;
+
     // audio
 
-    /// Воспроизводит музыку. Одновременно может играть только одна музыка; если уже играет
-    /// какая-то музыка, она будет остановлена.
+    /// Воспроизводит музыку. Если уже играет какая-то музыка, она будет остановлена (для
+    /// плавного перехода есть [`play_music_keeping_previous`](Self::play_music_keeping_previous)).
     fn play_music(&mut self, music: &Music, volume: f32, looping: bool) -> Result<()>;
 
-    /// Останавливает музыку.
+    /// Изменяет громкость уже играющей музыки (от 0 до 1), не перезапуская её. Если музыка
+    /// не играет, ничего не делает.
+    fn set_music_volume(&mut self, volume: f32) -> Result<()>;
+
+    /// Останавливает музыку (и текущую, и предыдущую).
     fn stop_music(&mut self) -> Result<()>;
 
+    /// Воспроизводит музыку, не останавливая текущую: текущая становится предыдущей и играет
+    /// дальше, пока её не остановят через [`stop_previous_music`](Self::stop_previous_music).
+    /// Если предыдущая музыка уже была, она останавливается. Нужно для плавного перехода
+    /// между треками, см. [`MusicManager`](crate::music::MusicManager).
+    ///
+    /// В SDL одновременно может играть только одна музыка, поэтому там текущая музыка сначала
+    /// затихает, а новая плавно начинается после неё; громкость предыдущей музыки при этом
+    /// не настраивается.
+    fn play_music_keeping_previous(
+        &mut self,
+        music: &Music,
+        volume: f32,
+        looping: bool,
+    ) -> Result<()>;
+
+    /// Изменяет громкость предыдущей музыки (от 0 до 1).
+    fn set_previous_music_volume(&mut self, volume: f32) -> Result<()>;
+
+    /// Останавливает предыдущую музыку.
+    fn stop_previous_music(&mut self) -> Result<()>;
+
     /// Возвращает текущую воспроизводимую музыку.
     fn get_playing_music(&self) -> Result<Option<Rc<Music>>>;
 
-    /// Воспроизводит звук. Можно одновременно воспроизводить несколько звуков, но эти звуки
-    /// должны быть разными.
-    fn play_sound(&mut self, sound: &Sound, volume: f32, looping: bool) -> Result<()>;
+    /// Воспроизводит звук и возвращает [голос](Voice) — дескриптор именно этого
+    /// воспроизведения. Один и тот же звук можно воспроизводить несколько раз внахлёст.
+    ///
+    /// Одновременно может звучать не больше [`Conf::max_voices`](crate::conf::Conf::max_voices)
+    /// голосов; что будет, когда они закончатся, задаёт
+    /// [`Conf::voice_stealing`](crate::conf::Conf::voice_stealing).
+    fn play_sound(&mut self, sound: &Sound, volume: f32, looping: bool) -> Result<Voice> {
+        let params = PlaySoundParams {
+            volume,
+            looping,
+            ..Default::default()
+        };
+        self.play_sound_ex(sound, params)
+    }
 
-    /// Останавливает звук.
+    /// Воспроизводит звук с дополнительными параметрами.
+    fn play_sound_ex(&mut self, sound: &Sound, params: PlaySoundParams) -> Result<Voice>;
+
+    /// Останавливает все голоса, воспроизводящие этот звук.
     fn stop_sound(&mut self, sound: &Sound) -> Result<()>;
 
+    /// Останавливает голос.
+    fn stop_voice(&mut self, voice: Voice) -> Result<()>;
+
+    /// Изменяет громкость голоса (от 0 до 1).
+    ///
+    /// В macroquad громкость задаётся для звука целиком, поэтому изменится у всех его голосов.
+    fn set_voice_volume(&mut self, voice: Voice, volume: f32) -> Result<()>;
+
+    /// Включает или выключает повтор голоса.
+    ///
+    /// SDL и macroquad не умеют менять это у уже играющего звука, поэтому на них голос
+    /// перезапускается с начала.
+    fn set_voice_looping(&mut self, voice: Voice, looping: bool) -> Result<()>;
+
+    /// Изменяет положение голоса в стерео (от -1 до 1), например, чтобы звук следовал
+    /// за своим источником на экране.
+    fn set_voice_pan(&mut self, voice: Voice, pan: f32) -> Result<()>;
+
+    /// Звучит ли ещё голос. В macroquad узнать, доиграл ли звук, нельзя, поэтому там голос
+    /// считается звучащим, пока его не остановят или не вытеснят другим.
+    fn is_voice_playing(&self, voice: Voice) -> bool;
+
     // resources
 
     /// Выгружает все неиспользуемые ресурсы (текстуры, шрифты, музыку и звуки).
@@ -222,6 +479,18 @@ pub trait Context {
         self.drop_unused_sounds();
     }
 
+    /// Состояние [горячей перезагрузки](crate::hot_reload) ассетов.
+    fn hot_reload(&self) -> &HotReload;
+
+    /// Мутабельная ссылка на состояние горячей перезагрузки, через которую можно добавить
+    /// в наблюдение свои файлы.
+    fn hot_reload_mut(&mut self) -> &mut HotReload;
+
+    /// Перезагружает текстуры, шрифты и звуки, файлы которых изменились с прошлой проверки.
+    /// Движок сам вызывает это перед каждым `process` сцены; если горячая перезагрузка
+    /// выключена, ничего не делает.
+    fn reload_changed_resources(&mut self) -> Result<()>;
+
     /// Перезагружает все ресурсы, загруженные с учётом языка (на данный момент это только
     /// текстуры).
     fn reload_lang_resources(&mut self) -> Result<()> {
@@ -245,6 +514,21 @@ pub trait Context {
         options: TextureOptions,
     ) -> Result<Rc<Texture>>;
 
+    /// Создаёт пустую прозрачную текстуру, в которую можно рисовать через
+    /// [`begin_target`](Self::begin_target). Параметры `options` задают, как текстура будет
+    /// сглаживаться при рисовании её самой.
+    ///
+    /// Как и текстуры из `draw_text_to_texture`, после пересоздания окна она может пропасть,
+    /// так что перед использованием стоит проверять её через
+    /// [`is_texture_valid`](Self::is_texture_valid) и при необходимости создавать и рисовать
+    /// заново.
+    fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+    ) -> Result<Rc<Texture>>;
+
     /// Загружает текстуру из файла (обёртка над `load_texture` для упрощения кода).
     fn load_texture_file(&mut self, path: &Path, options: TextureOptions) -> Result<Rc<Texture>> {
         self.load_texture(TextureSource::File(path.to_path_buf()), options)
@@ -301,3 +585,22 @@ pub trait Context {
     /// Останавливает и выгружает все неиспользуемые звуки.
     fn drop_unused_sounds(&mut self);
 }
+
+// На сколько отрезков разбивать окружность, чтобы она выглядела гладкой
+fn circle_segments(radius: f32) -> usize {
+    ((radius.abs().sqrt() * 4.0) as usize).clamp(4, 64) * 4
+}
+
+// Точки дуги от угла start до угла end (в градусах по часовой стрелке, 0 — вправо),
+// включая оба конца
+fn arc_points(center: Vec2, radius: f32, start: f32, end: f32, segments: usize) -> Vec<Vec2> {
+    (0..=segments)
+        .map(|i| {
+            let angle = (start + (end - start) * i as f32 / segments as f32).to_radians();
+            Vec2::new(
+                center.x + radius * angle.cos(),
+                center.y + radius * angle.sin(),
+            )
+        })
+        .collect()
+}
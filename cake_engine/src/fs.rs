@@ -3,7 +3,28 @@
 //! Как и откуда конкретно загружаются ассеты, зависит от платформы и бэкенда. На большинстве
 //! систем это просто считываение файлов из указанного каталога, но, например, на Android
 //! используется AssetManager.
+//!
+//! Поверх каталога ассетов можно смонтировать дополнительные источники: другие каталоги,
+//! [архивы](crate::pack) и вшитые в исполняемый файл данные. Ассет ищется сначала
+//! в смонтированных источниках (последний смонтированный проверяется первым), и только потом
+//! в каталоге ассетов.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use cake_engine::{fs::{self, Mount}, pack::Pack};
+//!
+//! static EMBEDDED: &[(&str, &[u8])] = &[("languages.json", b"[\"en\"]")];
+//!
+//! fs::mount(Mount::Embedded(EMBEDDED));
+//! fs::mount(Mount::Pack(Pack::open("data.pak").unwrap()));
+//! fs::mount(Mount::Directory("mods/my_mod".into()));
+//!
+//! // Сначала ищется в mods/my_mod, потом в data.pak, потом среди вшитых ассетов
+//! let languages = fs::read_asset_to_string("languages.json").unwrap();
+//! ```
 
+use crate::pack::{self, Pack};
 use anyhow::Result;
 use once_cell::sync::Lazy;
 use std::{
@@ -12,8 +33,73 @@ use std::{
 };
 
 static ASSETS_DIRECTORY: Lazy<Mutex<PathBuf>> = Lazy::new(|| Mutex::new(PathBuf::new()));
+static MOUNTS: Lazy<Mutex<Vec<Mount>>> = Lazy::new(|| Mutex::new(Vec::new()));
 static LANG_SUFFIXES: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
 
+/// Источник ассетов, который можно смонтировать функцией [`mount`].
+#[derive(Clone, Debug)]
+pub enum Mount {
+    /// Каталог на диске.
+    Directory(PathBuf),
+    /// Архив ассетов.
+    Pack(Pack),
+    /// Ассеты, вшитые в исполняемый файл (например, через `include_bytes!`). Пути пишутся
+    /// через `/` относительно корня.
+    Embedded(&'static [(&'static str, &'static [u8])]),
+}
+
+impl Mount {
+    /// Читает ассет из источника. Возвращает `None`, если в источнике такого ассета нет.
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> Result<Option<Vec<u8>>> {
+        match self {
+            Mount::Directory(dir) => {
+                let fullpath = dir.join(path);
+                if cfg!(target_os = "android") {
+                    // На Android файлы лежат не на диске, и отличить отсутствующий файл
+                    // от ошибки чтения не получится
+                    return Ok(crate::backend::fs::read_file(&fullpath).ok());
+                }
+                if !fullpath.is_file() {
+                    return Ok(None);
+                }
+                Ok(Some(crate::backend::fs::read_file(&fullpath)?))
+            }
+            Mount::Pack(pack) => pack.read(path),
+            Mount::Embedded(files) => {
+                let name = match pack::normalize_path(path.as_ref()) {
+                    Some(name) => name,
+                    None => return Ok(None),
+                };
+                Ok(files
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, data)| data.to_vec()))
+            }
+        }
+    }
+}
+
+/// Монтирует дополнительный источник ассетов. Он будет проверяться раньше всех смонтированных
+/// до него источников и раньше каталога ассетов.
+pub fn mount(mount: Mount) {
+    MOUNTS.lock().unwrap().push(mount);
+}
+
+/// Отмонтирует все источники, смонтированные функцией [`mount`]. Каталог ассетов остаётся.
+pub fn unmount_all() {
+    MOUNTS.lock().unwrap().clear();
+}
+
+/// Смонтированные источники в порядке монтирования.
+pub fn get_mounts() -> Vec<Mount> {
+    MOUNTS.lock().unwrap().clone()
+}
+
+/// Заменяет все смонтированные источники указанными (последний проверяется первым).
+pub fn set_mounts(mounts: Vec<Mount>) {
+    *MOUNTS.lock().unwrap() = mounts;
+}
+
 /// Путь, из которого загружаются ассеты (по умолчанию текущий каталог).
 pub fn get_assets_directory() -> PathBuf {
     ASSETS_DIRECTORY.lock().unwrap().clone()
@@ -37,19 +123,143 @@ pub fn set_lang_suffixes(suffixes: Vec<String>) {
 }
 
 /// Путь к указанному ассету внутри каталога ассетов, установленного функцией
-/// `set_assets_directory`.
+/// `set_assets_directory`. Смонтированные источники не учитываются.
 pub fn asset_path<P: AsRef<Path>>(path: P) -> PathBuf {
     get_assets_directory().join(path)
 }
 
+/// Путь к файлу на диске, содержащему указанный ассет, с учётом смонтированных источников.
+///
+/// Нужен для библиотек, которые умеют открывать файлы только по имени. Если ассет лежит
+/// в архиве или вшит в исполняемый файл, он извлекается во временный каталог.
+pub fn asset_file_path<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = path.as_ref();
+
+    let mounts = MOUNTS.lock().unwrap();
+    for mount in mounts.iter().rev() {
+        if let Mount::Directory(dir) = mount {
+            let fullpath = dir.join(path);
+            if fullpath.is_file() {
+                return Ok(fullpath);
+            }
+        } else if let Some(data) = mount.read(path)? {
+            // Контрольная сумма в имени каталога, чтобы разные версии ассета не перезаписывали
+            // друг друга и не извлекались заново при каждом запуске
+            let dir = std::env::temp_dir()
+                .join("cake_engine_assets")
+                .join(format!("{:08x}", pack::crc32(&data)));
+            let fullpath = dir.join(path.file_name().unwrap_or_default());
+            let up_to_date = match std::fs::metadata(&fullpath) {
+                Ok(m) => m.len() == data.len() as u64,
+                Err(_) => false,
+            };
+            if !up_to_date {
+                std::fs::create_dir_all(&dir)?;
+                std::fs::write(&fullpath, &data)?;
+            }
+            return Ok(fullpath);
+        }
+    }
+
+    Ok(asset_path(path))
+}
+
+/// Все файлы на диске, содержащие указанный ассет: из смонтированных каталогов и каталога
+/// ассетов. Ассеты из архивов и вшитые в исполняемый файл не учитываются.
+pub fn find_all_asset_files<P: AsRef<Path>>(path: P) -> Vec<PathBuf> {
+    let path = path.as_ref();
+    let mut result = Vec::new();
+
+    let fullpath = asset_path(path);
+    if fullpath.is_file() {
+        result.push(fullpath);
+    }
+    for mount in MOUNTS.lock().unwrap().iter() {
+        if let Mount::Directory(dir) = mount {
+            let fullpath = dir.join(path);
+            if fullpath.is_file() {
+                result.push(fullpath);
+            }
+        }
+    }
+
+    result
+}
+
 /// Считывает ассет в массив байт.
 pub fn read_asset_to_bytes<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
-    crate::backend::fs::read_asset_to_bytes(path)
+    let path = path.as_ref();
+
+    let mounts = MOUNTS.lock().unwrap();
+    for mount in mounts.iter().rev() {
+        if let Some(data) = mount.read(path)? {
+            return Ok(data);
+        }
+    }
+
+    crate::backend::fs::read_file(&asset_path(path))
 }
 
 /// Считывает ассет в строку (содержимое файла должно быть в кодировке UTF-8).
 pub fn read_asset_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
-    crate::backend::fs::read_asset_to_string(path)
+    Ok(String::from_utf8(read_asset_to_bytes(path)?)?)
+}
+
+/// Считывает ассет из всех источников, в которых он есть, начиная с наименее приоритетного
+/// (каталога ассетов). Нужно для файлов, которые надо не заменять, а объединять: например,
+/// моды могут добавлять новые строки в файлы перевода.
+pub fn read_all_assets_to_bytes<P: AsRef<Path>>(path: P) -> Result<Vec<Vec<u8>>> {
+    let path = path.as_ref();
+    let mut result = Vec::new();
+
+    if let Some(data) = Mount::Directory(get_assets_directory()).read(path)? {
+        result.push(data);
+    }
+    for mount in MOUNTS.lock().unwrap().iter() {
+        if let Some(data) = mount.read(path)? {
+            result.push(data);
+        }
+    }
+
+    Ok(result)
+}
+
+/// То же, что `read_all_assets_to_bytes`, но возвращает строки.
+pub fn read_all_assets_to_string<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
+    let mut result = Vec::new();
+    for data in read_all_assets_to_bytes(path)? {
+        result.push(String::from_utf8(data)?);
+    }
+    Ok(result)
+}
+
+/// То же, что `read_all_assets_to_string`, но с учётом языка.
+///
+/// Сначала идут файлы без языка в имени, потом файлы для языков из `set_lang_suffixes`
+/// от последнего к первому. Если объединять их по порядку, то для каждого ключа победит самый
+/// подходящий язык, а среди файлов одного языка — самый приоритетный источник.
+pub fn read_all_lang_assets_to_string<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let mut result = read_all_assets_to_string(path)?;
+
+    let suffixes = get_lang_suffixes();
+    for suffix in suffixes.iter().rev() {
+        if let Some(p) = lang_asset_path(path, suffix) {
+            result.extend(read_all_assets_to_string(p)?);
+        }
+    }
+
+    Ok(result)
+}
+
+// Путь к версии ассета для указанного языка: logo.png -> logo.ru.png
+pub(crate) fn lang_asset_path(path: &Path, suffix: &str) -> Option<PathBuf> {
+    let filename = path.file_name()?.to_str()?;
+    let (base, ext) = match filename.rfind('.') {
+        Some(idx) => (&filename[..idx], &filename[idx..]),
+        None => (filename, ""),
+    };
+    Some(path.with_file_name(format!("{}.{}{}", base, suffix, ext)))
 }
 
 /// Ищет ассет с учётом текущего языка и считывает его в массив байт.
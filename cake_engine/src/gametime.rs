@@ -91,7 +91,17 @@ impl GameTime {
     ///
     /// Этот метод вызывается бэкендами автоматически, и вам, как правило, не нужно его трогать.
     pub fn tick(&mut self) -> f32 {
-        let new_current_time = Instant::now();
+        self.tick_to(Instant::now())
+    }
+
+    /// То же самое, что и `tick`, но вместо реального времени прибавляет к текущему времени
+    /// указанное число секунд. Нужно для бэкендов, которые симулируют время (например, Dummy
+    /// со сценарием событий).
+    pub fn tick_by(&mut self, dt: f32) -> f32 {
+        self.tick_to(self.current_time + Duration::from_secs_f32(dt.max(0.0)))
+    }
+
+    fn tick_to(&mut self, new_current_time: Instant) -> f32 {
         let dt = (new_current_time - self.current_time).as_secs_f32();
         self.current_time = new_current_time;
         self.current_frame = self.current_frame.wrapping_add(1);
@@ -3,10 +3,16 @@
 
 use crate::{
     audio::{Music, Sound},
+    conf::Conf,
     font::Font,
+    hot_reload::HotReload,
     texture::{Texture, TextureOptions, TextureSource},
+    transform::Transform,
     vec::Vec2,
+    view::View,
+    voices::Voices,
 };
+use anyhow::Result;
 use std::{collections::HashMap, path::Path, rc::Rc};
 
 pub(crate) struct Globals {
@@ -21,10 +27,25 @@ pub(crate) struct Globals {
     pub(crate) music: HashMap<usize, Rc<crate::audio::Music>>,
     pub(crate) last_sound_id: usize,
     pub(crate) sounds: HashMap<usize, Rc<crate::audio::Sound>>,
+    pub(crate) voices: Voices,
+    pub(crate) hot_reload: HotReload,
+    // Стек преобразований; каждый элемент уже объединён со всеми предыдущими
+    pub(crate) transforms: Vec<Transform>,
+    // Стек текстур, в которые идёт рисование (последняя — текущая). Рядом с каждой лежат
+    // view и стек преобразований того, во что рисовали до неё: они вернутся после её завершения
+    pub(crate) targets: Vec<(usize, View, Vec<Transform>)>,
+}
+
+/// Ресурсы, файлы которых изменились с прошлой проверки.
+#[derive(Default)]
+pub(crate) struct ChangedResources {
+    pub textures: Vec<Rc<Texture>>,
+    pub fonts: Vec<Rc<Font>>,
+    pub sounds: Vec<Rc<Sound>>,
 }
 
 impl Globals {
-    pub fn new(view_target_size: Vec2) -> Globals {
+    pub fn new(view_target_size: Vec2, conf: &Conf) -> Globals {
         Globals {
             time: crate::gametime::GameTime::new(),
             input: crate::input::Input::new(),
@@ -37,9 +58,107 @@ impl Globals {
             music: HashMap::new(),
             last_sound_id: 0,
             sounds: HashMap::new(),
+            voices: Voices::new(conf.max_voices, conf.voice_stealing),
+            hot_reload: HotReload::new(conf.hot_reload),
+            transforms: Vec::new(),
+            targets: Vec::new(),
+        }
+    }
+
+    /// Запоминает текстуру, в которую начинается рисование, и подменяет view на систему
+    /// координат этой текстуры. Текстуры могут быть вложенными: преобразования того, во что
+    /// рисовали до этого, внутри не действуют. Рисовать в текстуру, которая уже есть в стеке,
+    /// нельзя.
+    pub(crate) fn begin_target(&mut self, texture: &Texture) -> Result<()> {
+        if self.is_target_active(texture.id) {
+            return Err(anyhow::anyhow!("Render target is already active"));
+        }
+        let outer_view = std::mem::replace(&mut self.view, View::new(texture.size_vec()));
+        let outer_transforms = std::mem::take(&mut self.transforms);
+        self.targets
+            .push((texture.id, outer_view, outer_transforms));
+        Ok(())
+    }
+
+    /// Возвращает на место view и преобразования того, во что рисовали до текущей текстуры.
+    /// Возвращает id текстуры, в которую шло рисование.
+    pub(crate) fn end_target(&mut self) -> Result<usize> {
+        match self.targets.pop() {
+            Some((id, outer_view, outer_transforms)) => {
+                self.view = outer_view;
+                self.transforms = outer_transforms;
+                Ok(id)
+            }
+            None => Err(anyhow::anyhow!("No active render target")),
         }
     }
 
+    /// Id текстуры, в которую сейчас идёт рисование.
+    #[allow(dead_code)]
+    pub(crate) fn target_id(&self) -> Option<usize> {
+        self.targets.last().map(|(id, _, _)| *id)
+    }
+
+    /// Есть ли текстура с таким id в стеке (рисование в неё ещё не завершено).
+    pub(crate) fn is_target_active(&self, id: usize) -> bool {
+        self.targets.iter().any(|(target, _, _)| *target == id)
+    }
+
+    /// Кладёт в стек преобразование, объединённое с текущим.
+    pub(crate) fn push_transform(&mut self, transform: Transform) {
+        let combined = self.get_transform().combine(&transform);
+        self.transforms.push(combined);
+    }
+
+    pub(crate) fn pop_transform(&mut self) -> Result<()> {
+        match self.transforms.pop() {
+            Some(_) => Ok(()),
+            None => Err(anyhow::anyhow!("Transform stack is empty")),
+        }
+    }
+
+    /// Текущее преобразование (все преобразования из стека, объединённые в одно).
+    pub(crate) fn get_transform(&self) -> Transform {
+        match self.transforms.last() {
+            Some(t) => *t,
+            None => Transform::IDENTITY,
+        }
+    }
+
+    /// Проверяет файлы ресурсов, если пришло время. Бэкенд должен перезагрузить то, что вернулось.
+    pub(crate) fn poll_hot_reload(&mut self) -> ChangedResources {
+        let mut result = ChangedResources::default();
+        if !self.hot_reload.begin_poll(self.time.uptime()) {
+            return result;
+        }
+
+        for t in self.textures.values() {
+            let (path, lang) = match &t.source {
+                TextureSource::File(p) => (p, false),
+                TextureSource::LangFile(p) => (p, true),
+                _ => continue,
+            };
+            if self.hot_reload.check(path, lang) {
+                self.hot_reload.push_changed(path);
+                result.textures.push(t.clone());
+            }
+        }
+        for f in self.fonts.values() {
+            if self.hot_reload.check(&f.path, false) {
+                self.hot_reload.push_changed(&f.path);
+                result.fonts.push(f.clone());
+            }
+        }
+        for s in self.sounds.values() {
+            if self.hot_reload.check(&s.path, false) {
+                self.hot_reload.push_changed(&s.path);
+                result.sounds.push(s.clone());
+            }
+        }
+
+        result
+    }
+
     pub(crate) fn add_texture(
         &mut self,
         source: TextureSource,
@@ -50,6 +169,12 @@ impl Globals {
         self.last_texture_id += 1;
         let id = self.last_texture_id;
 
+        match &source {
+            TextureSource::File(p) => self.hot_reload.track(p, false),
+            TextureSource::LangFile(p) => self.hot_reload.track(p, true),
+            _ => {}
+        }
+
         let t = Rc::new(Texture {
             id,
             source,
@@ -81,6 +206,7 @@ impl Globals {
     pub(crate) fn add_font(&mut self, path: &Path, size: u16) -> Rc<Font> {
         self.last_font_id += 1;
         let id = self.last_font_id;
+        self.hot_reload.track(path, false);
 
         let f = Rc::new(Font {
             id,
@@ -123,6 +249,7 @@ impl Globals {
     pub(crate) fn add_sound(&mut self, path: &Path) -> Rc<Sound> {
         self.last_sound_id += 1;
         let id = self.last_sound_id;
+        self.hot_reload.track(path, false);
 
         let s = Rc::new(Sound {
             id,
@@ -0,0 +1,176 @@
+//! Горячая перезагрузка ассетов для разработки.
+//!
+//! Если в [`Conf`](crate::conf::Conf) включён `hot_reload`, движок пару раз в секунду проверяет
+//! время изменения файлов всех загруженных текстур, шрифтов и звуков и перезагружает изменившиеся
+//! прямо на месте, как это делает `reload_lang_textures`. Размер текстуры при этом
+//! не обновляется, так что менять его не стоит.
+//!
+//! Следить можно только за файлами, лежащими в каталогах: ассеты из архивов и вшитые
+//! в исполняемый файл не меняются.
+//!
+//! Файлы, которые движок не загружает сам (например, файлы с переводами), можно добавить
+//! в наблюдение через [`HotReload::watch`] и проверять в своих сценах:
+//!
+//! ```
+//! # use cake_engine::{conf::Conf, context::Context, dummy::DummyContext};
+//! # use std::path::Path;
+//! # let mut dctx = DummyContext::new(&Conf { hot_reload: true, ..Default::default() });
+//! # let mut ctx: &mut dyn Context = &mut dctx;
+//! // При запуске игры
+//! ctx.hot_reload_mut().watch_lang("strings.json");
+//!
+//! // В методе process вашей сцены
+//! if ctx.hot_reload().is_changed(Path::new("strings.json")) {
+//!     println!("Пора перечитать строки");
+//! }
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+// Как часто проверять файлы
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Состояние горячей перезагрузки. Доступно через
+/// [`Context::hot_reload`](crate::context::Context::hot_reload).
+#[derive(Clone, Debug, Default)]
+pub struct HotReload {
+    enabled: bool,
+    last_poll: Option<Duration>,
+    mtimes: HashMap<PathBuf, SystemTime>,
+    changed_files: HashSet<PathBuf>,
+    // Путь ассета и нужно ли учитывать язык
+    watched: Vec<(PathBuf, bool)>,
+    changed: Vec<PathBuf>,
+}
+
+impl HotReload {
+    pub fn new(enabled: bool) -> HotReload {
+        HotReload {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Добавляет ассет в наблюдение.
+    pub fn watch<P: AsRef<Path>>(&mut self, path: P) {
+        self.add_watched(path.as_ref(), false);
+    }
+
+    /// Добавляет ассет в наблюдение вместе со всеми его языковыми вариантами (для `strings.json`
+    /// это `strings.ru.json`, `strings.en.json` и так далее).
+    pub fn watch_lang<P: AsRef<Path>>(&mut self, path: P) {
+        self.add_watched(path.as_ref(), true);
+    }
+
+    /// Ассеты, изменившиеся с прошлого вызова `process` сцены (в том виде, в каком их загружали
+    /// или добавляли в наблюдение). Пока `process` не вызывается (например, во время
+    /// [перехода](crate::transition) между сценами), изменения копятся, так что сцена их
+    /// не пропустит.
+    pub fn changed_assets(&self) -> &[PathBuf] {
+        &self.changed
+    }
+
+    /// Изменился ли указанный ассет с прошлого вызова `process` сцены.
+    pub fn is_changed(&self, path: &Path) -> bool {
+        self.changed.iter().any(|p| p == path)
+    }
+
+    fn add_watched(&mut self, path: &Path, lang: bool) {
+        if self.watched.iter().any(|(p, _)| p == path) {
+            return;
+        }
+        self.track(path, lang);
+        self.watched.push((path.to_path_buf(), lang));
+    }
+
+    /// Запоминает время изменения файлов ассета, чтобы следить за ними.
+    pub(crate) fn track(&mut self, path: &Path, lang: bool) {
+        if !self.enabled {
+            return;
+        }
+        for file in asset_files(path, lang) {
+            if let Ok(mtime) = std::fs::metadata(&file).and_then(|m| m.modified()) {
+                self.mtimes.entry(file).or_insert(mtime);
+            }
+        }
+    }
+
+    /// Начинает новую проверку: находит изменившиеся файлы и заполняет список изменившихся
+    /// наблюдаемых ассетов. Возвращает `false`, если проверять ещё рано.
+    pub(crate) fn begin_poll(&mut self, uptime: Duration) -> bool {
+        self.changed_files.clear();
+        if !self.enabled {
+            return false;
+        }
+        if let Some(last_poll) = self.last_poll {
+            if uptime < last_poll + POLL_INTERVAL {
+                return false;
+            }
+        }
+        self.last_poll = Some(uptime);
+
+        for (file, old_mtime) in self.mtimes.iter_mut() {
+            if let Ok(mtime) = std::fs::metadata(file).and_then(|m| m.modified()) {
+                if mtime != *old_mtime {
+                    *old_mtime = mtime;
+                    self.changed_files.insert(file.clone());
+                }
+            }
+        }
+
+        for (path, lang) in self.watched.clone() {
+            if self.check(&path, lang) {
+                self.push_changed(&path);
+            }
+        }
+        true
+    }
+
+    /// Изменился ли какой-нибудь из файлов ассета при текущей проверке. Если у ассета появились
+    /// новые файлы, они начинают отслеживаться, но изменёнными не считаются.
+    pub(crate) fn check(&mut self, path: &Path, lang: bool) -> bool {
+        if self.changed_files.is_empty() {
+            return false;
+        }
+        self.track(path, lang);
+        asset_files(path, lang)
+            .iter()
+            .any(|f| self.changed_files.contains(f))
+    }
+
+    pub(crate) fn push_changed(&mut self, path: &Path) {
+        if !self.is_changed(path) {
+            self.changed.push(path.to_path_buf());
+        }
+    }
+
+    /// Забывает изменившиеся ассеты. Вызывается после `process` сцены.
+    pub(crate) fn clear_changed(&mut self) {
+        self.changed.clear();
+    }
+}
+
+// Все файлы на диске, из которых может загружаться ассет (с языковыми вариантами, если надо)
+fn asset_files(path: &Path, lang: bool) -> Vec<PathBuf> {
+    let mut result = crate::fs::find_all_asset_files(path);
+    if lang {
+        for suffix in crate::fs::get_lang_suffixes() {
+            if let Some(p) = crate::fs::lang_asset_path(path, &suffix) {
+                result.extend(crate::fs::find_all_asset_files(p));
+            }
+        }
+    }
+    result
+}
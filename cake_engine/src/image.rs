@@ -0,0 +1,169 @@
+//! Изображение в памяти (например, скриншот).
+
+use crate::color::Color;
+use anyhow::Result;
+use std::path::Path;
+
+/// Изображение в памяти: пиксели RGBA по четыре байта, построчно сверху вниз, альфа
+/// не домножена на цвет.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Создаёт полностью прозрачное изображение.
+    pub fn new(width: u32, height: u32) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Создаёт изображение из готового массива пикселей RGBA.
+    pub fn from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> Result<Image> {
+        if pixels.len() != width as usize * height as usize * 4 {
+            return Err(anyhow::anyhow!(
+                "Invalid pixel buffer size {} for {}x{} image",
+                pixels.len(),
+                width,
+                height
+            ));
+        }
+        Ok(Image {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Декодирует PNG любого формата в RGBA.
+    pub fn from_png_bytes(data: &[u8]) -> Result<Image> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+
+        let channels = match info.color_type {
+            png::ColorType::Grayscale => 1,
+            png::ColorType::GrayscaleAlpha => 2,
+            png::ColorType::Rgb => 3,
+            png::ColorType::Rgba => 4,
+            png::ColorType::Indexed => {
+                // После EXPAND палитры остаться не должно
+                return Err(anyhow::anyhow!("Unexpected indexed PNG after expanding"));
+            }
+        };
+
+        let mut pixels = Vec::with_capacity(info.width as usize * info.height as usize * 4);
+        for y in 0..info.height as usize {
+            let line =
+                &buf[y * info.line_size..y * info.line_size + info.width as usize * channels];
+            for p in line.chunks_exact(channels) {
+                match channels {
+                    1 => pixels.extend_from_slice(&[p[0], p[0], p[0], 255]),
+                    2 => pixels.extend_from_slice(&[p[0], p[0], p[0], p[1]]),
+                    3 => pixels.extend_from_slice(&[p[0], p[1], p[2], 255]),
+                    _ => pixels.extend_from_slice(p),
+                }
+            }
+        }
+
+        Image::from_rgba(info.width, info.height, pixels)
+    }
+
+    /// Загружает PNG-файл (путь обычный, а не путь ассета).
+    pub fn load_png(path: &Path) -> Result<Image> {
+        Image::from_png_bytes(&std::fs::read(path)?)
+    }
+
+    /// Кодирует изображение в PNG.
+    pub fn to_png_bytes(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut data, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.pixels)?;
+            writer.finish()?;
+        }
+        Ok(data)
+    }
+
+    /// Сохраняет изображение в PNG-файл.
+    pub fn save_png(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_png_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Пиксели RGBA построчно.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> Color {
+        let i = self.offset(x, y);
+        let p = &self.pixels[i..i + 4];
+        Color::new(p[0], p[1], p[2], p[3])
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
+        let i = self.offset(x, y);
+        self.pixels[i..i + 4].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+
+    /// Заливает всё изображение цветом (без смешивания).
+    pub fn fill(&mut self, color: Color) {
+        for p in self.pixels.chunks_exact_mut(4) {
+            p.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    /// Накладывает цвет на пиксель с учётом прозрачности (обычное смешивание source-over).
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if color.a == 0 {
+            return;
+        }
+        if color.a == 255 {
+            self.set_pixel(x, y, color);
+            return;
+        }
+
+        let i = self.offset(x, y);
+        let dst = &mut self.pixels[i..i + 4];
+
+        let sa = color.a as u32;
+        let da = dst[3] as u32 * (255 - sa) / 255;
+        let out_a = sa + da;
+        if out_a == 0 {
+            dst.copy_from_slice(&[0, 0, 0, 0]);
+            return;
+        }
+
+        let mix = |s: u8, d: u8| ((s as u32 * sa + d as u32 * da + out_a / 2) / out_a) as u8;
+        dst[0] = mix(color.r, dst[0]);
+        dst[1] = mix(color.g, dst[1]);
+        dst[2] = mix(color.b, dst[2]);
+        dst[3] = out_a as u8;
+    }
+
+    fn offset(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+}
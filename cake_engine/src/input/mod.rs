@@ -53,6 +53,7 @@ pub struct KeyMods {
 
 /// Событие. Набор поддерживаемых событий зависит от бэкенда.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Event {
     /// Пользователь запросил выход (например, нажал Ctrl+C или кликнул в крестик у окна).
     Quit,
@@ -108,13 +108,25 @@ pub mod context;
 pub mod font;
 pub mod fs;
 pub mod gametime;
+pub mod hot_reload;
+pub mod image;
 pub mod input;
 pub mod label;
 pub mod log;
+pub mod music;
+pub mod pack;
+pub mod particles;
 pub mod rect;
+pub mod recording;
 pub mod scene;
+pub mod slider;
+#[cfg(feature = "software")]
+pub mod snapshot;
 pub mod sprite;
 pub mod texture;
+pub mod transform;
+pub mod transition;
+pub mod tween;
 pub mod utils;
 pub mod vec;
 pub mod view;
@@ -123,6 +135,8 @@ pub mod view;
 pub mod android;
 
 mod globals;
+mod rng;
+mod voices;
 
 #[doc(hidden)]
 pub use backend::*;
@@ -0,0 +1,422 @@
+//! Музыка: плейлисты и плавные переходы между треками.
+//!
+//! [`MusicManager`] хранит именованные [плейлисты](Playlist) и сам переключает треки:
+//! по окончании трека включает следующий, перемешивает их и повторяет согласно
+//! [режиму повтора](RepeatMode). При смене плейлиста или трека старый трек плавно затихает,
+//! а новый плавно нарастает.
+//!
+//! ```
+//! use cake_engine::music::{MusicManager, Playlist, RepeatMode};
+//! use std::path::PathBuf;
+//! # use cake_engine::{conf::Conf, context::Context, dummy::DummyContext};
+//! # let mut dctx = DummyContext::new(&Conf::default());
+//! # let mut ctx: &mut dyn Context = &mut dctx;
+//!
+//! let mut music = MusicManager::new();
+//! music.add_playlist("menu", Playlist {
+//!     tracks: vec![PathBuf::from("music/music_main.ogg")],
+//!     repeat: RepeatMode::All,
+//!     ..Default::default()
+//! });
+//!
+//! // Трек нарастает в течение секунды
+//! music.play(ctx, "menu", 1.0).unwrap();
+//! assert_eq!(music.current_playlist(), Some("menu"));
+//!
+//! // В методе process сцены
+//! music.process(ctx).unwrap();
+//!
+//! // Затихание за полсекунды
+//! music.stop(ctx, 0.5).unwrap();
+//! ```
+//!
+//! Плейлисты можно описать в файле (при включенной фиче `serde`) и загрузить через
+//! [`MusicManager::load_manifest`]. Файл — JSON-объект, где ключ — название плейлиста:
+//!
+//! ```json
+//! {
+//!     "menu": {"tracks": ["music/calm.ogg"]},
+//!     "game": {"tracks": ["music/a.ogg", "music/b.ogg"], "shuffle": true}
+//! }
+//! ```
+//!
+//! Одновременно играть две музыки умеют не все бэкенды: в SDL при переходе старый трек
+//! затухает за фиксированные полсекунды, не обращая внимания на `fade`, и только после этого
+//! начинает нарастать новый. А в macroquad нельзя узнать, что трек доиграл, поэтому там
+//! следующий трек включается только через [`MusicManager::next`].
+
+use crate::{audio::Music, context::Context, rng::Rng, tween::Tween};
+use anyhow::Result;
+use std::{collections::HashMap, path::PathBuf, rc::Rc, time::Duration};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Что делать, когда трек доиграл.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RepeatMode {
+    /// После последнего трека начать плейлист сначала.
+    #[default]
+    All,
+    /// Повторять текущий трек.
+    One,
+    /// После последнего трека остановиться.
+    Off,
+}
+
+/// Плейлист.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Playlist {
+    /// Пути к трекам относительно каталога с ассетами.
+    pub tracks: Vec<PathBuf>,
+    /// Играть треки в случайном порядке (при каждом повторе плейлиста порядок новый).
+    pub shuffle: bool,
+    pub repeat: RepeatMode,
+}
+
+/// Проигрыватель музыки. Подробности и примеры в [документации модуля](self).
+pub struct MusicManager {
+    playlists: HashMap<String, Playlist>,
+    volume: f32,
+    enabled: bool,
+    // Плейлист, который должен играть (запоминается, даже если музыка выключена)
+    playlist_name: Option<String>,
+    order: Vec<usize>,
+    position: usize,
+    current: Option<Rc<Music>>,
+    looping: bool,
+    // Громкость текущего трека относительно общей: нарастание и затихание
+    gain: Tween<f32>,
+    previous: Option<(Rc<Music>, Tween<f32>)>,
+    stopping: bool,
+    last_uptime: Option<Duration>,
+    rng: Rng,
+}
+
+impl MusicManager {
+    pub fn new() -> MusicManager {
+        MusicManager {
+            playlists: HashMap::new(),
+            volume: 1.0,
+            enabled: true,
+            playlist_name: None,
+            order: Vec::new(),
+            position: 0,
+            current: None,
+            looping: false,
+            gain: Tween::new(1.0, 1.0, 0.0),
+            previous: None,
+            stopping: false,
+            last_uptime: None,
+            rng: Rng::new(),
+        }
+    }
+
+    /// Задаёт зерно для перемешивания треков.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng.set_seed(seed);
+    }
+
+    /// Добавляет плейлист (или заменяет плейлист с тем же названием).
+    pub fn add_playlist(&mut self, name: &str, playlist: Playlist) {
+        self.playlists.insert(name.to_string(), playlist);
+    }
+
+    pub fn playlist(&self, name: &str) -> Option<&Playlist> {
+        self.playlists.get(name)
+    }
+
+    /// Загружает плейлисты из JSON-файла в каталоге с ассетами.
+    ///
+    /// Файл читается из всех смонтированных источников (см. [`fs`](crate::fs)). Если плейлист
+    /// с таким именем уже есть, новые треки добавляются в его конец, а `shuffle` и `repeat`
+    /// берутся из последнего прочитанного файла.
+    #[cfg(feature = "serde")]
+    pub fn load_manifest<P: AsRef<std::path::Path>>(&mut self, path: P) -> Result<()> {
+        let manifests = crate::fs::read_all_assets_to_string(path.as_ref())?;
+        if manifests.is_empty() {
+            return Err(anyhow::anyhow!("{:?} not found", path.as_ref()));
+        }
+
+        for data in manifests {
+            let playlists: HashMap<String, Playlist> = serde_json::from_str(&data)?;
+            for (name, playlist) in playlists {
+                match self.playlists.get_mut(&name) {
+                    Some(existing) => {
+                        for track in playlist.tracks {
+                            if !existing.tracks.contains(&track) {
+                                existing.tracks.push(track);
+                            }
+                        }
+                        existing.shuffle = playlist.shuffle;
+                        existing.repeat = playlist.repeat;
+                    }
+                    None => {
+                        self.playlists.insert(name, playlist);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Общая громкость музыки от 0 до 1.
+    pub fn get_volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// Изменяет общую громкость музыки; применяется сразу, в том числе во время переходов.
+    pub fn set_volume(&mut self, ctx: &mut dyn Context, volume: f32) -> Result<()> {
+        self.volume = volume.clamp(0.0, 1.0);
+        self.apply_volumes(ctx)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Включает или выключает музыку. Выключенный проигрыватель запоминает, какой плейлист
+    /// просили включить, и начинает его с первого трека при включении.
+    pub fn set_enabled(&mut self, ctx: &mut dyn Context, enabled: bool) -> Result<()> {
+        if enabled == self.enabled {
+            return Ok(());
+        }
+        self.enabled = enabled;
+
+        if enabled {
+            if self.playlist_name.is_some() {
+                self.position = 0;
+                self.start_track(ctx, 0.0)?;
+            }
+        } else {
+            self.stop_now(ctx)?;
+        }
+        Ok(())
+    }
+
+    /// Название играющего плейлиста.
+    pub fn current_playlist(&self) -> Option<&str> {
+        self.playlist_name.as_deref()
+    }
+
+    /// Играющий трек.
+    pub fn current_track(&self) -> Option<&Rc<Music>> {
+        self.current.as_ref()
+    }
+
+    /// Включает плейлист, плавно переходя к нему от текущего трека за `fade` секунд. Если этот
+    /// плейлист уже играет, ничего не делает. Если первый трек нового плейлиста уже играет,
+    /// он не перезапускается.
+    pub fn play(&mut self, ctx: &mut dyn Context, name: &str, fade: f32) -> Result<()> {
+        let playlist = match self.playlists.get(name) {
+            Some(p) => p,
+            None => return Err(anyhow::anyhow!("Unknown playlist {:?}", name)),
+        };
+        if self.playlist_name.as_deref() == Some(name) && !self.stopping {
+            return Ok(());
+        }
+
+        let track_count = playlist.tracks.len();
+        let shuffle = playlist.shuffle;
+        self.playlist_name = Some(name.to_string());
+        self.make_order(track_count, shuffle);
+        self.position = 0;
+
+        if self.enabled {
+            self.start_track(ctx, fade)?;
+        }
+        Ok(())
+    }
+
+    /// Переходит к следующему треку плейлиста за `fade` секунд.
+    pub fn next(&mut self, ctx: &mut dyn Context, fade: f32) -> Result<()> {
+        if !self.enabled || self.playlist_name.is_none() {
+            return Ok(());
+        }
+        if self.advance() {
+            self.start_track(ctx, fade)
+        } else {
+            self.stop(ctx, fade)
+        }
+    }
+
+    /// Плавно останавливает музыку за `fade` секунд.
+    pub fn stop(&mut self, ctx: &mut dyn Context, fade: f32) -> Result<()> {
+        self.playlist_name = None;
+        if self.current.is_none() {
+            return Ok(());
+        }
+        if fade <= 0.0 {
+            return self.stop_now(ctx);
+        }
+        self.gain = Tween::new(self.gain.value(), 0.0, fade);
+        self.stopping = true;
+        Ok(())
+    }
+
+    /// Обработка: переходы между треками и переключение доигравших треков. Вызывайте
+    /// в `process` сцены. Время берётся из `ctx.time()`, так что лишние вызовы в том же кадре
+    /// ничего не испортят.
+    pub fn process(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        let uptime = ctx.time().uptime();
+        let dt = match self.last_uptime {
+            Some(last) => uptime.saturating_sub(last).as_secs_f32(),
+            None => 0.0,
+        };
+        self.last_uptime = Some(uptime);
+
+        if let Some((_, tween)) = self.previous.as_mut() {
+            tween.update(dt);
+            if tween.is_finished() {
+                self.previous = None;
+                ctx.stop_previous_music()?;
+            } else {
+                ctx.set_previous_music_volume(self.volume * tween.value())?;
+            }
+        }
+
+        let current = match self.current.as_ref() {
+            Some(m) => m.clone(),
+            None => return Ok(()),
+        };
+
+        if !self.gain.is_finished() {
+            self.gain.update(dt);
+            ctx.set_music_volume(self.volume * self.gain.value())?;
+            if self.stopping && self.gain.is_finished() {
+                return self.stop_now(ctx);
+            }
+        }
+
+        if !self.looping && !self.stopping {
+            let finished = match ctx.get_playing_music()? {
+                Some(m) => m.id != current.id,
+                None => true,
+            };
+            if finished {
+                if self.advance() {
+                    self.start_track(ctx, 0.0)?;
+                } else {
+                    self.playlist_name = None;
+                    self.stop_now(ctx)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn stop_now(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        self.current = None;
+        self.previous = None;
+        self.stopping = false;
+        ctx.stop_music()
+    }
+
+    fn start_track(&mut self, ctx: &mut dyn Context, fade: f32) -> Result<()> {
+        let playlist = match self
+            .playlist_name
+            .as_ref()
+            .and_then(|n| self.playlists.get(n))
+        {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let path = match self
+            .order
+            .get(self.position)
+            .and_then(|&i| playlist.tracks.get(i))
+        {
+            Some(p) => p.clone(),
+            None => return self.stop_now(ctx),
+        };
+        let looping = playlist.repeat == RepeatMode::One
+            || (playlist.repeat == RepeatMode::All && playlist.tracks.len() == 1);
+        self.stopping = false;
+
+        // Этот трек уже играет — просто возвращаем ему громкость
+        if self.current.as_ref().is_some_and(|m| m.path() == path) {
+            self.gain = Tween::new(self.gain.value(), 1.0, fade);
+            return self.apply_volumes(ctx);
+        }
+
+        let music = match self.previous.as_ref() {
+            Some((m, _)) if m.path() == path => m.clone(),
+            _ => ctx.load_music_file(&path)?,
+        };
+
+        match self.current.take() {
+            Some(old) if fade > 0.0 => {
+                ctx.play_music_keeping_previous(&music, 0.0, looping)?;
+                self.previous = Some((old, Tween::new(self.gain.value(), 0.0, fade)));
+            }
+            _ => {
+                ctx.play_music(&music, 0.0, looping)?;
+                self.previous = None;
+            }
+        }
+        self.gain = Tween::new(if fade > 0.0 { 0.0 } else { 1.0 }, 1.0, fade);
+        self.current = Some(music);
+        self.looping = looping;
+        self.apply_volumes(ctx)
+    }
+
+    fn apply_volumes(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        if self.current.is_some() {
+            ctx.set_music_volume(self.volume * self.gain.value())?;
+        }
+        if let Some((_, tween)) = self.previous.as_ref() {
+            ctx.set_previous_music_volume(self.volume * tween.value())?;
+        }
+        Ok(())
+    }
+
+    /// Переходит к следующей позиции плейлиста. Возвращает `false`, если плейлист закончился.
+    fn advance(&mut self) -> bool {
+        let playlist = match self
+            .playlist_name
+            .as_ref()
+            .and_then(|n| self.playlists.get(n))
+        {
+            Some(p) => p,
+            None => return false,
+        };
+        let track_count = playlist.tracks.len();
+        let shuffle = playlist.shuffle;
+        let repeat = playlist.repeat;
+
+        if self.position + 1 < self.order.len() {
+            self.position += 1;
+            return true;
+        }
+        match repeat {
+            RepeatMode::Off => false,
+            RepeatMode::All | RepeatMode::One => {
+                self.make_order(track_count, shuffle);
+                self.position = 0;
+                track_count > 0
+            }
+        }
+    }
+
+    fn make_order(&mut self, track_count: usize, shuffle: bool) {
+        self.order = (0..track_count).collect();
+        if shuffle {
+            // Тасование Фишера — Йетса
+            for i in (1..track_count).rev() {
+                let j = (self.rng.next_u64() % (i as u64 + 1)) as usize;
+                self.order.swap(i, j);
+            }
+        }
+    }
+}
+
+impl Default for MusicManager {
+    fn default() -> MusicManager {
+        MusicManager::new()
+    }
+}
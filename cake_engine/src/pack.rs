@@ -0,0 +1,284 @@
+//! Архив ассетов.
+//!
+//! Простой собственный формат, чтобы можно было распространять все ассеты одним файлом
+//! и проверять их целостность. Архив не сжимается: большая часть ассетов (PNG, OGG)
+//! и так сжата.
+//!
+//! Формат (все числа little-endian):
+//!
+//! * сигнатура `CAKEPACK`;
+//! * версия формата (`u32`, сейчас 1);
+//! * количество файлов (`u32`);
+//! * для каждого файла: длина пути (`u16`), путь в UTF-8 с разделителем `/`, смещение данных
+//!   от начала архива (`u64`), размер (`u64`) и CRC32 содержимого (`u32`);
+//! * CRC32 всего, что было до этого момента (`u32`);
+//! * содержимое файлов.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use cake_engine::{fs::{self, Mount}, pack::{self, Pack}};
+//!
+//! // При сборке дистрибутива
+//! pack::write_pack("data", "data.pak").unwrap();
+//!
+//! // При запуске игры
+//! let pack = Pack::open("data.pak").unwrap();
+//! pack.verify().unwrap();
+//! fs::mount(Mount::Pack(pack));
+//! ```
+
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
+};
+
+const MAGIC: &[u8; 8] = b"CAKEPACK";
+const VERSION: u32 = 1;
+// Запись в списке файлов с пустым путём: длина пути, смещение, размер и CRC32
+const MIN_ENTRY_SIZE: u64 = 2 + 8 + 8 + 4;
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    offset: u64,
+    size: u64,
+    crc: u32,
+}
+
+/// Открытый архив ассетов.
+///
+/// При открытии читается только список файлов, а содержимое читается с диска при каждом
+/// обращении и сверяется с контрольной суммой.
+#[derive(Clone, Debug)]
+pub struct Pack {
+    path: PathBuf,
+    entries: HashMap<String, Entry>,
+}
+
+impl Pack {
+    /// Открывает архив и читает список файлов.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Pack> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = File::open(&path)?;
+        let file_len = file.metadata()?.len();
+        let mut header = Vec::new();
+
+        let magic = read_exact(&mut file, &mut header, 8)?;
+        if magic != MAGIC {
+            return Err(anyhow::anyhow!("{:?} is not an asset pack", path));
+        }
+        let version = read_u32(&mut file, &mut header)?;
+        if version != VERSION {
+            return Err(anyhow::anyhow!(
+                "{:?}: unsupported pack version {}",
+                path,
+                version
+            ));
+        }
+
+        // Количеству файлов до проверки контрольной суммы верить нельзя, но больше файлов,
+        // чем влезает в архив, там точно нет
+        let count = read_u32(&mut file, &mut header)?;
+        if count as u64 > file_len / MIN_ENTRY_SIZE {
+            return Err(anyhow::anyhow!("{:?}: file list is corrupted", path));
+        }
+
+        let mut index = Vec::new();
+        for _ in 0..count {
+            let name_len =
+                u16::from_le_bytes(read_exact(&mut file, &mut header, 2)?.try_into().unwrap());
+            let name = read_exact(&mut file, &mut header, name_len as usize)?;
+            let offset = read_u64(&mut file, &mut header)?;
+            let size = read_u64(&mut file, &mut header)?;
+            let crc = read_u32(&mut file, &mut header)?;
+            index.push((name, Entry { offset, size, crc }));
+        }
+
+        let index_crc = read_u32(&mut file, &mut Vec::new())?;
+        if index_crc != crc32(&header) {
+            return Err(anyhow::anyhow!("{:?}: file list is corrupted", path));
+        }
+
+        let mut entries = HashMap::new();
+        for (name, entry) in index {
+            let name = String::from_utf8(name)?;
+            let in_bounds = match entry.offset.checked_add(entry.size) {
+                Some(end) => end <= file_len,
+                None => false,
+            };
+            if !in_bounds {
+                return Err(anyhow::anyhow!("{:?}: {} is out of bounds", path, name));
+            }
+            entries.insert(name, entry);
+        }
+
+        Ok(Pack { path, entries })
+    }
+
+    /// Путь к файлу архива.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Есть ли в архиве указанный файл.
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        match normalize_path(path.as_ref()) {
+            Some(name) => self.entries.contains_key(&name),
+            None => false,
+        }
+    }
+
+    /// Список всех файлов в архиве в алфавитном порядке.
+    pub fn files(&self) -> Vec<String> {
+        let mut result: Vec<String> = self.entries.keys().cloned().collect();
+        result.sort();
+        result
+    }
+
+    /// Читает файл из архива. Возвращает `None`, если такого файла в архиве нет, и ошибку,
+    /// если содержимое не совпадает с контрольной суммой.
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> Result<Option<Vec<u8>>> {
+        let name = match normalize_path(path.as_ref()) {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        let entry = match self.entries.get(&name) {
+            Some(entry) => *entry,
+            None => return Ok(None),
+        };
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(entry.offset))?;
+        let mut data = vec![0; entry.size as usize];
+        file.read_exact(&mut data)?;
+
+        if crc32(&data) != entry.crc {
+            return Err(anyhow::anyhow!("{:?}: {} is corrupted", self.path, name));
+        }
+        Ok(Some(data))
+    }
+
+    /// Читает и проверяет все файлы архива.
+    pub fn verify(&self) -> Result<()> {
+        for name in self.files() {
+            self.read(&name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Упаковывает все файлы из каталога `src` (включая подкаталоги) в архив `dst`.
+///
+/// Все файлы целиком читаются в память, так что для гигабайтных каталогов функция
+/// не подходит.
+pub fn write_pack<P1: AsRef<Path>, P2: AsRef<Path>>(src: P1, dst: P2) -> Result<()> {
+    let mut files = Vec::new();
+    collect_files(src.as_ref(), "", &mut files)?;
+    files.sort();
+
+    let mut contents = Vec::with_capacity(files.len());
+    for name in &files {
+        contents.push(std::fs::read(src.as_ref().join(name))?);
+    }
+
+    let mut header = Vec::new();
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    header.extend_from_slice(&(files.len() as u32).to_le_bytes());
+
+    // Данные начинаются сразу после списка файлов и его контрольной суммы
+    let index_size: usize = files.iter().map(|name| 2 + name.len() + 8 + 8 + 4).sum();
+    let mut offset = (header.len() + index_size + 4) as u64;
+
+    for (name, data) in files.iter().zip(contents.iter()) {
+        if name.len() > u16::MAX as usize {
+            return Err(anyhow::anyhow!("Path is too long: {}", name));
+        }
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+        header.extend_from_slice(&offset.to_le_bytes());
+        header.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        header.extend_from_slice(&crc32(data).to_le_bytes());
+        offset += data.len() as u64;
+    }
+    let index_crc = crc32(&header);
+
+    let mut file = File::create(dst)?;
+    file.write_all(&header)?;
+    file.write_all(&index_crc.to_le_bytes())?;
+    for data in &contents {
+        file.write_all(data)?;
+    }
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Приводит путь к ассету к виду, в котором он хранится в архиве: компоненты через `/`, без
+/// `.` в начале. Возвращает `None` для абсолютных путей и путей с `..`.
+pub(crate) fn normalize_path(path: &Path) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    Some(parts.join("/"))
+}
+
+/// CRC32 (тот же, что в zip и PNG).
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn collect_files(dir: &Path, prefix: &str, files: &mut Vec<String>) -> Result<()> {
+    for item in std::fs::read_dir(dir)? {
+        let item = item?;
+        let name = match item.file_name().to_str() {
+            Some(name) => format!("{}{}", prefix, name),
+            None => return Err(anyhow::anyhow!("Invalid file name: {:?}", item.path())),
+        };
+        if item.file_type()?.is_dir() {
+            collect_files(&item.path(), &format!("{}/", name), files)?;
+        } else {
+            files.push(name);
+        }
+    }
+    Ok(())
+}
+
+// Читает указанное количество байт и заодно дописывает их в header для подсчёта контрольной суммы
+fn read_exact(file: &mut File, header: &mut Vec<u8>, len: usize) -> Result<Vec<u8>> {
+    let mut buf = vec![0; len];
+    file.read_exact(&mut buf)?;
+    header.extend_from_slice(&buf);
+    Ok(buf)
+}
+
+fn read_u32(file: &mut File, header: &mut Vec<u8>) -> Result<u32> {
+    Ok(u32::from_le_bytes(
+        read_exact(file, header, 4)?.try_into().unwrap(),
+    ))
+}
+
+fn read_u64(file: &mut File, header: &mut Vec<u8>) -> Result<u64> {
+    Ok(u64::from_le_bytes(
+        read_exact(file, header, 8)?.try_into().unwrap(),
+    ))
+}
@@ -0,0 +1,270 @@
+//! Система частиц.
+//!
+//! [`ParticleEmitter`] порождает частицы — маленькие копии одной текстуры, которые летят,
+//! падают, вращаются, меняют цвет и размер и исчезают по истечении времени жизни. Частицы
+//! можно выпускать непрерывно с заданной частотой или пачкой через [`ParticleEmitter::burst`].
+//!
+//! Все параметры, заданные парой `(min, max)`, выбираются для каждой частицы случайно
+//! в этих пределах.
+//!
+//! ```
+//! use std::path::Path;
+//! use cake_engine::{
+//!     color::Color,
+//!     particles::{ParticleEmitter, ParticleParams},
+//!     texture::TextureOptions,
+//!     vec::Vec2,
+//! };
+//! # use cake_engine::{conf::Conf, context::Context, dummy::DummyContext};
+//! # let mut dctx = DummyContext::new(&Conf::default());
+//! # let mut ctx: &mut dyn Context = &mut dctx;
+//!
+//! let texture = ctx.load_texture_file(
+//!     Path::new("images/particle.png"),
+//!     TextureOptions::default()
+//! ).unwrap();
+//!
+//! let mut sparks = ParticleEmitter::new(texture, ParticleParams {
+//!     lifetime: (0.2, 0.5),
+//!     velocity: (Vec2::new(-100.0, -200.0), Vec2::new(100.0, -50.0)),
+//!     gravity: (Vec2::new(0.0, 400.0), Vec2::new(0.0, 400.0)),
+//!     color: (Color::WHITE, Color::new(255, 200, 0, 0)),
+//!     ..Default::default()
+//! });
+//!
+//! sparks.set_position(Vec2::new(100.0, 100.0));
+//! sparks.burst(20);
+//! assert_eq!(sparks.particle_count(), 20);
+//!
+//! // В process сцены
+//! sparks.process(1.0 / 60.0);
+//! // В render сцены
+//! sparks.render(ctx).unwrap();
+//! ```
+
+use crate::{
+    color::Color,
+    context::{Context, DrawTextureParams},
+    rng::Rng,
+    texture::Texture,
+    tween::{Easing, Lerp},
+    vec::Vec2,
+};
+use anyhow::Result;
+use std::rc::Rc;
+
+/// Параметры частиц эмиттера.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParticleParams {
+    /// Сколько частиц в секунду выпускать, пока эмиттер включен.
+    pub rate: f32,
+    /// Больше стольких живых частиц эмиттер не выпускает.
+    pub max_particles: usize,
+    /// Время жизни частицы в секундах.
+    pub lifetime: (f32, f32),
+    /// Смещение места появления частицы относительно позиции эмиттера.
+    pub offset: (Vec2, Vec2),
+    /// Начальная скорость в пикселях в секунду.
+    pub velocity: (Vec2, Vec2),
+    /// Ускорение в пикселях в секунду за секунду.
+    pub gravity: (Vec2, Vec2),
+    /// Начальный поворот в градусах по часовой стрелке.
+    pub rotation: (f32, f32),
+    /// Скорость вращения в градусах в секунду.
+    pub angular_velocity: (f32, f32),
+    /// Цвет в начале и в конце жизни частицы.
+    pub color: (Color, Color),
+    /// Масштаб текстуры в начале и в конце жизни частицы.
+    pub scale: (f32, f32),
+    /// Как цвет и масштаб меняются в течение жизни частицы.
+    pub easing: Easing,
+}
+
+impl Default for ParticleParams {
+    fn default() -> Self {
+        ParticleParams {
+            rate: 0.0,
+            max_particles: 256,
+            lifetime: (1.0, 1.0),
+            offset: (Vec2::new(0.0, 0.0), Vec2::new(0.0, 0.0)),
+            velocity: (Vec2::new(0.0, 0.0), Vec2::new(0.0, 0.0)),
+            gravity: (Vec2::new(0.0, 0.0), Vec2::new(0.0, 0.0)),
+            rotation: (0.0, 0.0),
+            angular_velocity: (0.0, 0.0),
+            color: (Color::WHITE, Color::WHITE),
+            scale: (1.0, 1.0),
+            easing: Easing::Linear,
+        }
+    }
+}
+
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    gravity: Vec2,
+    rotation: f32,
+    angular_velocity: f32,
+    age: f32,
+    lifetime: f32,
+}
+
+/// Источник частиц. Подробнее в [документации модуля](self).
+pub struct ParticleEmitter {
+    texture: Rc<Texture>,
+    params: ParticleParams,
+    position: Vec2,
+    emitting: bool,
+    particles: Vec<Particle>,
+    // Накопившаяся дробная часть частиц, которые пора выпустить
+    pending: f32,
+    rng: Rng,
+}
+
+impl ParticleEmitter {
+    /// Создаёт эмиттер. Если в параметрах указана ненулевая частота, эмиттер сразу начинает
+    /// выпускать частицы.
+    pub fn new(texture: Rc<Texture>, params: ParticleParams) -> ParticleEmitter {
+        ParticleEmitter {
+            texture,
+            params,
+            position: Vec2::new(0.0, 0.0),
+            emitting: true,
+            particles: Vec::new(),
+            pending: 0.0,
+            rng: Rng::new(),
+        }
+    }
+
+    /// Задаёт зерно для случайных чисел, чтобы частицы вели себя одинаково при каждом запуске.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng.set_seed(seed);
+    }
+
+    pub fn params(&self) -> &ParticleParams {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut ParticleParams {
+        &mut self.params
+    }
+
+    /// Место, в котором появляются новые частицы. Уже выпущенные частицы при его изменении
+    /// не двигаются.
+    pub fn get_position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+    }
+
+    /// Выпускает ли эмиттер частицы с частотой `rate`.
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+
+    /// Включает или выключает непрерывный выпуск частиц. Уже выпущенные частицы доживают
+    /// своё как обычно.
+    pub fn set_emitting(&mut self, emitting: bool) {
+        if !emitting {
+            self.pending = 0.0;
+        }
+        self.emitting = emitting;
+    }
+
+    /// Сразу выпускает `count` частиц (но не больше, чем позволяет `max_particles`).
+    pub fn burst(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.particles.len() >= self.params.max_particles {
+                break;
+            }
+            self.spawn();
+        }
+    }
+
+    /// Число живых частиц.
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Удаляет все частицы.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.pending = 0.0;
+    }
+
+    /// Двигает частицы, удаляет умершие и выпускает новые. Вызывайте в `process` сцены.
+    pub fn process(&mut self, dt: f32) {
+        for p in self.particles.iter_mut() {
+            p.age += dt;
+            p.velocity.x += p.gravity.x * dt;
+            p.velocity.y += p.gravity.y * dt;
+            p.position.x += p.velocity.x * dt;
+            p.position.y += p.velocity.y * dt;
+            p.rotation += p.angular_velocity * dt;
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+
+        if self.emitting && self.params.rate > 0.0 {
+            self.pending += self.params.rate * dt;
+            let count = self.pending.floor();
+            self.pending -= count;
+            self.burst(count as usize);
+        }
+    }
+
+    /// Рисует все живые частицы.
+    pub fn render(&self, ctx: &mut dyn Context) -> Result<()> {
+        let (color_start, color_end) = self.params.color;
+        let (scale_start, scale_end) = self.params.scale;
+
+        let params: Vec<DrawTextureParams> = self
+            .particles
+            .iter()
+            .map(|p| {
+                let t = if p.lifetime > 0.0 {
+                    self.params.easing.apply(p.age / p.lifetime)
+                } else {
+                    1.0
+                };
+                let scale = f32::lerp(scale_start, scale_end, t);
+                DrawTextureParams {
+                    origin: Vec2::new(0.5, 0.5),
+                    position: p.position,
+                    rotation: p.rotation,
+                    scale: Vec2::new(scale, scale),
+                    color: Color::lerp(color_start, color_end, t),
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        ctx.draw_texture_batch(&self.texture, &params)
+    }
+
+    fn spawn(&mut self) {
+        let params = self.params;
+        let offset = self.random_vec2(params.offset);
+        let particle = Particle {
+            position: Vec2::new(self.position.x + offset.x, self.position.y + offset.y),
+            velocity: self.random_vec2(params.velocity),
+            gravity: self.random_vec2(params.gravity),
+            rotation: self.random_f32(params.rotation),
+            angular_velocity: self.random_f32(params.angular_velocity),
+            age: 0.0,
+            lifetime: self.random_f32(params.lifetime),
+        };
+        self.particles.push(particle);
+    }
+
+    fn random_f32(&mut self, (min, max): (f32, f32)) -> f32 {
+        f32::lerp(min, max, self.rng.next_unit_f32())
+    }
+
+    fn random_vec2(&mut self, (min, max): (Vec2, Vec2)) -> Vec2 {
+        Vec2::new(
+            self.random_f32((min.x, max.x)),
+            self.random_f32((min.y, max.y)),
+        )
+    }
+}
@@ -0,0 +1,597 @@
+//! Обёртка над контекстом, записывающая все вызовы рисования и воспроизведения звука.
+//!
+//! Полезна для дешёвых тестов, которым не нужны пиксели: достаточно завернуть в
+//! [`RecordingContext`] любой контекст (например, `DummyContext`), вызвать `render` у сцены
+//! и посмотреть, что она нарисовала.
+//!
+//! ```
+//! # use cake_engine::{conf::Conf, context::Context, dummy::DummyContext, rect::Rect, color::Color};
+//! # use cake_engine::recording::{RecordedCommand, RecordingContext};
+//! let mut ctx = RecordingContext::new(DummyContext::new(&Conf::default()));
+//! ctx.set_fill_color(Color::RED);
+//! ctx.fill_rect(Rect::new(0.0, 0.0, 10.0, 10.0)).unwrap();
+//!
+//! assert_eq!(
+//!     ctx.commands(),
+//!     &[RecordedCommand::FillRect {
+//!         rect: Rect::new(0.0, 0.0, 10.0, 10.0),
+//!         color: Color::RED,
+//!     }]
+//! );
+//! ```
+
+use crate::{
+    audio::{Music, Sound, Voice},
+    color::Color,
+    context::{Context, DrawTextureParams, PlaySoundParams},
+    font::Font,
+    gametime::GameTime,
+    hot_reload::HotReload,
+    image::Image,
+    input::Input,
+    rect::Rect,
+    texture::{Texture, TextureOptions, TextureSource},
+    transform::Transform,
+    vec::Vec2,
+    view::View,
+};
+use anyhow::Result;
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// Записанный вызов контекста.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordedCommand {
+    /// Очистка окна цветом.
+    Clear {
+        color: Color,
+    },
+    /// Рисование прямоугольника цветом.
+    FillRect {
+        rect: Rect,
+        color: Color,
+    },
+    /// Рисование многоугольника цветом (через него же рисуются линии, круги и прочие фигуры,
+    /// если бэкенд не рисует их сам).
+    FillPolygon {
+        points: Vec<Vec2>,
+        color: Color,
+    },
+    /// Рисование текстуры. Для текстур, загруженных не из файла (например, созданных
+    /// через `draw_text_to_texture`), путь отсутствует.
+    DrawTexture {
+        path: Option<PathBuf>,
+        size: Vec2,
+        params: DrawTextureParams,
+    },
+    /// Рисование строки текста.
+    DrawText {
+        text: String,
+        font_path: PathBuf,
+        font_size: u16,
+        color: Color,
+        position: Vec2,
+        scale: Vec2,
+    },
+    PlayMusic {
+        path: PathBuf,
+        volume: f32,
+        looping: bool,
+    },
+    SetMusicVolume {
+        volume: f32,
+    },
+    StopMusic,
+    PlayMusicKeepingPrevious {
+        path: PathBuf,
+        volume: f32,
+        looping: bool,
+    },
+    SetPreviousMusicVolume {
+        volume: f32,
+    },
+    StopPreviousMusic,
+    PlaySound {
+        path: PathBuf,
+        params: PlaySoundParams,
+    },
+    StopSound {
+        path: PathBuf,
+    },
+    StopVoice {
+        voice: Voice,
+    },
+    SetVoiceVolume {
+        voice: Voice,
+        volume: f32,
+    },
+    SetVoiceLooping {
+        voice: Voice,
+        looping: bool,
+    },
+    SetVoicePan {
+        voice: Voice,
+        pan: f32,
+    },
+    /// Начало рисования в текстуру указанного размера.
+    BeginTarget {
+        size: Vec2,
+    },
+    /// Конец рисования в текстуру.
+    EndTarget,
+    /// Добавление преобразования в стек. Вызовы рисования записываются как есть, без учёта
+    /// преобразований.
+    PushTransform {
+        transform: Transform,
+    },
+    /// Удаление преобразования из стека.
+    PopTransform,
+}
+
+/// Контекст, который передаёт все вызовы во вложенный контекст и попутно записывает вызовы
+/// рисования и воспроизведения звука.
+///
+/// Подробнее в [документации модуля](self).
+pub struct RecordingContext<C: Context> {
+    inner: C,
+    fill_color: Color,
+    commands: Vec<RecordedCommand>,
+}
+
+impl<C: Context> RecordingContext<C> {
+    pub fn new(inner: C) -> RecordingContext<C> {
+        RecordingContext {
+            inner,
+            fill_color: Color::BLACK,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Вложенный контекст.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Мутабельная ссылка на вложенный контекст (например, чтобы передать ему события ввода).
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Забирает вложенный контекст, выбрасывая записанные вызовы.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    /// Все записанные с момента создания или последней очистки вызовы.
+    pub fn commands(&self) -> &[RecordedCommand] {
+        &self.commands
+    }
+
+    /// Забирает записанные вызовы, очищая список.
+    pub fn take_commands(&mut self) -> Vec<RecordedCommand> {
+        std::mem::take(&mut self.commands)
+    }
+
+    /// Очищает список записанных вызовов.
+    pub fn clear_commands(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Возвращает параметры всех вызовов рисования текстуры, загруженной из указанного файла.
+    pub fn texture_draws(&self, path: &Path) -> Vec<&DrawTextureParams> {
+        self.commands
+            .iter()
+            .filter_map(|c| match c {
+                RecordedCommand::DrawTexture {
+                    path: Some(p),
+                    params,
+                    ..
+                } if p == path => Some(params),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl<C: Context> Context for RecordingContext<C> {
+    fn get_backend_name(&self) -> &'static str {
+        self.inner.get_backend_name()
+    }
+
+    // time
+
+    fn time(&self) -> &GameTime {
+        self.inner.time()
+    }
+
+    fn time_mut(&mut self) -> &mut GameTime {
+        self.inner.time_mut()
+    }
+
+    // input
+
+    fn input(&self) -> &Input {
+        self.inner.input()
+    }
+
+    // view
+
+    fn view(&self) -> &View {
+        self.inner.view()
+    }
+
+    fn view_mut(&mut self) -> &mut View {
+        self.inner.view_mut()
+    }
+
+    // transform
+
+    fn push_transform(&mut self, transform: Transform) {
+        self.commands
+            .push(RecordedCommand::PushTransform { transform });
+        self.inner.push_transform(transform);
+    }
+
+    fn pop_transform(&mut self) -> Result<()> {
+        self.commands.push(RecordedCommand::PopTransform);
+        self.inner.pop_transform()
+    }
+
+    fn get_transform(&self) -> Transform {
+        self.inner.get_transform()
+    }
+
+    // window
+
+    fn get_dpi_scale(&self) -> Vec2 {
+        self.inner.get_dpi_scale()
+    }
+
+    fn get_logical_window_size(&self) -> Vec2 {
+        self.inner.get_logical_window_size()
+    }
+
+    fn get_physical_window_size(&self) -> (u32, u32) {
+        self.inner.get_physical_window_size()
+    }
+
+    fn get_fullscreen(&self) -> bool {
+        self.inner.get_fullscreen()
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) -> Result<()> {
+        self.inner.set_fullscreen(fullscreen)
+    }
+
+    fn get_vsync(&self) -> bool {
+        self.inner.get_vsync()
+    }
+
+    fn set_vsync(&mut self, vsync: bool) -> Result<()> {
+        self.inner.set_vsync(vsync)
+    }
+
+    fn set_fps_limit(&mut self, value: f32) {
+        self.inner.set_fps_limit(value);
+    }
+
+    fn get_mouse_cursor_visibility(&self) -> bool {
+        self.inner.get_mouse_cursor_visibility()
+    }
+
+    fn set_mouse_cursor_visibility(&mut self, visible: bool) -> Result<()> {
+        self.inner.set_mouse_cursor_visibility(visible)
+    }
+
+    fn is_simulating_mouse_with_touch(&self) -> bool {
+        self.inner.is_simulating_mouse_with_touch()
+    }
+
+    fn simulate_mouse_with_touch(&mut self, enabled: bool) {
+        self.inner.simulate_mouse_with_touch(enabled);
+    }
+
+    // drawing
+
+    fn set_fill_color(&mut self, color: Color) {
+        self.fill_color = color;
+        self.inner.set_fill_color(color);
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.commands.push(RecordedCommand::Clear {
+            color: self.fill_color,
+        });
+        self.inner.clear()
+    }
+
+    fn fill_rect(&mut self, rect: Rect) -> Result<()> {
+        self.commands.push(RecordedCommand::FillRect {
+            rect,
+            color: self.fill_color,
+        });
+        self.inner.fill_rect(rect)
+    }
+
+    fn fill_polygon(&mut self, points: &[Vec2]) -> Result<()> {
+        self.commands.push(RecordedCommand::FillPolygon {
+            points: points.to_vec(),
+            color: self.fill_color,
+        });
+        self.inner.fill_polygon(points)
+    }
+
+    fn draw_texture_ex(&mut self, texture: &Texture, params: DrawTextureParams) -> Result<()> {
+        let path = match texture.source() {
+            TextureSource::File(p) | TextureSource::LangFile(p) => Some(p.clone()),
+            TextureSource::None | TextureSource::Data(_) => None,
+        };
+        self.commands.push(RecordedCommand::DrawTexture {
+            path,
+            size: texture.size_vec(),
+            params,
+        });
+        self.inner.draw_texture_ex(texture, params)
+    }
+
+    fn draw_texture_batch(
+        &mut self,
+        texture: &Texture,
+        params: &[DrawTextureParams],
+    ) -> Result<()> {
+        // Записываем как отдельные вызовы, чтобы тестам было всё равно, как именно рисовали
+        let path = match texture.source() {
+            TextureSource::File(p) | TextureSource::LangFile(p) => Some(p.clone()),
+            TextureSource::None | TextureSource::Data(_) => None,
+        };
+        for p in params {
+            self.commands.push(RecordedCommand::DrawTexture {
+                path: path.clone(),
+                size: texture.size_vec(),
+                params: *p,
+            });
+        }
+        self.inner.draw_texture_batch(texture, params)
+    }
+
+    fn draw_text_to_texture(
+        &mut self,
+        text: &str,
+        font: &Font,
+        color: Color,
+        smooth: bool,
+    ) -> Result<Option<Rc<Texture>>> {
+        self.inner.draw_text_to_texture(text, font, color, smooth)
+    }
+
+    fn draw_text(
+        &mut self,
+        text: &str,
+        font: &Font,
+        color: Color,
+        smooth: bool,
+        position: Vec2,
+        scale: Vec2,
+    ) -> Result<()> {
+        self.commands.push(RecordedCommand::DrawText {
+            text: text.to_string(),
+            font_path: font.path().to_path_buf(),
+            font_size: font.size(),
+            color,
+            position,
+            scale,
+        });
+        self.inner
+            .draw_text(text, font, color, smooth, position, scale)
+    }
+
+    fn capture_screenshot(&mut self) -> Result<Image> {
+        self.inner.capture_screenshot()
+    }
+
+    fn begin_target(&mut self, target: &Texture) -> Result<()> {
+        self.commands.push(RecordedCommand::BeginTarget {
+            size: target.size_vec(),
+        });
+        self.inner.begin_target(target)
+    }
+
+    fn end_target(&mut self) -> Result<()> {
+        self.commands.push(RecordedCommand::EndTarget);
+        self.inner.end_target()
+    }
+
+    // audio
+
+    fn play_music(&mut self, music: &Music, volume: f32, looping: bool) -> Result<()> {
+        self.commands.push(RecordedCommand::PlayMusic {
+            path: music.path().to_path_buf(),
+            volume,
+            looping,
+        });
+        self.inner.play_music(music, volume, looping)
+    }
+
+    fn set_music_volume(&mut self, volume: f32) -> Result<()> {
+        self.commands
+            .push(RecordedCommand::SetMusicVolume { volume });
+        self.inner.set_music_volume(volume)
+    }
+
+    fn stop_music(&mut self) -> Result<()> {
+        self.commands.push(RecordedCommand::StopMusic);
+        self.inner.stop_music()
+    }
+
+    fn play_music_keeping_previous(
+        &mut self,
+        music: &Music,
+        volume: f32,
+        looping: bool,
+    ) -> Result<()> {
+        self.commands
+            .push(RecordedCommand::PlayMusicKeepingPrevious {
+                path: music.path().to_path_buf(),
+                volume,
+                looping,
+            });
+        self.inner
+            .play_music_keeping_previous(music, volume, looping)
+    }
+
+    fn set_previous_music_volume(&mut self, volume: f32) -> Result<()> {
+        self.commands
+            .push(RecordedCommand::SetPreviousMusicVolume { volume });
+        self.inner.set_previous_music_volume(volume)
+    }
+
+    fn stop_previous_music(&mut self) -> Result<()> {
+        self.commands.push(RecordedCommand::StopPreviousMusic);
+        self.inner.stop_previous_music()
+    }
+
+    fn get_playing_music(&self) -> Result<Option<Rc<Music>>> {
+        self.inner.get_playing_music()
+    }
+
+    fn play_sound_ex(&mut self, sound: &Sound, params: PlaySoundParams) -> Result<Voice> {
+        self.commands.push(RecordedCommand::PlaySound {
+            path: sound.path().to_path_buf(),
+            params,
+        });
+        self.inner.play_sound_ex(sound, params)
+    }
+
+    fn stop_sound(&mut self, sound: &Sound) -> Result<()> {
+        self.commands.push(RecordedCommand::StopSound {
+            path: sound.path().to_path_buf(),
+        });
+        self.inner.stop_sound(sound)
+    }
+
+    fn stop_voice(&mut self, voice: Voice) -> Result<()> {
+        self.commands.push(RecordedCommand::StopVoice { voice });
+        self.inner.stop_voice(voice)
+    }
+
+    fn set_voice_volume(&mut self, voice: Voice, volume: f32) -> Result<()> {
+        self.commands
+            .push(RecordedCommand::SetVoiceVolume { voice, volume });
+        self.inner.set_voice_volume(voice, volume)
+    }
+
+    fn set_voice_looping(&mut self, voice: Voice, looping: bool) -> Result<()> {
+        self.commands
+            .push(RecordedCommand::SetVoiceLooping { voice, looping });
+        self.inner.set_voice_looping(voice, looping)
+    }
+
+    fn set_voice_pan(&mut self, voice: Voice, pan: f32) -> Result<()> {
+        self.commands
+            .push(RecordedCommand::SetVoicePan { voice, pan });
+        self.inner.set_voice_pan(voice, pan)
+    }
+
+    fn is_voice_playing(&self, voice: Voice) -> bool {
+        self.inner.is_voice_playing(voice)
+    }
+
+    // resources
+
+    fn drop_unused_resources(&mut self) {
+        self.inner.drop_unused_resources();
+    }
+
+    fn hot_reload(&self) -> &HotReload {
+        self.inner.hot_reload()
+    }
+
+    fn hot_reload_mut(&mut self) -> &mut HotReload {
+        self.inner.hot_reload_mut()
+    }
+
+    fn reload_changed_resources(&mut self) -> Result<()> {
+        self.inner.reload_changed_resources()
+    }
+
+    fn reload_lang_resources(&mut self) -> Result<()> {
+        self.inner.reload_lang_resources()
+    }
+
+    // resources - textures
+
+    fn is_texture_valid(&self, texture: &Texture) -> bool {
+        self.inner.is_texture_valid(texture)
+    }
+
+    fn load_texture(
+        &mut self,
+        source: TextureSource,
+        options: TextureOptions,
+    ) -> Result<Rc<Texture>> {
+        self.inner.load_texture(source, options)
+    }
+
+    fn create_render_target(
+        &mut self,
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+    ) -> Result<Rc<Texture>> {
+        self.inner.create_render_target(width, height, options)
+    }
+
+    fn drop_texture_if_unused(&mut self, texture: Rc<Texture>) -> bool {
+        self.inner.drop_texture_if_unused(texture)
+    }
+
+    fn drop_unused_textures(&mut self) {
+        self.inner.drop_unused_textures();
+    }
+
+    fn reload_lang_textures(&mut self) -> Result<()> {
+        self.inner.reload_lang_textures()
+    }
+
+    // resources - fonts
+
+    fn load_ttf_file(&mut self, path: &Path, size: u16) -> Result<Rc<Font>> {
+        self.inner.load_ttf_file(path, size)
+    }
+
+    fn drop_unused_fonts(&mut self) {
+        self.inner.drop_unused_fonts();
+    }
+
+    fn get_text_size(&self, text: &str, font: &Font) -> Result<Vec2> {
+        self.inner.get_text_size(text, font)
+    }
+
+    fn get_font_metrics(&self, font: &Font) -> Result<(f32, f32)> {
+        self.inner.get_font_metrics(font)
+    }
+
+    fn get_font_line_height(&self, font: &Font) -> Result<f32> {
+        self.inner.get_font_line_height(font)
+    }
+
+    // resources - audio
+
+    fn load_music_file(&mut self, path: &Path) -> Result<Rc<Music>> {
+        self.inner.load_music_file(path)
+    }
+
+    fn drop_unused_music(&mut self) {
+        self.inner.drop_unused_music();
+    }
+
+    fn load_sound_file(&mut self, path: &Path) -> Result<Rc<Sound>> {
+        self.inner.load_sound_file(path)
+    }
+
+    fn drop_unused_sounds(&mut self) {
+        self.inner.drop_unused_sounds();
+    }
+}
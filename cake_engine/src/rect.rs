@@ -59,6 +59,16 @@ impl Rect {
         Vec2::new(self.x + self.width / 2.0, self.y + self.height / 2.0)
     }
 
+    /// Углы прямоугольника по часовой стрелке, начиная с левого верхнего.
+    pub fn get_corners(&self) -> [Vec2; 4] {
+        [
+            Vec2::new(self.x, self.y),
+            Vec2::new(self.x + self.width, self.y),
+            Vec2::new(self.x + self.width, self.y + self.height),
+            Vec2::new(self.x, self.y + self.height),
+        ]
+    }
+
     /// Является ли прямоугольник вырожденным (ширина и/или высота — ноль).
     pub fn is_degenerate(&self) -> bool {
         self.width == 0.0 || self.height == 0.0
@@ -0,0 +1,32 @@
+// Простенький генератор случайных чисел (xorshift64) для мест, где важна не случайность,
+// а повторяемость при одинаковом зерне: разлёт частиц, перемешивание плейлиста.
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new() -> Rng {
+        Rng {
+            state: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        // Нулевое состояние xorshift так и останется нулевым
+        self.state = seed.max(1);
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Число от 0 до 1 (не включая 1).
+    pub fn next_unit_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
@@ -11,6 +11,11 @@
 //! В методе `process` вы возвращаете движку значение [`SceneResult`](self::SceneResult), которое
 //! сообщает ему, что делать дальше.
 //!
+//! Сцены хранятся в стеке: `SceneResult::Push` кладёт новую сцену поверх текущей, а
+//! `SceneResult::Pop` убирает текущую и возвращает управление сцене под ней. Обрабатывается
+//! только верхняя сцена стека, но если она [прозрачная](self::Scene::is_overlay), то под ней
+//! рендерятся и нижние сцены — так можно сделать меню паузы или диалог поверх игры.
+//!
 //! Не забывайте обработать событие выхода из игры (через список `events` или метод
 //! `ctx.input().is_quit_requested()`), чтобы пользователь мог завершить игру.
 //!
@@ -112,7 +117,11 @@
 //! # assert!(matches!(scene.process(&mut dctx, 1.0 / 60.0, &[Event::Quit]), Ok(SceneResult::Quit)));
 //! ```
 
-use crate::{context::Context, input::Event};
+use crate::{
+    context::Context,
+    input::Event,
+    transition::{ActiveTransition, Transition},
+};
 use anyhow::Result;
 use std::fmt::{Debug, Formatter};
 
@@ -125,6 +134,16 @@ pub enum SceneResult {
     Quit,
     /// Переключение на другую сцену. Текущая сцена отрендерена НЕ будет.
     Switch(Box<dyn Scene>),
+    /// Переключение на другую сцену с анимированным [переходом](crate::transition). Текущая
+    /// сцена рендерится до конца перехода и останавливается после него, в метод `start` новой
+    /// сцены она не передаётся.
+    SwitchWith(Box<dyn Scene>, Transition),
+    /// Новая сцена кладётся в стек поверх текущей. Текущая сцена не останавливается, но и
+    /// не обрабатывается, пока новая сцена не будет убрана из стека.
+    Push(Box<dyn Scene>),
+    /// Текущая сцена останавливается и убирается из стека, управление возвращается сцене под
+    /// ней. Если стек опустел, игра закрывается.
+    Pop,
 }
 
 impl Debug for SceneResult {
@@ -133,10 +152,16 @@ impl Debug for SceneResult {
             SceneResult::Normal => "SceneResult::Normal",
             SceneResult::Quit => "SceneResult::Quit",
             SceneResult::Switch(_) => "SceneResult::Switch(...)",
+            SceneResult::SwitchWith(_, _) => "SceneResult::SwitchWith(...)",
+            SceneResult::Push(_) => "SceneResult::Push(...)",
+            SceneResult::Pop => "SceneResult::Pop",
         })
     }
 }
 
+/// Функция, создающая первую сцену игры. Её принимают функции запуска бэкендов.
+pub type SceneBuilder = dyn Fn(&mut dyn Context) -> Result<Box<dyn Scene>>;
+
 /// Сцена.
 ///
 /// Подробности и пример в [документации модуля](self).
@@ -170,19 +195,178 @@ pub trait Scene {
     /// Рисование.
     ///
     /// Движок вызывает этот метод сразу после метода [`process`](self::Scene::process), если он
-    /// вернул [`SceneResult::Normal`](self::SceneResult::Normal).
+    /// вернул [`SceneResult::Normal`](self::SceneResult::Normal). Если сцена находится в стеке
+    /// под [прозрачной](self::Scene::is_overlay) сценой, метод вызывается и без `process`.
     #[allow(unused_variables)]
     fn render(&mut self, ctx: &mut dyn Context) -> Result<()> {
         Ok(())
     }
 
     /// Этот метод вызывается перед переключением на новую сцену и вызовом метода
-    /// [`start`](self::Scene::start) у новой сцены. Можно использовать это, например,
+    /// [`start`](self::Scene::start) у новой сцены (при переключении с переходом — после его
+    /// завершения), а также перед удалением сцены из стека. Можно использовать это, например,
     /// для остановки фоновых звуков.
     #[allow(unused_variables)]
     fn stop(&mut self, ctx: &mut dyn Context) -> Result<()> {
         Ok(())
     }
+
+    /// Если возвращает `true`, то перед рендерингом этой сцены будет отрендерена сцена под ней
+    /// в стеке (обрабатываться она при этом не будет).
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// Что делать главному циклу после обработки кадра стеком сцен.
+pub(crate) enum StackResult {
+    /// Отрендерить кадр.
+    Render,
+    /// Стек изменился, кадр не рендерится.
+    Changed,
+    /// Закрыть игру.
+    Quit,
+}
+
+/// Стек сцен, который главные циклы бэкендов используют вместо одной текущей сцены.
+pub(crate) struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+    initial: bool,
+    transition: Option<ActiveTransition>,
+}
+
+impl SceneStack {
+    /// Создаёт стек с единственной сценой. Она считается начальной: следующая сцена после неё
+    /// не получит её в методе `start`.
+    pub fn new(scene: Box<dyn Scene>) -> SceneStack {
+        SceneStack {
+            scenes: vec![scene],
+            initial: true,
+            transition: None,
+        }
+    }
+
+    /// Запускает сцену и создаёт стек с ней. В отличие от `new`, сцена не считается начальной.
+    #[cfg(feature = "software")]
+    pub fn start(ctx: &mut dyn Context, mut scene: Box<dyn Scene>) -> Result<SceneStack> {
+        scene.start(ctx, None)?;
+        Ok(SceneStack {
+            scenes: vec![scene],
+            initial: false,
+            transition: None,
+        })
+    }
+
+    /// Обрабатывает верхнюю сцену и выполняет то, что она вернула.
+    pub fn process(
+        &mut self,
+        ctx: &mut dyn Context,
+        dt: f32,
+        events: &[Event],
+    ) -> Result<StackResult> {
+        ctx.reload_changed_resources()?;
+
+        // Во время перехода новая сцена только рендерится, ввод игнорируется
+        if let Some(transition) = self.transition.as_mut() {
+            if !transition.update(dt) {
+                self.transition.take().unwrap().finish(ctx)?;
+            }
+            return Ok(StackResult::Render);
+        }
+
+        let scene = match self.scenes.last_mut() {
+            Some(s) => s,
+            None => return Ok(StackResult::Quit),
+        };
+
+        let result = scene.process(ctx, dt, events)?;
+        // Сцена уже видела изменившиеся ассеты
+        ctx.hot_reload_mut().clear_changed();
+
+        match result {
+            SceneResult::Normal => Ok(StackResult::Render),
+            SceneResult::Quit => Ok(StackResult::Quit),
+            SceneResult::Switch(next_scene) => {
+                self.switch(ctx, next_scene)?;
+                Ok(StackResult::Changed)
+            }
+            SceneResult::SwitchWith(mut next_scene, transition) => {
+                // Старая сцена рисуется до конца перехода и остановится только после него,
+                // поэтому новой сцене она не передаётся
+                let prev_scene = self.scenes.pop().unwrap();
+                next_scene.start(ctx, None)?;
+                self.scenes.push(next_scene);
+                self.initial = false;
+                self.transition = Some(ActiveTransition::new(transition, prev_scene));
+                Ok(StackResult::Changed)
+            }
+            SceneResult::Push(mut next_scene) => {
+                next_scene.start(ctx, None)?;
+                self.scenes.push(next_scene);
+                self.initial = false;
+                Ok(StackResult::Changed)
+            }
+            SceneResult::Pop => {
+                let mut prev_scene = self.scenes.pop().unwrap();
+                prev_scene.stop(ctx)?;
+                drop(prev_scene);
+                ctx.drop_unused_resources();
+                if self.scenes.is_empty() {
+                    Ok(StackResult::Quit)
+                } else {
+                    Ok(StackResult::Changed)
+                }
+            }
+        }
+    }
+
+    /// Рендерит верхнюю сцену, а под ней — все сцены, которые видны через прозрачные сцены.
+    pub fn render(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        match self.transition.as_mut() {
+            Some(transition) => transition.render(ctx, |ctx| render_visible(&mut self.scenes, ctx)),
+            None => render_visible(&mut self.scenes, ctx),
+        }
+    }
+
+    fn switch(&mut self, ctx: &mut dyn Context, mut next_scene: Box<dyn Scene>) -> Result<()> {
+        let mut prev_scene = self.scenes.pop().unwrap();
+        prev_scene.stop(ctx)?;
+        next_scene.start(
+            ctx,
+            if !self.initial {
+                Some(prev_scene)
+            } else {
+                None
+            },
+        )?;
+        self.scenes.push(next_scene);
+        ctx.drop_unused_resources();
+        self.initial = false;
+        Ok(())
+    }
+
+    /// Останавливает все сцены сверху вниз.
+    #[cfg(feature = "software")]
+    pub fn stop_all(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        if let Some(transition) = self.transition.take() {
+            transition.finish(ctx)?;
+        }
+        while let Some(mut scene) = self.scenes.pop() {
+            scene.stop(ctx)?;
+        }
+        Ok(())
+    }
+}
+
+fn render_visible(scenes: &mut [Box<dyn Scene>], ctx: &mut dyn Context) -> Result<()> {
+    let mut first = scenes.len().saturating_sub(1);
+    while first > 0 && scenes[first].is_overlay() {
+        first -= 1;
+    }
+    for scene in &mut scenes[first..] {
+        scene.render(ctx)?;
+    }
+    Ok(())
 }
 
 pub(crate) struct InitialScene<'a> {
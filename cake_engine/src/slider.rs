@@ -0,0 +1,214 @@
+//! Ползунок.
+//!
+//! Полоска со значением от 0 до 1, которое меняется перетаскиванием мышью. Рисуется
+//! прямоугольниками, текстуры не нужны.
+//!
+//! # Examples
+//!
+//! ```
+//! use cake_engine::{color::Color, slider::Slider, vec::Vec2};
+//! # use cake_engine::{conf::Conf, context::Context, dummy::DummyContext};
+//! # let mut dctx = DummyContext::new(&Conf::default());
+//! # let mut ctx: &mut dyn Context = &mut dctx;
+//!
+//! // Ползунок 200x24 с центром в координатах 400/300
+//! let mut slider = Slider::new(Vec2::new(400.0, 300.0), Vec2::new(200.0, 24.0));
+//! slider.set_value(0.5);
+//! slider.set_fill_color(Color::new(35, 20, 250, 255));
+//!
+//! // В методе process вашей сцены
+//! slider.process(ctx).unwrap();
+//! if slider.just_changed() {
+//!     println!("Громкость: {}", slider.value());
+//! }
+//!
+//! // В методе render
+//! slider.render(ctx).unwrap();
+//! ```
+
+use crate::{color::Color, context::Context, input::MouseButton, rect::Rect, vec::Vec2};
+use anyhow::Result;
+
+/// Ползунок.
+///
+/// Подробности и примеры в [документации модуля](self).
+#[derive(Clone, Debug)]
+pub struct Slider {
+    position: Vec2,
+    origin: Vec2,
+    size: Vec2,
+    value: f32,
+    track_color: Color,
+    fill_color: Color,
+    knob_color: Color,
+    knob_color_hover: Color,
+    hovered: bool,
+    dragging: bool,
+    just_changed: bool,
+}
+
+impl Slider {
+    /// Создаёт новый ползунок в указанных координатах и указанного размера.
+    ///
+    /// Выравнивание по умолчанию по центру, значение 0.
+    pub fn new(position: Vec2, size: Vec2) -> Slider {
+        Slider {
+            position,
+            origin: Vec2::new(0.5, 0.5),
+            size,
+            value: 0.0,
+            track_color: Color::new(40, 40, 40, 192),
+            fill_color: Color::new(200, 200, 200, 255),
+            knob_color: Color::new(200, 200, 200, 255),
+            knob_color_hover: Color::WHITE,
+            hovered: false,
+            dragging: false,
+            just_changed: false,
+        }
+    }
+
+    /// Текущее значение от 0 до 1.
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Изменяет значение. Значения за пределами от 0 до 1 обрезаются.
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value.clamp(0.0, 1.0);
+    }
+
+    /// Текущее положение ползунка относительно опорной точки.
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    /// Изменяет положение ползунка.
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+    }
+
+    /// Текущая опорная точка (0.0 — левая/верхняя сторона, 1.0 — правая/нижняя, 0.5 — центр).
+    pub fn origin(&self) -> Vec2 {
+        self.origin
+    }
+
+    /// Изменяет опорную точку.
+    pub fn set_origin(&mut self, origin: Vec2) {
+        self.origin = origin;
+    }
+
+    /// Размер ползунка в пикселях.
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Изменяет размер ползунка.
+    pub fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+    }
+
+    /// Изменяет цвет полоски.
+    pub fn set_track_color(&mut self, color: Color) {
+        self.track_color = color;
+    }
+
+    /// Изменяет цвет заполненной части полоски.
+    pub fn set_fill_color(&mut self, color: Color) {
+        self.fill_color = color;
+    }
+
+    /// Изменяет цвет бегунка в обычном состоянии и при наведении курсора мыши.
+    pub fn set_knob_colors(&mut self, color: Color, color_hover: Color) {
+        self.knob_color = color;
+        self.knob_color_hover = color_hover;
+    }
+
+    /// Прямоугольник, занимаемый полоской.
+    pub fn get_bounding_rect(&self) -> Rect {
+        Rect::new(
+            self.position.x - self.size.x * self.origin.x,
+            self.position.y - self.size.y * self.origin.y,
+            self.size.x,
+            self.size.y,
+        )
+    }
+
+    /// Наведён ли курсор мыши на ползунок (или ползунок перетаскивается прямо сейчас).
+    pub fn is_hovered(&self) -> bool {
+        self.hovered || self.dragging
+    }
+
+    /// Перетаскивается ли ползунок прямо сейчас.
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Возвращает `true`, если значение изменилось при последнем вызове `process`.
+    pub fn just_changed(&self) -> bool {
+        self.just_changed
+    }
+
+    /// Обработка. Позволяет ползунку проверить состояние мыши и изменить значение, если его
+    /// тащат левой кнопкой мыши.
+    pub fn process(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        self.just_changed = false;
+
+        let input = ctx.input();
+        let mouse_pos = input.get_mouse_position();
+        let rect = self.get_bounding_rect();
+
+        self.hovered = input.is_mouse_entered() && rect.contains_point(mouse_pos);
+
+        if self.hovered && input.is_mouse_button_just_pressed(MouseButton::Left) {
+            self.dragging = true;
+        } else if !input.is_mouse_button_pressed(MouseButton::Left) {
+            self.dragging = false;
+        }
+
+        if self.dragging && rect.width > 0.0 {
+            let value = ((mouse_pos.x - rect.x) / rect.width).clamp(0.0, 1.0);
+            if value != self.value {
+                self.value = value;
+                self.just_changed = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Рисование ползунка.
+    pub fn render(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        let rect = self.get_bounding_rect();
+
+        // Полоска тоньше бегунка, чтобы его было видно
+        let track = Rect::new(
+            rect.x,
+            rect.y + rect.height / 3.0,
+            rect.width,
+            rect.height / 3.0,
+        );
+        ctx.set_fill_color(self.track_color);
+        ctx.fill_rect(track)?;
+
+        let filled = track.width * self.value;
+        if filled > 0.0 {
+            ctx.set_fill_color(self.fill_color);
+            ctx.fill_rect(Rect::new(track.x, track.y, filled, track.height))?;
+        }
+
+        let knob_width = rect.height / 2.0;
+        ctx.set_fill_color(if self.is_hovered() {
+            self.knob_color_hover
+        } else {
+            self.knob_color
+        });
+        ctx.fill_rect(Rect::new(
+            track.x + filled - knob_width / 2.0,
+            rect.y,
+            knob_width,
+            rect.height,
+        ))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+//! Снапшот-тесты: сцена прогоняется на [программном бэкенде](crate::software) заданное число
+//! кадров, после чего получившийся кадр сравнивается с эталонной PNG-картинкой.
+//!
+//! ```ignore
+//! let mut ctx = SoftwareContext::new(&conf);
+//! let scene = Box::new(MyScene::new(&mut ctx)?);
+//! let params = SnapshotParams {
+//!     frames: 10,
+//!     events: vec![(5, Event::MouseMove { point: Vec2::new(100.0, 100.0), touch_id: None })],
+//!     ..Default::default()
+//! };
+//! let image = run_scene(&mut ctx, scene, &params)?;
+//! check_snapshot(&image, Path::new("tests/snapshots/my_scene.png"), &params)?;
+//! ```
+//!
+//! Эталонные картинки нужно хранить в репозитории рядом с тестами. Если эталона нет, проверка
+//! завершается ошибкой; чтобы создать или перезаписать эталоны, запустите тесты
+//! с переменной окружения `CAKE_ENGINE_UPDATE_SNAPSHOTS=1`.
+//! При несовпадении рядом с эталоном сохраняются `*.actual.png` с получившимся кадром
+//! и `*.diff.png`, на которой отличающиеся пиксели закрашены красным.
+
+use crate::{
+    color::Color,
+    context::Context,
+    image::Image,
+    input::Event,
+    scene::{Scene, SceneStack, StackResult},
+    software::SoftwareContext,
+};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Переменная окружения, при установке которой эталонные картинки перезаписываются.
+pub const UPDATE_ENV: &str = "CAKE_ENGINE_UPDATE_SNAPSHOTS";
+
+/// Параметры прогона сцены и сравнения кадра.
+#[derive(Clone, Debug)]
+pub struct SnapshotParams {
+    /// Сколько раз вызвать `process` у сцены. Переключение сцены тоже считается кадром.
+    pub frames: usize,
+    /// Время кадра, передаваемое в `process`.
+    pub dt: f32,
+    /// События вместе с номером кадра, на котором они будут переданы сцене.
+    pub events: Vec<(usize, Event)>,
+    /// На сколько может отличаться каждый канал пикселя, чтобы пиксель считался совпадающим.
+    pub tolerance: u8,
+    /// Сколько пикселей может не совпасть, чтобы кадр всё равно считался совпадающим.
+    pub max_mismatched_pixels: usize,
+}
+
+impl Default for SnapshotParams {
+    fn default() -> Self {
+        SnapshotParams {
+            frames: 1,
+            dt: 1.0 / 60.0,
+            events: Vec::new(),
+            tolerance: 2,
+            max_mismatched_pixels: 0,
+        }
+    }
+}
+
+/// Результат попиксельного сравнения двух картинок.
+pub struct Comparison {
+    /// Количество отличающихся пикселей.
+    pub mismatched_pixels: usize,
+    /// Картинка-разница: совпадающие пиксели приглушены, отличающиеся закрашены красным.
+    pub diff: Image,
+}
+
+/// Прогоняет сцену и возвращает последний отрисованный кадр. Переключение сцен и стек сцен
+/// работают так же, как в главном цикле; если сцена завершит игру, прогон остановится досрочно.
+pub fn run_scene(
+    ctx: &mut SoftwareContext,
+    scene: Box<dyn Scene>,
+    params: &SnapshotParams,
+) -> Result<Image> {
+    let mut scenes = SceneStack::start(ctx, scene)?;
+
+    for frame in 0..params.frames {
+        let events: Vec<Event> = params
+            .events
+            .iter()
+            .filter(|(f, _)| *f == frame)
+            .map(|(_, e)| *e)
+            .collect();
+
+        ctx.input_mut().clear();
+        ctx.input_mut().handle_events(&events);
+
+        match scenes.process(ctx, params.dt, &events)? {
+            StackResult::Render => {
+                scenes.render(ctx)?;
+            }
+            StackResult::Changed => {}
+            StackResult::Quit => {
+                break;
+            }
+        }
+        ctx.view_mut().clear_changed_flag();
+    }
+
+    scenes.stop_all(ctx)?;
+    Ok(ctx.framebuffer().clone())
+}
+
+/// Сравнивает две картинки одинакового размера.
+pub fn compare_images(actual: &Image, expected: &Image, tolerance: u8) -> Result<Comparison> {
+    if actual.width() != expected.width() || actual.height() != expected.height() {
+        return Err(anyhow::anyhow!(
+            "Image size mismatch: {}x{} vs {}x{}",
+            actual.width(),
+            actual.height(),
+            expected.width(),
+            expected.height()
+        ));
+    }
+
+    let mut diff = Image::new(actual.width(), actual.height());
+    let mut mismatched_pixels = 0;
+
+    for y in 0..actual.height() {
+        for x in 0..actual.width() {
+            let a = actual.get_pixel(x, y);
+            let e = expected.get_pixel(x, y);
+            let matches = a.r.abs_diff(e.r) <= tolerance
+                && a.g.abs_diff(e.g) <= tolerance
+                && a.b.abs_diff(e.b) <= tolerance
+                && a.a.abs_diff(e.a) <= tolerance;
+
+            if matches {
+                diff.set_pixel(x, y, Color::new(e.r / 4, e.g / 4, e.b / 4, 255));
+            } else {
+                mismatched_pixels += 1;
+                diff.set_pixel(x, y, Color::RED);
+            }
+        }
+    }
+
+    Ok(Comparison {
+        mismatched_pixels,
+        diff,
+    })
+}
+
+/// Сравнивает кадр с эталонной картинкой. При несовпадении сохраняет кадр и картинку-разницу
+/// рядом с эталоном и возвращает ошибку. Если эталона нет, тоже возвращает ошибку, сохранив
+/// получившийся кадр рядом — эталон записывается только при `CAKE_ENGINE_UPDATE_SNAPSHOTS=1`.
+pub fn check_snapshot(image: &Image, reference: &Path, params: &SnapshotParams) -> Result<()> {
+    let update = std::env::var(UPDATE_ENV).is_ok_and(|v| v == "1");
+    if update {
+        crate::log::warn!("Writing snapshot {}", reference.display());
+        return image.save_png(reference);
+    }
+
+    let actual_path = sibling_path(reference, "actual");
+    let diff_path = sibling_path(reference, "diff");
+
+    if !reference.exists() {
+        image.save_png(&actual_path)?;
+        return Err(anyhow::anyhow!(
+            "Snapshot {} not found (run with {}=1 to create it)",
+            reference.display(),
+            UPDATE_ENV
+        ));
+    }
+
+    let expected = Image::load_png(reference)?;
+
+    let comparison = match compare_images(image, &expected, params.tolerance) {
+        Ok(c) => c,
+        Err(e) => {
+            image.save_png(&actual_path)?;
+            return Err(e);
+        }
+    };
+
+    if comparison.mismatched_pixels > params.max_mismatched_pixels {
+        image.save_png(&actual_path)?;
+        comparison.diff.save_png(&diff_path)?;
+        return Err(anyhow::anyhow!(
+            "Snapshot {} mismatch: {} pixels differ (see {})",
+            reference.display(),
+            comparison.mismatched_pixels,
+            diff_path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+// foo/bar.png -> foo/bar.<suffix>.png
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.{}.png", stem, suffix))
+}
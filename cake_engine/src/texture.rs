@@ -4,6 +4,7 @@ use crate::{rect::Rect, vec::Vec2};
 use std::path::PathBuf;
 
 /// Откуда была взята текстура.
+#[derive(Debug)]
 pub enum TextureSource {
     /// Из ниоткуда (используется, например, при динамической генерации).
     None,
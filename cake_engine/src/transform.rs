@@ -0,0 +1,207 @@
+//! Аффинное преобразование координат.
+//!
+//! Через [`Context::push_transform`](crate::context::Context::push_transform) преобразование
+//! применяется ко всему, что рисуется после него: сдвиг, масштабирование и поворот
+//! выполняются в координатах игры, до перевода в систему координат окна через
+//! [`View`](crate::view::View). Так можно трясти экран, приближать игровое поле или рисовать
+//! составные виджеты относительно их собственной позиции.
+//!
+//! ```
+//! # use cake_engine::{conf::Conf, context::Context, dummy::DummyContext, rect::Rect};
+//! # use cake_engine::{transform::Transform, vec::Vec2};
+//! # let mut ctx = DummyContext::new(&Conf::default());
+//! ctx.push_transform(Transform::translation(Vec2::new(100.0, 50.0)));
+//! // Нарисуется в точке (110, 60)
+//! ctx.fill_rect(Rect::new(10.0, 10.0, 4.0, 4.0)).unwrap();
+//! ctx.pop_transform().unwrap();
+//! ```
+
+use crate::{context::DrawTextureParams, rect::Rect, vec::Vec2};
+
+/// Аффинное преобразование: точка `(x, y)` переходит в
+/// `(a * x + c * y + tx, b * x + d * y + ty)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    tx: f32,
+    ty: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+impl Transform {
+    /// Преобразование, которое ничего не делает.
+    pub const IDENTITY: Transform = Transform {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        tx: 0.0,
+        ty: 0.0,
+    };
+
+    /// Сдвиг на указанный вектор.
+    pub const fn translation(offset: Vec2) -> Transform {
+        Transform {
+            tx: offset.x,
+            ty: offset.y,
+            ..Transform::IDENTITY
+        }
+    }
+
+    /// Масштабирование относительно начала координат. Отрицательный масштаб отражает
+    /// координаты.
+    pub const fn scaling(scale: Vec2) -> Transform {
+        Transform {
+            a: scale.x,
+            d: scale.y,
+            ..Transform::IDENTITY
+        }
+    }
+
+    /// Масштабирование относительно указанной точки — она остаётся на месте.
+    pub fn scaling_around(center: Vec2, scale: Vec2) -> Transform {
+        Transform::translation(center)
+            .combine(&Transform::scaling(scale))
+            .combine(&Transform::translation(Vec2::new(-center.x, -center.y)))
+    }
+
+    /// Поворот относительно начала координат по часовой стрелке в градусах (как у
+    /// [`DrawTextureParams::rotation`]).
+    pub fn rotation(degrees: f32) -> Transform {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Transform {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            ..Transform::IDENTITY
+        }
+    }
+
+    /// Поворот относительно указанной точки — она остаётся на месте.
+    pub fn rotation_around(center: Vec2, degrees: f32) -> Transform {
+        Transform::translation(center)
+            .combine(&Transform::rotation(degrees))
+            .combine(&Transform::translation(Vec2::new(-center.x, -center.y)))
+    }
+
+    /// Объединяет два преобразования в одно: сначала применяется `other`, потом `self`.
+    pub fn combine(&self, other: &Transform) -> Transform {
+        Transform {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            tx: self.a * other.tx + self.c * other.ty + self.tx,
+            ty: self.b * other.tx + self.d * other.ty + self.ty,
+        }
+    }
+
+    /// Обратное преобразование. Если преобразование вырожденное (например, масштаб ноль),
+    /// возвращает `None`.
+    ///
+    /// Пригодится для перевода координат мыши в систему координат того, что нарисовано
+    /// с преобразованием: сами события ввода преобразование не учитывают.
+    pub fn inverse(&self) -> Option<Transform> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 {
+            return None;
+        }
+        let a = self.d / det;
+        let b = -self.b / det;
+        let c = -self.c / det;
+        let d = self.a / det;
+        Some(Transform {
+            a,
+            b,
+            c,
+            d,
+            tx: -(a * self.tx + c * self.ty),
+            ty: -(b * self.tx + d * self.ty),
+        })
+    }
+
+    /// Применяет преобразование к точке.
+    pub fn apply(&self, point: Vec2) -> Vec2 {
+        Vec2::new(
+            self.a * point.x + self.c * point.y + self.tx,
+            self.b * point.x + self.d * point.y + self.ty,
+        )
+    }
+
+    /// Возвращает `true`, если преобразование оставляет стороны прямоугольников параллельными
+    /// осям (то есть нет поворота и наклона).
+    pub fn is_axis_aligned(&self) -> bool {
+        self.b == 0.0 && self.c == 0.0
+    }
+
+    /// Возвращает `true`, если это только сдвиг.
+    pub fn is_translation(&self) -> bool {
+        self.is_axis_aligned() && self.a == 1.0 && self.d == 1.0
+    }
+
+    /// Первые две строки матрицы 3x3 (третья всегда `[0, 0, 1]`). Нужно бэкендам, которые
+    /// умеют применять преобразования сами.
+    #[allow(dead_code)] // используется не всеми бэкендами
+    pub(crate) fn matrix(&self) -> [f32; 6] {
+        [self.a, self.c, self.tx, self.b, self.d, self.ty]
+    }
+
+    /// Применяет преобразование к прямоугольнику. Результат имеет смысл только для
+    /// [выровненных по осям](Self::is_axis_aligned) преобразований; отражение превращается
+    /// в отрицательные ширину и/или высоту.
+    #[allow(dead_code)] // используется не всеми бэкендами
+    pub(crate) fn apply_to_rect(&self, rect: Rect) -> Rect {
+        let p1 = self.apply(rect.get_position());
+        let p2 = self.apply(Vec2::new(rect.x + rect.width, rect.y + rect.height));
+        Rect::new(p1.x, p1.y, p2.x - p1.x, p2.y - p1.y)
+    }
+
+    /// Переводит параметры рисования текстуры так, чтобы без преобразования текстура
+    /// нарисовалась там же, где нарисовалась бы с ним.
+    ///
+    /// `DrawTextureParams` не умеет наклон, поэтому результат точный, только если
+    /// преобразование вместе с поворотом и масштабом текстуры его не дают. Неравномерное
+    /// масштабирование повёрнутой текстуры (или поворот неравномерно масштабированной)
+    /// рисуется приблизительно.
+    #[allow(dead_code)] // используется не всеми бэкендами
+    pub(crate) fn apply_to_params(&self, params: &DrawTextureParams) -> DrawTextureParams {
+        if self.is_translation() {
+            return DrawTextureParams {
+                position: self.apply(params.position),
+                ..*params
+            };
+        }
+
+        let m = self.combine(
+            &Transform::rotation(params.rotation).combine(&Transform::scaling(params.scale)),
+        );
+        // Раскладываем итоговую матрицу на поворот и масштаб; знак масштаба по x сохраняем
+        // как был, чтобы отражённая текстура не превращалась в повёрнутую на 180 градусов
+        let sign = if params.scale.x < 0.0 { -1.0 } else { 1.0 };
+        let sx = sign * (m.a * m.a + m.b * m.b).sqrt();
+        if sx == 0.0 {
+            return DrawTextureParams {
+                position: self.apply(params.position),
+                scale: Vec2::new(0.0, 0.0),
+                ..*params
+            };
+        }
+        let det = m.a * m.d - m.b * m.c;
+
+        DrawTextureParams {
+            position: self.apply(params.position),
+            rotation: (m.b / sx).atan2(m.a / sx).to_degrees(),
+            scale: Vec2::new(sx, det / sx),
+            ..*params
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Анимированные переходы между сценами.
+//!
+//! Чтобы переключиться на другую сцену с анимацией, верните из `process`
+//! [`SceneResult::SwitchWith`](crate::scene::SceneResult::SwitchWith):
+//!
+//! ```ignore
+//! let next_scene = Box::new(SceneGame::new(ctx)?);
+//! return Ok(SceneResult::SwitchWith(next_scene, Transition::Fade {
+//!     color: Color::BLACK,
+//!     duration: 0.5,
+//! }));
+//! ```
+//!
+//! Старая сцена остаётся жить до конца перехода: движок рендерит её каждый кадр вместе с новой
+//! (когда её нужно нарисовать полупрозрачной или со сдвигом — через текстуру размером с окно)
+//! и вызывает у неё `stop` только после завершения перехода. Пока переход не закончится,
+//! `process` обеих сцен не вызывается — ввод игнорируется, а сцены только рендерятся.
+
+use crate::{
+    color::Color,
+    context::{Context, DrawTextureParams},
+    scene::Scene,
+    texture::{Texture, TextureOptions},
+    vec::Vec2,
+};
+use anyhow::Result;
+use std::rc::Rc;
+
+/// Направление, в котором уезжает старая сцена при переходе [`Transition::Slide`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlideDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Вид перехода между сценами. Длительность указывается в секундах.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transition {
+    /// Старая сцена плавно закрашивается цветом, после чего из этого цвета проявляется
+    /// новая сцена.
+    Fade { color: Color, duration: f32 },
+    /// Старая сцена плавно растворяется, открывая новую.
+    Crossfade { duration: f32 },
+    /// Старая сцена уезжает за край окна, открывая новую.
+    Slide {
+        direction: SlideDirection,
+        duration: f32,
+    },
+}
+
+impl Transition {
+    /// Длительность перехода в секундах.
+    pub fn duration(&self) -> f32 {
+        match self {
+            Transition::Fade { duration, .. } => *duration,
+            Transition::Crossfade { duration } => *duration,
+            Transition::Slide { duration, .. } => *duration,
+        }
+    }
+}
+
+/// Выполняющийся переход вместе со старой сценой.
+pub(crate) struct ActiveTransition {
+    transition: Transition,
+    elapsed: f32,
+    old_scene: Box<dyn Scene>,
+    // Текстура, в которую рисуется старая сцена, когда её нельзя нарисовать прямо в окно
+    target: Option<Rc<Texture>>,
+}
+
+impl ActiveTransition {
+    /// Начинает переход. Старая сцена уже должна быть убрана из стека, но ещё не остановлена.
+    pub fn new(transition: Transition, old_scene: Box<dyn Scene>) -> ActiveTransition {
+        ActiveTransition {
+            transition,
+            elapsed: 0.0,
+            old_scene,
+            target: None,
+        }
+    }
+
+    /// Продвигает переход на `dt` секунд. Возвращает `false`, если переход завершён.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.elapsed += dt;
+        self.elapsed < self.transition.duration()
+    }
+
+    /// Рисует кадр перехода. Функция `render_new` должна отрендерить новую сцену.
+    pub fn render<F>(&mut self, ctx: &mut dyn Context, render_new: F) -> Result<()>
+    where
+        F: FnOnce(&mut dyn Context) -> Result<()>,
+    {
+        let duration = self.transition.duration();
+        let t = if duration > 0.0 {
+            (self.elapsed / duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let area = ctx.view().visible_area();
+
+        match self.transition {
+            Transition::Fade { color, .. } => {
+                // Первая половина — затемнение старой сцены, вторая — проявление новой
+                let alpha = if t < 0.5 {
+                    self.old_scene.render(ctx)?;
+                    t * 2.0
+                } else {
+                    render_new(ctx)?;
+                    (1.0 - t) * 2.0
+                };
+                ctx.set_fill_color(fade(color, alpha));
+                ctx.fill_rect(area)?;
+            }
+            Transition::Crossfade { .. } => {
+                render_new(ctx)?;
+                let old_frame = self.render_old_to_target(ctx)?;
+                draw_frame(
+                    ctx,
+                    &old_frame,
+                    Vec2::new(0.0, 0.0),
+                    fade(Color::WHITE, 1.0 - t),
+                )?;
+            }
+            Transition::Slide { direction, .. } => {
+                render_new(ctx)?;
+                let old_frame = self.render_old_to_target(ctx)?;
+                let offset = match direction {
+                    SlideDirection::Left => Vec2::new(-area.width * t, 0.0),
+                    SlideDirection::Right => Vec2::new(area.width * t, 0.0),
+                    SlideDirection::Up => Vec2::new(0.0, -area.height * t),
+                    SlideDirection::Down => Vec2::new(0.0, area.height * t),
+                };
+                draw_frame(ctx, &old_frame, offset, Color::WHITE)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Останавливает старую сцену и выгружает то, что после неё осталось.
+    pub fn finish(mut self, ctx: &mut dyn Context) -> Result<()> {
+        if let Some(t) = self.target.take() {
+            ctx.drop_texture_if_unused(t);
+        }
+        self.old_scene.stop(ctx)?;
+        drop(self.old_scene);
+        ctx.drop_unused_resources();
+        Ok(())
+    }
+
+    // Рисует старую сцену в текстуру размером с окно. Система координат в текстуре та же,
+    // что и в окне, так что сцена не заметит подмены
+    fn render_old_to_target(&mut self, ctx: &mut dyn Context) -> Result<Rc<Texture>> {
+        let (width, height) = ctx.get_physical_window_size();
+        let (width, height) = (width.max(1), height.max(1));
+        let valid = match self.target.as_ref() {
+            Some(t) => t.width() == width && t.height() == height && ctx.is_texture_valid(t),
+            None => false,
+        };
+        if !valid {
+            if let Some(t) = self.target.take() {
+                ctx.drop_texture_if_unused(t);
+            }
+            let target = ctx.create_render_target(width, height, TextureOptions::default())?;
+            self.target = Some(target);
+        }
+        let target = Rc::clone(self.target.as_ref().unwrap());
+
+        let view = ctx.view().clone();
+        ctx.begin_target(&target)?;
+        ctx.view_mut().set(Some(view.get_or_default()));
+        ctx.view_mut().set_mode(view.get_mode());
+        ctx.set_fill_color(Color::new(0, 0, 0, 0));
+        // Даже если что-то не нарисовалось, рисование в текстуру надо завершить
+        let drawn = ctx.clear().and_then(|_| self.old_scene.render(ctx));
+        ctx.end_target()?;
+        drawn?;
+        Ok(target)
+    }
+}
+
+// Текстура со старой сценой размером с окно, растягиваем её на всю видимую область
+fn draw_frame(ctx: &mut dyn Context, frame: &Texture, offset: Vec2, color: Color) -> Result<()> {
+    let area = ctx.view().visible_area();
+    let size = frame.size_vec();
+    ctx.draw_texture_ex(
+        frame,
+        DrawTextureParams {
+            position: Vec2::new(area.x + offset.x, area.y + offset.y),
+            scale: Vec2::new(area.width / size.x, area.height / size.y),
+            color,
+            ..Default::default()
+        },
+    )
+}
+
+// Умножает прозрачность цвета на коэффициент от 0 до 1
+fn fade(color: Color, k: f32) -> Color {
+    Color {
+        a: (color.a as f32 * k.clamp(0.0, 1.0)).round() as u8,
+        ..color
+    }
+}
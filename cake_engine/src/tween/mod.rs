@@ -0,0 +1,425 @@
+//! Плавное изменение значений во времени (твины).
+//!
+//! [`Tween`] сам по себе просто считает значение между `from` и `to` с учётом
+//! [функции плавности](Easing). Чтобы анимировать свойство объекта, твин привязывается
+//! к сеттеру через [`Tween::bind`], а получившиеся дорожки собираются в
+//! [последовательности](Sequence) и [параллельные группы](Parallel):
+//!
+//! ```
+//! # use cake_engine::{color::Color, vec::Vec2};
+//! use cake_engine::tween::{Animation, Easing, Parallel, Sequence, Tween};
+//!
+//! struct Thing {
+//!     position: Vec2,
+//!     color: Color,
+//!     visible: bool,
+//! }
+//!
+//! impl Thing {
+//!     fn set_position(&mut self, position: Vec2) {
+//!         self.position = position;
+//!     }
+//!
+//!     fn set_color(&mut self, color: Color) {
+//!         self.color = color;
+//!     }
+//! }
+//!
+//! let mut thing = Thing {
+//!     position: Vec2::new(0.0, 0.0),
+//!     color: Color::TRANSPARENT,
+//!     visible: false,
+//! };
+//!
+//! let mut anim = Sequence::new()
+//!     .then_delay(0.5)
+//!     .then(
+//!         Parallel::new()
+//!             .with(
+//!                 Tween::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0), 1.0)
+//!                     .with_easing(Easing::BackOut)
+//!                     .bind(Thing::set_position),
+//!             )
+//!             .with(Tween::new(Color::TRANSPARENT, Color::WHITE, 0.5).bind(Thing::set_color)),
+//!     )
+//!     .then_call(|thing: &mut Thing| thing.visible = true);
+//!
+//! // Обычно это делается в `process` сцены
+//! while !anim.is_finished() {
+//!     anim.update(&mut thing, 1.0 / 60.0);
+//! }
+//!
+//! assert_eq!(thing.position, Vec2::new(100.0, 0.0));
+//! assert_eq!(thing.color, Color::WHITE);
+//! assert!(thing.visible);
+//! ```
+//!
+//! Сеттеры у [`Sprite`](crate::sprite::Sprite), [`Label`](crate::label::Label) и
+//! [`Button`](crate::button::Button) подходят для `bind` напрямую: например,
+//! `Tween::new(a, b, 0.3).bind(Button::set_position)`.
+//!
+//! Анимация не трогает объект, пока до неё не дошла очередь, так что начальное значение
+//! (например, положение кнопки за краем экрана) нужно выставить самостоятельно.
+
+mod easing;
+
+pub use easing::Easing;
+
+use crate::{color::Color, rect::Rect, vec::Vec2};
+
+/// Значение, которое можно плавно менять твином.
+pub trait Lerp: Copy {
+    /// Значение между `from` и `to`: при `t = 0` — `from`, при `t = 1` — `to`. Некоторые
+    /// функции плавности выходят за эти пределы, и это нормально.
+    fn lerp(from: Self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(from: f32, to: f32, t: f32) -> f32 {
+        from + (to - from) * t
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(from: Vec2, to: Vec2, t: f32) -> Vec2 {
+        Vec2::new(f32::lerp(from.x, to.x, t), f32::lerp(from.y, to.y, t))
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(from: Color, to: Color, t: f32) -> Color {
+        let channel =
+            |a: u8, b: u8| f32::lerp(a as f32, b as f32, t).round().clamp(0.0, 255.0) as u8;
+        Color::new(
+            channel(from.r, to.r),
+            channel(from.g, to.g),
+            channel(from.b, to.b),
+            channel(from.a, to.a),
+        )
+    }
+}
+
+impl Lerp for Rect {
+    fn lerp(from: Rect, to: Rect, t: f32) -> Rect {
+        Rect::new(
+            f32::lerp(from.x, to.x, t),
+            f32::lerp(from.y, to.y, t),
+            f32::lerp(from.width, to.width, t),
+            f32::lerp(from.height, to.height, t),
+        )
+    }
+}
+
+/// Плавный переход значения от `from` к `to` за `duration` секунд.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tween<T: Lerp> {
+    from: T,
+    to: T,
+    duration: f32,
+    easing: Easing,
+    elapsed: f32,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// Создаёт твин с линейной функцией плавности.
+    pub fn new(from: T, to: T, duration: f32) -> Tween<T> {
+        Tween {
+            from,
+            to,
+            duration,
+            easing: Easing::Linear,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Задаёт функцию плавности.
+    pub fn with_easing(mut self, easing: Easing) -> Tween<T> {
+        self.easing = easing;
+        self
+    }
+
+    pub fn from(&self) -> T {
+        self.from
+    }
+
+    pub fn to(&self) -> T {
+        self.to
+    }
+
+    /// Длительность в секундах.
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn easing(&self) -> Easing {
+        self.easing
+    }
+
+    /// Продвигает твин на `dt` секунд. Возвращает время, оставшееся после завершения твина
+    /// (ноль, если твин ещё не завершён).
+    pub fn update(&mut self, dt: f32) -> f32 {
+        let left = self.duration - self.elapsed;
+        if dt >= left {
+            self.elapsed = self.duration;
+            dt - left.max(0.0)
+        } else {
+            self.elapsed += dt;
+            0.0
+        }
+    }
+
+    /// Доля прошедшего времени от 0 до 1 (без учёта функции плавности).
+    pub fn progress(&self) -> f32 {
+        if self.duration > 0.0 {
+            (self.elapsed / self.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Текущее значение.
+    pub fn value(&self) -> T {
+        T::lerp(self.from, self.to, self.easing.apply(self.progress()))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Возвращает твин в начало.
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    /// Привязывает твин к свойству объекта: при каждом обновлении текущее значение будет
+    /// передаваться в `setter`.
+    pub fn bind<S>(self, setter: fn(&mut S, T)) -> Track<S, T> {
+        Track {
+            tween: self,
+            setter,
+        }
+    }
+}
+
+/// Анимация объекта типа `S`.
+pub trait Animation<S> {
+    /// Продвигает анимацию на `dt` секунд и применяет изменения к `target`. Возвращает время,
+    /// оставшееся после завершения анимации (ноль, если анимация ещё не завершена), чтобы
+    /// следующий шаг последовательности мог начаться в том же кадре.
+    fn update(&mut self, target: &mut S, dt: f32) -> f32;
+
+    fn is_finished(&self) -> bool;
+
+    /// Возвращает анимацию в начало. Объект при этом не меняется.
+    fn reset(&mut self);
+}
+
+/// Твин, привязанный к сеттеру. Создаётся через [`Tween::bind`].
+pub struct Track<S, T: Lerp> {
+    tween: Tween<T>,
+    setter: fn(&mut S, T),
+}
+
+impl<S, T: Lerp> Track<S, T> {
+    pub fn tween(&self) -> &Tween<T> {
+        &self.tween
+    }
+}
+
+impl<S, T: Lerp> Animation<S> for Track<S, T> {
+    fn update(&mut self, target: &mut S, dt: f32) -> f32 {
+        let rest = self.tween.update(dt);
+        (self.setter)(target, self.tween.value());
+        rest
+    }
+
+    fn is_finished(&self) -> bool {
+        self.tween.is_finished()
+    }
+
+    fn reset(&mut self) {
+        self.tween.reset();
+    }
+}
+
+/// Пауза, ничего не меняющая в объекте.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Delay {
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Delay {
+    pub fn new(duration: f32) -> Delay {
+        Delay {
+            duration,
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl<S> Animation<S> for Delay {
+    fn update(&mut self, _target: &mut S, dt: f32) -> f32 {
+        let left = self.duration - self.elapsed;
+        if dt >= left {
+            self.elapsed = self.duration;
+            dt - left.max(0.0)
+        } else {
+            self.elapsed += dt;
+            0.0
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+}
+
+/// Мгновенный шаг, вызывающий функцию. Обычно добавляется в конец последовательности через
+/// [`Sequence::then_call`], чтобы узнать о завершении анимации.
+pub struct Call<S> {
+    func: Box<dyn FnMut(&mut S)>,
+    called: bool,
+}
+
+impl<S> Call<S> {
+    pub fn new<F: FnMut(&mut S) + 'static>(func: F) -> Call<S> {
+        Call {
+            func: Box::new(func),
+            called: false,
+        }
+    }
+}
+
+impl<S> Animation<S> for Call<S> {
+    fn update(&mut self, target: &mut S, dt: f32) -> f32 {
+        if !self.called {
+            self.called = true;
+            (self.func)(target);
+        }
+        dt
+    }
+
+    fn is_finished(&self) -> bool {
+        self.called
+    }
+
+    fn reset(&mut self) {
+        self.called = false;
+    }
+}
+
+/// Анимации, выполняющиеся друг за другом.
+pub struct Sequence<S> {
+    steps: Vec<Box<dyn Animation<S>>>,
+    current: usize,
+}
+
+impl<S: 'static> Sequence<S> {
+    pub fn new() -> Sequence<S> {
+        Sequence {
+            steps: Vec::new(),
+            current: 0,
+        }
+    }
+
+    /// Добавляет шаг в конец последовательности.
+    pub fn then<A: Animation<S> + 'static>(mut self, step: A) -> Sequence<S> {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    /// Добавляет паузу в конец последовательности.
+    pub fn then_delay(self, duration: f32) -> Sequence<S> {
+        self.then(Delay::new(duration))
+    }
+
+    /// Добавляет в конец последовательности вызов функции.
+    pub fn then_call<F: FnMut(&mut S) + 'static>(self, func: F) -> Sequence<S> {
+        self.then(Call::new(func))
+    }
+}
+
+impl<S: 'static> Default for Sequence<S> {
+    fn default() -> Sequence<S> {
+        Sequence::new()
+    }
+}
+
+impl<S> Animation<S> for Sequence<S> {
+    fn update(&mut self, target: &mut S, dt: f32) -> f32 {
+        let mut dt = dt;
+        while let Some(step) = self.steps.get_mut(self.current) {
+            dt = step.update(target, dt);
+            if !step.is_finished() {
+                return 0.0;
+            }
+            self.current += 1;
+        }
+        dt
+    }
+
+    fn is_finished(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+
+    fn reset(&mut self) {
+        for step in &mut self.steps {
+            step.reset();
+        }
+        self.current = 0;
+    }
+}
+
+/// Анимации, выполняющиеся одновременно. Группа завершается, когда завершатся все её анимации.
+pub struct Parallel<S> {
+    items: Vec<Box<dyn Animation<S>>>,
+}
+
+impl<S: 'static> Parallel<S> {
+    pub fn new() -> Parallel<S> {
+        Parallel { items: Vec::new() }
+    }
+
+    /// Добавляет анимацию в группу.
+    pub fn with<A: Animation<S> + 'static>(mut self, item: A) -> Parallel<S> {
+        self.items.push(Box::new(item));
+        self
+    }
+}
+
+impl<S: 'static> Default for Parallel<S> {
+    fn default() -> Parallel<S> {
+        Parallel::new()
+    }
+}
+
+impl<S> Animation<S> for Parallel<S> {
+    fn update(&mut self, target: &mut S, dt: f32) -> f32 {
+        let mut rest = dt;
+        for item in &mut self.items {
+            if !item.is_finished() {
+                rest = rest.min(item.update(target, dt));
+            }
+        }
+        if self.is_finished() {
+            rest
+        } else {
+            0.0
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.items.iter().all(|item| item.is_finished())
+    }
+
+    fn reset(&mut self) {
+        for item in &mut self.items {
+            item.reset();
+        }
+    }
+}
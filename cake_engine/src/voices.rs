@@ -0,0 +1,127 @@
+// Учёт голосов, общий для всех бэкендов: какие голоса сейчас звучат, с какой громкостью и кого
+// останавливать, когда голоса закончились. Сами бэкенды хранят только свои нативные объекты,
+// привязанные к номерам голосов.
+
+use crate::{
+    audio::{Voice, VoiceStealing},
+    context::PlaySoundParams,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct VoiceInfo {
+    pub voice: Voice,
+    pub sound_id: usize,
+    pub params: PlaySoundParams,
+}
+
+// Используется не всеми бэкендами
+#[allow(dead_code)]
+pub(crate) struct Allocation {
+    pub voice: Voice,
+    /// Голос, который надо остановить, чтобы освободить место для нового.
+    pub stolen: Option<VoiceInfo>,
+    /// `false`, если места нет и звук воспроизводить не надо.
+    pub start: bool,
+}
+
+pub(crate) struct Voices {
+    max_voices: usize,
+    stealing: VoiceStealing,
+    last_id: u64,
+    // В порядке запуска, так что самый старый голос всегда первый
+    active: Vec<VoiceInfo>,
+}
+
+impl Voices {
+    pub fn new(max_voices: usize, stealing: VoiceStealing) -> Voices {
+        Voices {
+            max_voices,
+            stealing,
+            last_id: 0,
+            active: Vec::new(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn max_voices(&self) -> usize {
+        self.max_voices
+    }
+
+    /// Заводит новый голос. Перед вызовом бэкенд должен убрать доигравшие голоса через
+    /// `remove_finished`, иначе они будут считаться занятыми.
+    pub fn allocate(&mut self, sound_id: usize, params: PlaySoundParams) -> Allocation {
+        self.last_id += 1;
+        let voice = Voice { id: self.last_id };
+
+        let mut stolen = None;
+        if self.active.len() >= self.max_voices {
+            let idx = match self.stealing {
+                VoiceStealing::Oldest => Some(0),
+                VoiceStealing::Quietest => self
+                    .active
+                    .iter()
+                    .enumerate()
+                    .min_by(|(_, a), (_, b)| a.params.volume.total_cmp(&b.params.volume))
+                    .map(|(i, _)| i),
+                VoiceStealing::Skip => None,
+            };
+            match idx {
+                Some(i) if i < self.active.len() => stolen = Some(self.active.remove(i)),
+                _ => {
+                    return Allocation {
+                        voice,
+                        stolen: None,
+                        start: false,
+                    }
+                }
+            }
+        }
+
+        self.active.push(VoiceInfo {
+            voice,
+            sound_id,
+            params,
+        });
+
+        Allocation {
+            voice,
+            stolen,
+            start: true,
+        }
+    }
+
+    pub fn get(&self, voice: Voice) -> Option<&VoiceInfo> {
+        self.active.iter().find(|v| v.voice == voice)
+    }
+
+    pub fn get_mut(&mut self, voice: Voice) -> Option<&mut VoiceInfo> {
+        self.active.iter_mut().find(|v| v.voice == voice)
+    }
+
+    pub fn remove(&mut self, voice: Voice) -> Option<VoiceInfo> {
+        let idx = self.active.iter().position(|v| v.voice == voice)?;
+        Some(self.active.remove(idx))
+    }
+
+    /// Убирает все голоса указанного звука и возвращает их.
+    pub fn remove_sound(&mut self, sound_id: usize) -> Vec<VoiceInfo> {
+        self.remove_finished(|v| v.sound_id != sound_id)
+    }
+
+    /// Убирает голоса, для которых `is_playing` вернула `false`, и возвращает их.
+    pub fn remove_finished<F: FnMut(&VoiceInfo) -> bool>(
+        &mut self,
+        mut is_playing: F,
+    ) -> Vec<VoiceInfo> {
+        let mut removed = Vec::new();
+        self.active.retain(|v| {
+            if is_playing(v) {
+                true
+            } else {
+                removed.push(*v);
+                false
+            }
+        });
+        removed
+    }
+}
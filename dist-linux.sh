@@ -26,7 +26,8 @@ EOF
     chmod 755 "$DEST/luna_deny_cakes_game_$b.sh"
 done
 
-cp -Rpv data "$DEST"
+# Все ассеты упаковываются в один архив с контрольными суммами
+cargo run --release --bin luna_deny_cakes_game_pack -- data "$DEST/data.pak"
 
 chmod -R a+rX "$DEST"
 
@@ -8,7 +8,8 @@ mkdir -pv "$DEST"
 cp -pv target/x86_64-pc-windows-gnu/release/luna_deny_cakes_game_{macroquad,sdl,sfml}.exe "$DEST"
 cp -pv target/x86_64-pc-windows-gnu/release/*.dll "$DEST"
 
-cp -Rpv data "$DEST"
+# Все ассеты упаковываются в один архив с контрольными суммами
+cargo run --release --bin luna_deny_cakes_game_pack -- data "$DEST/data.pak"
 
 chmod -R a+rX "$DEST"
 
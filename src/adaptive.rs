@@ -0,0 +1,181 @@
+//! Адаптивная сложность: по ходу игры потихоньку подкручивает баланс в зависимости от того,
+//! как у игрока идут дела.
+
+use crate::{
+    balance::Balance,
+    data::{options::Difficulty, results::SessionResult},
+    game::Game,
+};
+use serde::{Deserialize, Serialize};
+
+// Как часто (в секундах) пересматривать сложность
+const PERIOD: f32 = 5.0;
+// Насколько меняется уровень сложности за один шаг
+const STEP: f32 = 0.1;
+const MIN_LEVEL: f32 = -1.0;
+const MAX_LEVEL: f32 = 1.0;
+
+// Сколько последних партий учитывать при выборе начального уровня
+const RECENT_COUNT: usize = 5;
+
+// Потеря здоровья Селестии за период (в процентах), при которой игрок явно не справляется
+const HP_DROP_HIGH: f32 = 8.0;
+// Потеря здоровья за период, при которой игрок явно скучает
+const HP_DROP_LOW: f32 = 1.0;
+// Доля периода, которую игрок просидел без маны даже на самое дешёвое действие
+const STARVATION_HIGH: f32 = 0.5;
+
+// Насколько (в долях от исходного значения) меняются параметры на крайних уровнях
+const EAT_RANGE: f32 = 0.3;
+const VEL_RANGE: f32 = 0.25;
+const REGEN_RANGE: f32 = 0.3;
+
+/// Одно изменение сложности по ходу партии.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveStep {
+    pub time: f32,
+    pub level: f32,
+    pub eat_in_sec: f32,
+    pub celestia_vel: f32,
+    pub regen_mana_in_sec: f32,
+}
+
+/// Что адаптивная сложность натворила за партию — сохраняется вместе с результатом.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveReport {
+    pub start_level: f32,
+    pub final_level: f32,
+    pub steps: Vec<AdaptiveStep>,
+}
+
+pub struct AdaptiveDifficulty {
+    base: Balance,
+    level: f32,
+    elapsed: f32,
+    period_time: f32,
+    period_start_hp: f32,
+    starved_time: f32,
+    report: AdaptiveReport,
+}
+
+impl AdaptiveDifficulty {
+    /// Выбирает начальный уровень по недавним результатам и сразу применяет его к игре.
+    pub fn new(
+        game: &mut Game,
+        difficulty: Difficulty,
+        recent: &[SessionResult],
+    ) -> AdaptiveDifficulty {
+        let level = AdaptiveDifficulty::initial_level(difficulty, recent);
+
+        let adaptive = AdaptiveDifficulty {
+            base: *game.get_balance(),
+            level,
+            elapsed: 0.0,
+            period_time: 0.0,
+            period_start_hp: game.get_celestia_hp_percent(),
+            starved_time: 0.0,
+            report: AdaptiveReport {
+                start_level: level,
+                final_level: level,
+                steps: Vec::new(),
+            },
+        };
+        adaptive.apply(game);
+        adaptive
+    }
+
+    fn initial_level(difficulty: Difficulty, recent: &[SessionResult]) -> f32 {
+        let recent: Vec<_> = recent
+            .iter()
+            .rev()
+            .filter(|r| r.difficulty == difficulty)
+            .filter_map(|r| r.adaptive.as_ref().map(|a| (r.win, a.final_level)))
+            .take(RECENT_COUNT)
+            .collect();
+
+        // Продолжаем с того уровня, на котором закончилась прошлая партия, и сдвигаем его
+        // в сторону недавних побед или поражений
+        let last_level = match recent.first() {
+            Some((_, level)) => *level,
+            None => return 0.0,
+        };
+        let balance: f32 = recent
+            .iter()
+            .map(|(win, _)| if *win { 1.0 } else { -1.0 })
+            .sum();
+
+        (last_level + balance * STEP).clamp(MIN_LEVEL, MAX_LEVEL)
+    }
+
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    pub fn report(&self) -> &AdaptiveReport {
+        &self.report
+    }
+
+    fn apply(&self, game: &mut Game) {
+        let mut balance = *game.get_balance();
+        balance.eat_in_sec = self.base.eat_in_sec * (1.0 + EAT_RANGE * self.level);
+        balance.celestia_vel = self.base.celestia_vel * (1.0 + VEL_RANGE * self.level);
+        balance.regen_mana_in_sec = self.base.regen_mana_in_sec * (1.0 - REGEN_RANGE * self.level);
+        game.set_balance(balance);
+    }
+
+    pub fn update(&mut self, game: &mut Game, dt: f32) {
+        self.elapsed += dt;
+        self.period_time += dt;
+
+        let balance = game.get_balance();
+        let cheapest_action = balance
+            .jump_cost
+            .min(balance.chicken_cost)
+            .min(balance.shield_cost);
+        if game.get_mana() < cheapest_action {
+            self.starved_time += dt;
+        }
+
+        if self.period_time < PERIOD {
+            return;
+        }
+
+        let hp = game.get_celestia_hp_percent();
+        let hp_drop = self.period_start_hp - hp;
+        let starvation = self.starved_time / self.period_time;
+
+        let mut delta = 0.0;
+        if hp_drop > HP_DROP_HIGH {
+            delta -= STEP;
+        } else if hp_drop < HP_DROP_LOW && starvation < STARVATION_HIGH {
+            delta += STEP;
+        }
+        if starvation >= STARVATION_HIGH {
+            delta -= STEP;
+        }
+
+        self.period_time = 0.0;
+        self.period_start_hp = hp;
+        self.starved_time = 0.0;
+
+        // Округляем, чтобы не копилась погрешность от многократного сложения
+        let new_level = ((self.level + delta) * 10.0).round() / 10.0;
+        let new_level = new_level.clamp(MIN_LEVEL, MAX_LEVEL);
+        if new_level == self.level {
+            return;
+        }
+
+        self.level = new_level;
+        self.apply(game);
+
+        let balance = game.get_balance();
+        self.report.final_level = new_level;
+        self.report.steps.push(AdaptiveStep {
+            time: self.elapsed,
+            level: new_level,
+            eat_in_sec: balance.eat_in_sec,
+            celestia_vel: balance.celestia_vel,
+            regen_mana_in_sec: balance.regen_mana_in_sec,
+        });
+    }
+}
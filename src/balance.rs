@@ -1,6 +1,8 @@
 use crate::data::options::Difficulty;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Balance {
     pub luna_vel: f32,
     pub celestia_vel: f32,
@@ -15,6 +17,12 @@ pub struct Balance {
     pub regen_mana_in_sec: f32,
     pub eat_in_sec: f32,
     pub celestia_start_hp: f32,
+    pub mana_per_cake: f32,
+    pub pickup_interval: f32,
+    pub pickup_lifetime: f32,
+    pub pickup_mana: f32,
+    pub laser_boost_time: f32,
+    pub laser_boost_factor: f32,
 }
 
 impl Balance {
@@ -35,6 +43,55 @@ impl Balance {
 
         balance
     }
+
+    /// Все параметры баланса с их названиями — для редактора уровней.
+    pub fn fields(&self) -> [(&'static str, f32); 19] {
+        [
+            ("luna_vel", self.luna_vel),
+            ("celestia_vel", self.celestia_vel),
+            ("shield_time", self.shield_time),
+            ("chicken_vel", self.chicken_vel),
+            ("laser_power_in_sec", self.laser_power_in_sec),
+            ("laser_cost_in_sec", self.laser_cost_in_sec),
+            ("shield_cost", self.shield_cost),
+            ("jump_cost", self.jump_cost),
+            ("chicken_cost", self.chicken_cost),
+            ("max_mana", self.max_mana),
+            ("regen_mana_in_sec", self.regen_mana_in_sec),
+            ("eat_in_sec", self.eat_in_sec),
+            ("celestia_start_hp", self.celestia_start_hp),
+            ("mana_per_cake", self.mana_per_cake),
+            ("pickup_interval", self.pickup_interval),
+            ("pickup_lifetime", self.pickup_lifetime),
+            ("pickup_mana", self.pickup_mana),
+            ("laser_boost_time", self.laser_boost_time),
+            ("laser_boost_factor", self.laser_boost_factor),
+        ]
+    }
+
+    pub fn fields_mut(&mut self) -> [(&'static str, &mut f32); 19] {
+        [
+            ("luna_vel", &mut self.luna_vel),
+            ("celestia_vel", &mut self.celestia_vel),
+            ("shield_time", &mut self.shield_time),
+            ("chicken_vel", &mut self.chicken_vel),
+            ("laser_power_in_sec", &mut self.laser_power_in_sec),
+            ("laser_cost_in_sec", &mut self.laser_cost_in_sec),
+            ("shield_cost", &mut self.shield_cost),
+            ("jump_cost", &mut self.jump_cost),
+            ("chicken_cost", &mut self.chicken_cost),
+            ("max_mana", &mut self.max_mana),
+            ("regen_mana_in_sec", &mut self.regen_mana_in_sec),
+            ("eat_in_sec", &mut self.eat_in_sec),
+            ("celestia_start_hp", &mut self.celestia_start_hp),
+            ("mana_per_cake", &mut self.mana_per_cake),
+            ("pickup_interval", &mut self.pickup_interval),
+            ("pickup_lifetime", &mut self.pickup_lifetime),
+            ("pickup_mana", &mut self.pickup_mana),
+            ("laser_boost_time", &mut self.laser_boost_time),
+            ("laser_boost_factor", &mut self.laser_boost_factor),
+        ]
+    }
 }
 
 impl Default for Balance {
@@ -53,6 +110,12 @@ impl Default for Balance {
             regen_mana_in_sec: 5.0,
             eat_in_sec: 0.2,
             celestia_start_hp: 5.0,
+            mana_per_cake: 40.0,
+            pickup_interval: 12.0,
+            pickup_lifetime: 8.0,
+            pickup_mana: 40.0,
+            laser_boost_time: 5.0,
+            laser_boost_factor: 2.0,
         }
     }
 }
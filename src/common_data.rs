@@ -1,21 +1,34 @@
 use crate::{
     achievements::AchievementStore,
-    data::options::OPTIONS,
+    data::{options::OPTIONS, texts::get_text},
     utils::{tex, tex_lang},
 };
 use anyhow::Result;
 use cake_engine::{
-    audio::Music,
     button::Button,
     color::Color,
     context::{Context, DrawTextureParams},
     font::Font,
+    input::ScanCode,
     label::Label,
+    music::MusicManager,
     sprite::Sprite,
     texture::Texture,
     vec::Vec2,
 };
-use std::{path::Path, rc::Rc};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// Клавиша, по которой сохраняется скриншот
+const SCREENSHOT_KEY: ScanCode = ScanCode::F12;
+// Сколько показывать сообщение о сохранённом скриншоте
+const SCREENSHOT_MESSAGE_TIME: Duration = Duration::from_secs(2);
+// За сколько секунд музыка переходит от одного плейлиста к другому
+const MUSIC_FADE: f32 = 1.5;
 
 #[derive(Clone)]
 pub struct CommonData {
@@ -35,11 +48,15 @@ pub struct CommonData {
     pub checkbox_off: Rc<Texture>,
     pub color_over: Color,
     pub color_norm: Color,
-    pub music_main: Option<Rc<Music>>,
+    // Общий для всех сцен, поэтому не копируется вместе с CommonData
+    pub music: Rc<RefCell<MusicManager>>,
     pub achievements: AchievementStore,
     pub fps_counter_label: Label,
     pub fps: u64,
     pub draw_fps_counter: bool,
+    pub screenshot_label: Label,
+    screenshot_requested: bool,
+    screenshot_label_until: Option<Duration>,
 }
 
 impl CommonData {
@@ -53,6 +70,10 @@ impl CommonData {
         fps_counter_label.set_position(Vec2::new(area.x + area.width, area.y + area.height));
         fps_counter_label.set_origin(Vec2::new(1.0, 1.0));
 
+        let mut screenshot_label = Label::new(font_small.clone(), Color::WHITE);
+        screenshot_label.set_position(Vec2::new(area.x + area.width / 2.0, area.y + 8.0));
+        screenshot_label.set_origin(Vec2::new(0.5, 0.0));
+
         let mut common_data = CommonData {
             // Галочка в настройках, счётчик кадров
             font_small,
@@ -81,11 +102,14 @@ impl CommonData {
             checkbox_off: tex!(ctx, "images/checkbox_off.png"),
             color_over: Color::WHITE,
             color_norm: Color::new(200, 200, 200, 255),
-            music_main: None,
+            music: Rc::new(RefCell::new(MusicManager::new())),
             achievements: AchievementStore::new(),
             fps_counter_label,
             fps: 0,
             draw_fps_counter: OPTIONS.lock().unwrap().get_show_fps_counter(),
+            screenshot_label,
+            screenshot_requested: false,
+            screenshot_label_until: None,
         };
 
         common_data.button_close.set_origin(Vec2::new(1.0, 0.0));
@@ -94,24 +118,37 @@ impl CommonData {
             .button_close
             .set_color_hover(common_data.color_over);
 
-        if OPTIONS.lock().unwrap().get_musicon() {
-            common_data.play_music(ctx)?;
+        {
+            let (musicon, volume) = {
+                let options = OPTIONS.lock().unwrap();
+                (options.get_musicon(), options.get_effective_music_volume())
+            };
+            let mut music = common_data.music.borrow_mut();
+            music.load_manifest("music.json")?;
+            music.set_volume(ctx, volume)?;
+            music.set_enabled(ctx, musicon)?;
         }
+        common_data.play_music(ctx, "menu")?;
+
+        ctx.hot_reload_mut().watch_lang("strings.json");
 
         Ok(common_data)
     }
 
-    pub fn get_or_load_music<'a>(&'a mut self, ctx: &mut dyn Context) -> Result<&'a Music> {
-        if self.music_main.is_none() {
-            self.music_main = Some(ctx.load_music_file(Path::new("music/music_main.ogg"))?);
-        }
-        Ok(self.music_main.as_ref().unwrap())
+    /// Загружает все ресурсы заново (например, после включения или выключения модов). Прогресс
+    /// достижений сохраняется, а музыка начинается заново.
+    pub fn reload(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        self.music.borrow_mut().stop(ctx, 0.0)?;
+        let achievements = self.achievements.clone();
+        *self = CommonData::new(ctx)?;
+        self.achievements = achievements;
+        Ok(())
     }
 
-    pub fn play_music(&mut self, ctx: &mut dyn Context) -> Result<()> {
-        let music_main = self.get_or_load_music(ctx)?;
-        ctx.play_music(music_main, 1.0, true)?;
-        Ok(())
+    /// Плавно переключает музыку на указанный плейлист из `music.json`. Если он уже играет,
+    /// ничего не происходит, так что можно вызывать хоть каждый кадр.
+    pub fn play_music(&self, ctx: &mut dyn Context, playlist: &str) -> Result<()> {
+        self.music.borrow_mut().play(ctx, playlist, MUSIC_FADE)
     }
 
     pub fn process(&mut self, ctx: &mut dyn Context) -> Result<()> {
@@ -122,8 +159,20 @@ impl CommonData {
                 .set_position(Vec2::new(area.x + area.width, area.y));
             self.fps_counter_label
                 .set_position(Vec2::new(area.x + area.width, area.y + area.height));
+            self.screenshot_label
+                .set_position(Vec2::new(area.x + area.width / 2.0, area.y + 8.0));
         }
         self.button_close.process(ctx)?;
+        self.music.borrow_mut().process(ctx)?;
+        if ctx.hot_reload().is_changed(Path::new("strings.json")) {
+            // Уже созданные надписи не обновятся, но на следующем экране будут новые строки
+            crate::data::reload_lang(&OPTIONS.lock().unwrap())?;
+            cake_engine::log::info!("Strings reloaded");
+        }
+        if ctx.input().is_key_just_pressed(SCREENSHOT_KEY) {
+            // Сам скриншот делается в draw_overlay, когда кадр уже нарисован
+            self.screenshot_requested = true;
+        }
         if self.draw_fps_counter {
             let new_fps = ctx.time().get_fps();
             if new_fps != self.fps {
@@ -150,10 +199,37 @@ impl CommonData {
         Ok(())
     }
 
-    pub fn draw_fps_counter(&mut self, ctx: &mut dyn Context) -> Result<()> {
+    /// Рисует то, что показывается поверх любой сцены (кроме курсора), и сохраняет скриншот,
+    /// если он был запрошен.
+    pub fn draw_overlay(&mut self, ctx: &mut dyn Context) -> Result<()> {
         if self.draw_fps_counter {
             self.fps_counter_label.render(ctx)?;
         }
+
+        if self.screenshot_requested {
+            self.screenshot_requested = false;
+            match save_screenshot(ctx) {
+                Ok(path) => {
+                    cake_engine::log::info!("Screenshot saved to {}", path.display());
+                    self.screenshot_label
+                        .set_text(get_text("text_screenshot_saved"));
+                    self.screenshot_label_until =
+                        Some(ctx.time().uptime() + SCREENSHOT_MESSAGE_TIME);
+                }
+                Err(e) => {
+                    cake_engine::log::error!("Failed to save screenshot: {:?}", e);
+                }
+            }
+        }
+
+        if let Some(until) = self.screenshot_label_until {
+            if ctx.time().uptime() < until {
+                self.screenshot_label.render(ctx)?;
+            } else {
+                self.screenshot_label_until = None;
+            }
+        }
+
         Ok(())
     }
 
@@ -164,3 +240,20 @@ impl CommonData {
         Ok(())
     }
 }
+
+fn save_screenshot(ctx: &mut dyn Context) -> Result<PathBuf> {
+    let dir = match crate::data::data_dir() {
+        Some(d) => d.join("screenshots"),
+        None => return Err(anyhow::anyhow!("Data directory is not available")),
+    };
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let path = dir.join(format!(
+        "screenshot_{}_{:03}.png",
+        now.as_secs(),
+        now.subsec_millis()
+    ));
+
+    ctx.capture_screenshot()?.save_png(&path)?;
+    Ok(path)
+}
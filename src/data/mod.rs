@@ -1,9 +1,14 @@
 use crate::data::options::{Options, OPTIONS};
 use anyhow::Result;
-use cake_engine::fs;
-use std::path::PathBuf;
+use cake_engine::{
+    fs::{self, Mount},
+    pack::Pack,
+};
+use std::path::{Path, PathBuf};
 
+pub mod mods;
 pub mod options;
+pub mod results;
 pub mod texts;
 
 pub fn data_dir() -> Option<PathBuf> {
@@ -24,19 +29,30 @@ pub fn data_dir() -> Option<PathBuf> {
     }
 }
 
-pub fn init_assets_directory() {
+/// Ассеты берутся из каталога или архива, переданного первым аргументом. По умолчанию
+/// используется архив `data.pak`, а если его нет — каталог `data`.
+pub fn init_assets_directory() -> Result<()> {
     if cfg!(target_os = "android") {
-        return;
+        return Ok(());
     }
 
     let args: Vec<String> = std::env::args().collect();
 
     let path = match args.get(1) {
-        Some(x) => x,
-        None => "data",
+        Some(x) => PathBuf::from(x),
+        None if Path::new("data.pak").is_file() => PathBuf::from("data.pak"),
+        None => PathBuf::from("data"),
     };
 
-    fs::set_assets_directory(path.into());
+    if path.is_file() {
+        let pack = Pack::open(&path)?;
+        pack.verify()?;
+        fs::mount(Mount::Pack(pack));
+    } else {
+        fs::set_assets_directory(path);
+    }
+
+    Ok(())
 }
 
 pub fn init_options() -> Result<()> {
@@ -58,9 +74,11 @@ pub fn init_options() -> Result<()> {
 }
 
 pub fn init() -> Result<()> {
-    init_assets_directory();
+    init_assets_directory()?;
     init_options()?;
-    reload_lang(&OPTIONS.lock().unwrap())?;
+    let options = OPTIONS.lock().unwrap();
+    mods::mount_mods(&options);
+    reload_lang(&options)?;
     Ok(())
 }
 
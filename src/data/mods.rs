@@ -0,0 +1,66 @@
+//! Моды: каталоги `data_dir()/mods/<имя>`, файлы из которых заменяют или дополняют ассеты игры.
+//!
+//! Мод повторяет структуру каталога `data`. Файлы с совпадающими путями заменяют оригинальные
+//! (например, `images/luna.png`), а `strings.<язык>.json` и `music.json` объединяются
+//! с оригинальными, так что в них достаточно положить только новые или изменённые строки
+//! и плейлисты.
+
+use crate::data::options::Options;
+use cake_engine::fs::{self, Mount};
+use std::path::PathBuf;
+
+pub fn mods_dir() -> Option<PathBuf> {
+    // На Android смонтированные каталоги читаются через AssetManager, который не видит файлы
+    // приложения, так что моды там не поддерживаются
+    if cfg!(target_os = "android") {
+        return None;
+    }
+    Some(crate::data::data_dir()?.join("mods"))
+}
+
+/// Имена всех установленных модов в алфавитном порядке.
+pub fn find_mods() -> Vec<String> {
+    let dir = match mods_dir() {
+        Some(d) => d,
+        None => return Vec::new(),
+    };
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut result: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter_map(|e| e.file_name().to_str().map(|n| n.to_string()))
+        .collect();
+    result.sort();
+    result
+}
+
+/// Монтирует включённые в настройках моды, заменяя ранее смонтированные моды. Остальные
+/// источники ассетов (например, архив с данными игры) не трогаются.
+pub fn mount_mods(options: &Options) {
+    let dir = mods_dir();
+
+    let mut mounts: Vec<Mount> = fs::get_mounts()
+        .into_iter()
+        .filter(|m| match (m, &dir) {
+            (Mount::Directory(p), Some(dir)) => !p.starts_with(dir),
+            _ => true,
+        })
+        .collect();
+
+    if let Some(dir) = dir {
+        for name in options.get_mods() {
+            let path = dir.join(name);
+            if path.is_dir() {
+                mounts.push(Mount::Directory(path));
+            } else {
+                cake_engine::log::warn!("Mod {:?} not found in {:?}", name, dir);
+            }
+        }
+    }
+
+    fs::set_mounts(mounts);
+}
@@ -1,4 +1,4 @@
-use crate::{action::Action, data::texts::get_text};
+use crate::{action::Action, data::texts::get_text, mutator::Mutator};
 use anyhow::Result;
 use cake_engine::{
     input::{Key, MouseButton, ScanCode},
@@ -33,6 +33,9 @@ pub struct Options {
     window_size: Vec2,
     soundon: bool,
     musicon: bool,
+    master_volume: f32,
+    music_volume: f32,
+    sound_volume: f32,
     fullscreen: bool,
     vsync: bool,
     fps_limit: f32,
@@ -42,6 +45,10 @@ pub struct Options {
     #[serde(skip)]
     languages: Vec<String>,
     difficulty: Difficulty,
+    adaptive_difficulty: bool,
+    mutators: Vec<Mutator>,
+    // Включённые моды в порядке загрузки: более поздние перекрывают более ранние
+    mods: Vec<String>,
     apply_after_select: bool,
     keys: HashMap<Action, Key>,
 }
@@ -81,6 +88,9 @@ impl Default for Options {
             window_size: Vec2::new(1024.0, 768.0),
             soundon: true,
             musicon: !cfg!(feature = "macroquad"), // macroquad очень долго грузит музыку, поэтому отключаем по умолчанию
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sound_volume: 1.0,
             fullscreen: cfg!(target_os = "android"),
             vsync: true,
             fps_limit: 0.0,
@@ -89,6 +99,9 @@ impl Default for Options {
             currentlang: "".to_string(),
             languages: Vec::new(),
             difficulty: Difficulty::Easy,
+            adaptive_difficulty: false,
+            mutators: Vec::new(),
+            mods: Vec::new(),
             apply_after_select: false,
             keys: Options::get_default_keys(),
         }
@@ -180,6 +193,40 @@ impl Options {
         self.musicon
     }
 
+    pub fn get_master_volume(&self) -> f32 {
+        self.master_volume
+    }
+
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.master_volume = volume.clamp(0.0, 1.0);
+    }
+
+    pub fn get_music_volume(&self) -> f32 {
+        self.music_volume
+    }
+
+    pub fn set_music_volume(&mut self, volume: f32) {
+        self.music_volume = volume.clamp(0.0, 1.0);
+    }
+
+    pub fn get_sound_volume(&self) -> f32 {
+        self.sound_volume
+    }
+
+    pub fn set_sound_volume(&mut self, volume: f32) {
+        self.sound_volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Громкость, с которой надо играть музыку, с учётом общей громкости.
+    pub fn get_effective_music_volume(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    /// Громкость, с которой надо играть звуки, с учётом общей громкости.
+    pub fn get_effective_sound_volume(&self) -> f32 {
+        self.master_volume * self.sound_volume
+    }
+
     pub fn get_fullscreen(&self) -> bool {
         self.fullscreen
     }
@@ -312,6 +359,66 @@ impl Options {
         self.difficulty
     }
 
+    pub fn get_adaptive_difficulty(&self) -> bool {
+        self.adaptive_difficulty
+    }
+
+    pub fn set_adaptive_difficulty(&mut self, value: bool) {
+        self.adaptive_difficulty = value;
+    }
+
+    pub fn invert_adaptive_difficulty(&mut self) -> bool {
+        self.adaptive_difficulty = !self.adaptive_difficulty;
+        self.adaptive_difficulty
+    }
+
+    pub fn get_mutators(&self) -> &[Mutator] {
+        &self.mutators
+    }
+
+    pub fn is_mutator_enabled(&self, mutator: Mutator) -> bool {
+        self.mutators.contains(&mutator)
+    }
+
+    pub fn invert_mutator(&mut self, mutator: Mutator) -> bool {
+        if let Some(idx) = self.mutators.iter().position(|&m| m == mutator) {
+            self.mutators.remove(idx);
+            false
+        } else {
+            self.mutators.push(mutator);
+            true
+        }
+    }
+
+    pub fn get_mods(&self) -> &[String] {
+        &self.mods
+    }
+
+    pub fn is_mod_enabled(&self, name: &str) -> bool {
+        self.mods.iter().any(|m| m == name)
+    }
+
+    pub fn invert_mod(&mut self, name: &str) -> bool {
+        if let Some(idx) = self.mods.iter().position(|m| m == name) {
+            self.mods.remove(idx);
+            false
+        } else {
+            self.mods.push(name.to_string());
+            true
+        }
+    }
+
+    /// Перемещает включённый мод на одну позицию раньше в порядке загрузки.
+    pub fn move_mod_up(&mut self, name: &str) -> bool {
+        match self.mods.iter().position(|m| m == name) {
+            Some(idx) if idx > 0 => {
+                self.mods.swap(idx - 1, idx);
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn get_keys(&self) -> &HashMap<Action, Key> {
         &self.keys
     }
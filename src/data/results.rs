@@ -0,0 +1,64 @@
+use crate::{adaptive::AdaptiveReport, data::options::Difficulty, mutator::Mutator};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Сколько последних результатов хранить в файле.
+const MAX_RESULTS: usize = 100;
+
+/// Результат одной сыгранной партии.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionResult {
+    pub timestamp: u64,
+    pub difficulty: Difficulty,
+    pub win: bool,
+    pub message: String,
+    pub seed: u64,
+    pub duration: f32,
+    pub celestia_hp_percent: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adaptive: Option<AdaptiveReport>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mutators: Vec<Mutator>,
+}
+
+pub fn path() -> Option<PathBuf> {
+    Some(crate::data::data_dir()?.join("results.json"))
+}
+
+/// Загружает сохранённые результаты, от старых к новым.
+pub fn load_results() -> Result<Vec<SessionResult>> {
+    let path = match path() {
+        Some(p) => p,
+        None => return Err(anyhow::anyhow!("No data directory")),
+    };
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let serialized = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&serialized)?)
+}
+
+/// Добавляет результат в конец файла, выкидывая самые старые, если их слишком много.
+pub fn save_result(result: SessionResult) -> Result<()> {
+    let path = match path() {
+        Some(p) => p,
+        None => return Err(anyhow::anyhow!("No data directory")),
+    };
+
+    let mut results = load_results()?;
+    results.push(result);
+    if results.len() > MAX_RESULTS {
+        results.drain(..results.len() - MAX_RESULTS);
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let serialized = serde_json::to_string_pretty(&results)?;
+    std::fs::write(path, &serialized)?;
+    Ok(())
+}
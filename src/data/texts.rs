@@ -11,10 +11,20 @@ pub fn load_from_file(filename: &str) -> Result<()> {
     Ok(())
 }
 
+/// Загружает строки для текущего языка из всех источников ассетов, так что моды могут
+/// добавлять новые строки и заменять существующие. Строки, которых нет в текущем языке,
+/// берутся из запасного.
 pub fn load_from_lang_file(filename: &str) -> Result<()> {
-    let texts_json = cake_engine::fs::read_lang_asset_to_string(filename)?;
-    let texts: HashMap<String, String> = serde_json::from_str(&texts_json)?;
-    TEXTS.lock().unwrap().extend(texts);
+    let files = cake_engine::fs::read_all_lang_assets_to_string(filename)?;
+    if files.is_empty() {
+        return Err(anyhow::anyhow!("{} not found", filename));
+    }
+
+    let mut all_texts = TEXTS.lock().unwrap();
+    for texts_json in files {
+        let texts: HashMap<String, String> = serde_json::from_str(&texts_json)?;
+        all_texts.extend(texts);
+    }
     Ok(())
 }
 
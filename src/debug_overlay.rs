@@ -0,0 +1,173 @@
+use crate::{
+    data::texts::get_text,
+    game,
+    game::{Direction, Game},
+};
+use anyhow::Result;
+use cake_engine::{
+    color::Color, context::Context, font::Font, label::Label, rect::Rect, vec::Vec2,
+};
+use std::rc::Rc;
+
+const ZONE_COLOR: Color = Color::new(0, 128, 255, 64);
+const CAKE_COLOR: Color = Color::new(255, 255, 0, 64);
+const TARGET_COLOR: Color = Color::new(255, 0, 255, 160);
+const LASER_COLOR: Color = Color::new(255, 0, 0, 64);
+const OUTLINE_COLOR: Color = Color::new(255, 255, 255, 160);
+const PANEL_COLOR: Color = Color::new(0, 0, 0, 160);
+const PANEL_POS: Vec2 = Vec2::new(60.0, 10.0);
+const PANEL_PADDING: f32 = 6.0;
+
+/// Отладочный слой поверх игры: хитбоксы зон и кексиков, цель Селестии, область действия
+/// лазера и панель с внутренним состоянием игры.
+pub struct DebugOverlay {
+    enabled: bool,
+    label: Label,
+}
+
+impl DebugOverlay {
+    pub fn new(font: Rc<Font>) -> DebugOverlay {
+        let mut label = Label::new(font, Color::WHITE);
+        label.set_position(Vec2::new(
+            PANEL_POS.x + PANEL_PADDING,
+            PANEL_POS.y + PANEL_PADDING,
+        ));
+
+        DebugOverlay {
+            enabled: false,
+            label,
+        }
+    }
+
+    pub fn toggle(&mut self) -> bool {
+        self.enabled = !self.enabled;
+        self.enabled
+    }
+
+    pub fn update(&mut self, game: &Game) {
+        if !self.enabled {
+            return;
+        }
+
+        let target = match game.get_celestia_target_cake_id() {
+            Some(id) => id.to_string(),
+            None => "-".to_string(),
+        };
+
+        self.label.set_text(format!(
+            "{}: {:.1} / {:.0}\n\
+            {}: {:.2} ({:.1}%)\n\
+            {}: {:.2}\n\
+            {}: {}\n\
+            {}: {} / {}\n\
+            {}: {} ({:?})\n\
+            {}: {}",
+            get_text("debug_mana"),
+            game.get_mana(),
+            game.get_balance().max_mana,
+            get_text("debug_hp"),
+            game.get_celestia_hp(),
+            game.get_celestia_hp_percent(),
+            get_text("debug_wintimer"),
+            game.get_wintimer(),
+            get_text("debug_cakes"),
+            game.cakes().len(),
+            get_text("debug_chickens"),
+            game.chickens().len(),
+            game.falling_chickens().len(),
+            get_text("debug_celestia_target"),
+            target,
+            game.get_celestia_dir(),
+            get_text("debug_seed"),
+            game.get_seed(),
+        ));
+    }
+
+    fn cake_rect(game: &Game, cake_id: usize) -> Rect {
+        // Совпадает с проверкой в Game::get_cake_id_at
+        let cake = &game.cakes()[cake_id];
+        let zone = &game.zones()[cake.zoneidx];
+        Rect::new(
+            cake.x - game::CAKEW / 2.0,
+            zone.y - game::CAKEW / 2.0 - game::CAKE_Y,
+            game::CAKEW,
+            game::CAKEW,
+        )
+    }
+
+    pub fn render(&mut self, ctx: &mut dyn Context, game: &Game) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        // Зоны в том виде, в котором их видит Game::get_zone_id_by_xy
+        for zone in game.zones() {
+            let rect = Rect::new(
+                zone.left,
+                zone.y - game::ZONEH1,
+                zone.right - zone.left,
+                game::ZONEH1,
+            );
+            ctx.set_fill_color(ZONE_COLOR);
+            ctx.fill_rect(rect)?;
+            ctx.set_fill_color(OUTLINE_COLOR);
+            ctx.draw_rect_outline(rect, 1.0)?;
+        }
+
+        if let Some((zoneidx, left, right)) = game.get_laser_range() {
+            let zone = &game.zones()[zoneidx];
+            ctx.set_fill_color(LASER_COLOR);
+            ctx.fill_rect(Rect::new(
+                left,
+                zone.y - game::ZONEH1,
+                right - left,
+                game::ZONEH1,
+            ))?;
+        }
+
+        for i in 0..game.cakes().len() {
+            let rect = Self::cake_rect(game, i);
+            ctx.set_fill_color(CAKE_COLOR);
+            ctx.fill_rect(rect)?;
+            ctx.set_fill_color(OUTLINE_COLOR);
+            ctx.draw_rect_outline(rect, 1.0)?;
+        }
+
+        // Линия от Селестии до её цели и стрелочка направления
+        let celestia_pos = game.get_celestia_pos();
+        let line_y = celestia_pos.y - game::CAKE_Y;
+        ctx.set_fill_color(TARGET_COLOR);
+        if let Some(target_id) = game.get_celestia_target_cake_id() {
+            let rect = Self::cake_rect(game, target_id);
+            let target_x = rect.get_center().x;
+            ctx.draw_line(
+                Vec2::new(celestia_pos.x, line_y),
+                Vec2::new(target_x, line_y),
+                2.0,
+            )?;
+            ctx.fill_rect(rect)?;
+        }
+        if game.get_celestia_dir() != Direction::No {
+            let sig = game.get_celestia_dir().sig_f();
+            let tip_x = celestia_pos.x + sig * game::PONYW;
+            ctx.fill_polygon(&[
+                Vec2::new(tip_x + sig * 6.0, line_y),
+                Vec2::new(tip_x - sig * 4.0, line_y - 6.0),
+                Vec2::new(tip_x - sig * 4.0, line_y + 6.0),
+            ])?;
+        }
+
+        if let Some(b) = self.label.get_bounding_rect() {
+            ctx.set_fill_color(PANEL_COLOR);
+            ctx.fill_rect(Rect::new(
+                PANEL_POS.x,
+                PANEL_POS.y,
+                b.width + PANEL_PADDING * 2.0,
+                b.height + PANEL_PADDING * 2.0,
+            ))?;
+        }
+        self.label.render(ctx)?;
+
+        Ok(())
+    }
+}
@@ -1,10 +1,13 @@
 use crate::{
     balance::Balance,
     data::{options::Difficulty, texts::get_text},
+    level::Level,
+    mutator::Mutator,
 };
 use cake_engine::vec::Vec2;
 use rand::{seq::SliceRandom, Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
 pub const CAKE_Y: f32 = 30.0;
 pub const BLOCKW: f32 = 84.0;
@@ -15,8 +18,12 @@ pub const ZONEH: f32 = 110.0;
 pub const ZONEH1: f32 = ZONEH - BLOCKH;
 pub const CAKEW: f32 = 48.0;
 pub const PONYW: f32 = 30.0;
+pub const PICKUPW: f32 = 32.0;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+// Больше стольких бонусов на поле одновременно не появляется
+const MAX_PICKUPS: usize = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Zone {
     pub y: f32,
     pub left: f32,
@@ -72,6 +79,28 @@ pub struct Cake {
     pub shieldleft: f32,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PickupKind {
+    /// Пополняет ману.
+    Mana,
+    /// Даёт один бесплатный щит.
+    Shield,
+    /// На время усиливает лазер.
+    LaserBoost,
+}
+
+const PICKUP_KINDS: [PickupKind; 3] =
+    [PickupKind::Mana, PickupKind::Shield, PickupKind::LaserBoost];
+
+/// Бонус, лежащий на платформе; Луна подбирает его, проходя мимо.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Pickup {
+    pub zoneidx: usize,
+    pub x: f32,
+    pub kind: PickupKind,
+    pub lifetime: f32,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GameState {
     Normal,
@@ -88,6 +117,10 @@ pub struct Game {
     chickens: Vec<Chicken>,
     falling_chickens: Vec<FallingChicken>,
     cakes: Vec<Cake>,
+    pickups: Vec<Pickup>,
+    pickuptimer: f32,
+    shieldcharges: u32,
+    laserboostleft: f32,
     celestiax: f32,
     celestiazoneidx: usize,
     celestiadir: Direction,
@@ -100,47 +133,56 @@ pub struct Game {
     is_celestia_eating: bool,
     wintimer: f32,
     laserdir: Direction,
+    mutators: Vec<Mutator>,
 }
 
 impl Game {
-    pub fn new(difficulty: Difficulty) -> Game {
-        let seed = std::time::SystemTime::now()
+    fn time_seed() -> u64 {
+        std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
-            .as_secs();
+            .as_secs()
+    }
+
+    pub fn new(difficulty: Difficulty) -> Game {
+        Game::with_seed(difficulty, Game::time_seed())
+    }
+
+    /// Создаёт игру с заданным зерном случайных чисел — с одним и тем же зерном поле
+    /// и поведение Селестии будут одинаковыми.
+    pub fn with_seed(difficulty: Difficulty, seed: u64) -> Game {
         let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let level = Level::generate(difficulty, &mut rng);
+        Game::from_level(&level, seed, rng)
+    }
 
-        let balance = Balance::new(difficulty);
-
-        #[rustfmt::skip]
-        let zones = vec![
-            Zone { y: 90.0, left: 50.0, right: 50.0 + ZONEW * 11.0 },
-            Zone { y: 90.0 + 1.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
-            Zone { y: 90.0 + 2.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
-            Zone { y: 90.0 + 3.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
-            Zone { y: 90.0 + 4.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
-            Zone { y: 90.0 + 5.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
-            Zone { y: 90.0 + 6.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
-        ];
-
-        let mut cakes = Vec::new();
-        for i in 0..zones.len() {
-            for pos in [200.0, 500.0, 800.0] {
-                cakes.push(Cake {
-                    x: pos + rng.gen_range(-100..100) as f32,
-                    zoneidx: i,
-                    spriteidx: rng.gen_range(0..3),
-                    hp: 1.0,
-                    shieldleft: 0.0,
-                });
-            }
-        }
+    /// Создаёт игру на заранее подготовленном поле (например, из редактора уровней).
+    pub fn with_level(level: &Level) -> Game {
+        let seed = Game::time_seed();
+        Game::from_level(level, seed, ChaCha8Rng::seed_from_u64(seed))
+    }
 
-        let celestiazoneidx = 0;
+    fn from_level(level: &Level, seed: u64, rng: ChaCha8Rng) -> Game {
+        let balance = level.balance;
+        let zones = level.zones.clone();
+
+        let cakes = level
+            .cakes
+            .iter()
+            .map(|c| Cake {
+                zoneidx: c.zoneidx,
+                x: c.x,
+                spriteidx: c.spriteidx,
+                hp: c.hp,
+                shieldleft: 0.0,
+            })
+            .collect();
+
+        let celestiazoneidx = level.celestia_zone_idx;
         let celestiax = (zones[celestiazoneidx].left + zones[celestiazoneidx].right) / 2.0;
         let celestiahp = balance.celestia_start_hp;
 
-        let lunazoneidx = 6;
+        let lunazoneidx = level.luna_zone_idx;
         let lunax = (zones[lunazoneidx].left + zones[lunazoneidx].right) / 2.0;
         let mana = balance.max_mana;
 
@@ -152,6 +194,10 @@ impl Game {
             chickens: Vec::new(),
             falling_chickens: Vec::new(),
             cakes,
+            pickups: Vec::new(),
+            pickuptimer: 0.0,
+            shieldcharges: 0,
+            laserboostleft: 0.0,
             celestiax,
             celestiazoneidx,
             celestiadir: Direction::No,
@@ -164,9 +210,64 @@ impl Game {
             is_celestia_eating: false,
             wintimer: 0.0,
             laserdir: Direction::No,
+            mutators: Vec::new(),
+        }
+    }
+
+    /// Включает модификаторы правил. Вызывать нужно до начала игры.
+    pub fn apply_mutators(&mut self, mutators: &[Mutator]) {
+        for &mutator in mutators {
+            if self.mutators.contains(&mutator) {
+                continue;
+            }
+            self.mutators.push(mutator);
+
+            match mutator {
+                Mutator::Mirror => self.mirror(),
+                Mutator::FastCelestia => self.balance.celestia_vel *= 2.0,
+                Mutator::ManaFromCakes => self.balance.regen_mana_in_sec = 0.0,
+                Mutator::SteadyChickens | Mutator::OneHitLaser => {}
+            }
         }
     }
 
+    fn mirror(&mut self) {
+        let left = self
+            .zones
+            .iter()
+            .map(|z| z.left)
+            .fold(f32::INFINITY, f32::min);
+        let right = self
+            .zones
+            .iter()
+            .map(|z| z.right)
+            .fold(f32::NEG_INFINITY, f32::max);
+        // x' = axis - x отражает поле относительно его середины
+        let axis = left + right;
+
+        for zone in self.zones.iter_mut() {
+            (zone.left, zone.right) = (axis - zone.right, axis - zone.left);
+        }
+        for cake in self.cakes.iter_mut() {
+            cake.x = axis - cake.x;
+        }
+        self.celestiax = axis - self.celestiax;
+        self.lunax = axis - self.lunax;
+        self.lunadir = match self.lunadir {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::No => Direction::No,
+        };
+    }
+
+    pub fn mutators(&self) -> &[Mutator] {
+        &self.mutators
+    }
+
+    pub fn has_mutator(&self, mutator: Mutator) -> bool {
+        self.mutators.contains(&mutator)
+    }
+
     pub fn get_seed(&self) -> u64 {
         self.seed
     }
@@ -175,6 +276,16 @@ impl Game {
         &self.zones
     }
 
+    /// Нижний край самой нижней платформы. В уровнях из редактора платформы могут идти
+    /// в любом порядке, так что последняя не обязательно самая нижняя.
+    pub fn get_bottom(&self) -> f32 {
+        self.zones
+            .iter()
+            .map(|z| z.y)
+            .fold(f32::NEG_INFINITY, f32::max)
+            + BLOCKH
+    }
+
     pub fn chickens(&self) -> &[Chicken] {
         &self.chickens
     }
@@ -191,6 +302,10 @@ impl Game {
         &self.balance
     }
 
+    pub fn set_balance(&mut self, balance: Balance) {
+        self.balance = balance;
+    }
+
     pub fn get_mana(&self) -> f32 {
         self.mana
     }
@@ -217,10 +332,42 @@ impl Game {
         self.celestiazoneidx
     }
 
+    pub fn get_celestia_hp(&self) -> f32 {
+        self.celestiahp
+    }
+
     pub fn get_celestia_hp_percent(&self) -> f32 {
         self.celestiahp * 100.0 / self.balance.celestia_start_hp
     }
 
+    pub fn get_celestia_target_cake_id(&self) -> Option<usize> {
+        self.find_eaten_cake_id()
+            .or_else(|| self.find_nearest_cake_id())
+    }
+
+    fn find_eaten_cake_id(&self) -> Option<usize> {
+        self.cakes.iter().position(|cake| {
+            cake.zoneidx == self.celestiazoneidx
+                && cake.shieldleft <= 0.0
+                && (self.celestiax - cake.x).abs() < (PONYW / 2.0 + CAKEW / 2.0)
+        })
+    }
+
+    fn find_nearest_cake_id(&self) -> Option<usize> {
+        let mut near_cake_id = None;
+        let mut dist = f32::INFINITY;
+        for (i, cake) in self.cakes.iter().enumerate() {
+            if cake.zoneidx == self.celestiazoneidx && cake.shieldleft <= 0.0 {
+                let d = (cake.x - self.celestiax).abs();
+                if d < dist {
+                    near_cake_id = Some(i);
+                    dist = d;
+                }
+            }
+        }
+        near_cake_id
+    }
+
     pub fn get_luna_pos(&self) -> Vec2 {
         Vec2::new(self.lunax, self.zones[self.lunazoneidx].y)
     }
@@ -242,6 +389,7 @@ impl Game {
         if newlunax >= self.zones[self.lunazoneidx].left + PONYW / 2.0 {
             self.lunax = newlunax;
             self.lunadir = Direction::Left;
+            self.collect_pickups();
             true
         } else {
             false
@@ -257,12 +405,97 @@ impl Game {
         if newlunax <= self.zones[self.lunazoneidx].right - PONYW / 2.0 {
             self.lunax = newlunax;
             self.lunadir = Direction::Right;
+            self.collect_pickups();
             true
         } else {
             false
         }
     }
 
+    pub fn pickups(&self) -> &[Pickup] {
+        &self.pickups
+    }
+
+    pub fn get_shield_charges(&self) -> u32 {
+        self.shieldcharges
+    }
+
+    /// Тратит бесплатный щит, если он есть.
+    pub fn use_shield_charge(&mut self) -> bool {
+        if self.shieldcharges == 0 {
+            return false;
+        }
+        self.shieldcharges -= 1;
+        true
+    }
+
+    pub fn get_laser_boost_left(&self) -> f32 {
+        self.laserboostleft
+    }
+
+    fn collect_pickups(&mut self) {
+        let lunazoneidx = self.lunazoneidx;
+        let lunax = self.lunax;
+        let (collected, rest): (Vec<Pickup>, Vec<Pickup>) = self.pickups.iter().partition(|p| {
+            p.zoneidx == lunazoneidx && (p.x - lunax).abs() < (PONYW + PICKUPW) / 2.0
+        });
+        self.pickups = rest;
+
+        for pickup in collected {
+            match pickup.kind {
+                PickupKind::Mana => {
+                    self.mana = (self.mana + self.balance.pickup_mana).min(self.balance.max_mana);
+                }
+                PickupKind::Shield => self.shieldcharges += 1,
+                PickupKind::LaserBoost => self.laserboostleft = self.balance.laser_boost_time,
+            }
+        }
+    }
+
+    fn update_pickups(&mut self, dt: f32) {
+        for pickup in self.pickups.iter_mut() {
+            pickup.lifetime -= dt;
+        }
+        self.pickups.retain(|p| p.lifetime > 0.0);
+
+        if self.laserboostleft > 0.0 {
+            self.laserboostleft = (self.laserboostleft - dt).max(0.0);
+        }
+
+        // Нулевой интервал отключает бонусы совсем
+        if self.balance.pickup_interval <= 0.0 {
+            return;
+        }
+
+        self.pickuptimer += dt;
+        if self.pickuptimer < self.balance.pickup_interval {
+            return;
+        }
+        self.pickuptimer = 0.0;
+
+        if self.pickups.len() >= MAX_PICKUPS {
+            return;
+        }
+
+        let zoneidx = self.rng.gen_range(0..self.zones.len());
+        let zone = self.zones[zoneidx];
+        let left = zone.left + PICKUPW / 2.0;
+        let right = zone.right - PICKUPW / 2.0;
+        let x = if left < right {
+            self.rng.gen_range(left..right)
+        } else {
+            (zone.left + zone.right) / 2.0
+        };
+        let kind = *PICKUP_KINDS.choose(&mut self.rng).unwrap();
+
+        self.pickups.push(Pickup {
+            zoneidx,
+            x,
+            kind,
+            lifetime: self.balance.pickup_lifetime,
+        });
+    }
+
     pub fn get_zone_id_by_xy(&self, mxy: Vec2) -> Option<usize> {
         for (i, zone) in self.zones.iter().enumerate() {
             if zone.left < mxy.x && mxy.x < zone.right && zone.y > mxy.y && mxy.y > zone.y - ZONEH1
@@ -366,6 +599,15 @@ impl Game {
         self.laserdir
     }
 
+    pub fn get_laser_range(&self) -> Option<(usize, f32, f32)> {
+        let zone = &self.zones[self.lunazoneidx];
+        match self.laserdir {
+            Direction::Left => Some((self.lunazoneidx, zone.left, self.lunax)),
+            Direction::Right => Some((self.lunazoneidx, self.lunax, zone.right)),
+            Direction::No => None,
+        }
+    }
+
     pub fn start_laser(&mut self, mxy: Vec2) {
         self.laserdir = if mxy.x > self.lunax {
             Direction::Right
@@ -388,6 +630,10 @@ impl Game {
         true
     }
 
+    pub fn get_wintimer(&self) -> f32 {
+        self.wintimer
+    }
+
     pub fn get_state(&self) -> &GameState {
         &self.state
     }
@@ -399,21 +645,29 @@ impl Game {
             }
         }
 
+        self.update_pickups(dt);
+
         for falling_chicken in self.falling_chickens.iter_mut() {
             falling_chicken.pos.x += falling_chicken.vel.x * dt;
             falling_chicken.pos.y += falling_chicken.vel.y * dt;
             falling_chicken.rotation += 2.0 * falling_chicken.vel.x * dt;
         }
 
-        let bottom = self.zones.last().unwrap().y + BLOCKH;
+        let bottom = self.get_bottom();
         self.falling_chickens.retain(|c| c.pos.y < bottom + 100.0);
 
+        let steady_chickens = self.has_mutator(Mutator::SteadyChickens);
         for chicken in self.chickens.iter_mut() {
             chicken.x += chicken.vx * dt;
             let zone = self.zones[chicken.zoneidx];
             if chicken.vx < 0.0 && chicken.x < zone.left
                 || chicken.vx > 0.0 && chicken.x > zone.right
             {
+                if steady_chickens {
+                    chicken.vx = -chicken.vx;
+                    chicken.x = chicken.x.clamp(zone.left, zone.right);
+                    continue;
+                }
                 self.falling_chickens.push(FallingChicken {
                     pos: Vec2::new(chicken.x, zone.y),
                     vel: Vec2::new(chicken.vx, 200.0),
@@ -424,12 +678,28 @@ impl Game {
         }
 
         if self.laserdir != Direction::No {
+            let one_hit = self.has_mutator(Mutator::OneHitLaser);
+            let mana_from_cakes = self.has_mutator(Mutator::ManaFromCakes);
+            let mut mana_gain = 0.0;
+            let laser_power = if self.laserboostleft > 0.0 {
+                self.balance.laser_power_in_sec * self.balance.laser_boost_factor
+            } else {
+                self.balance.laser_power_in_sec
+            };
             for cake in self.cakes.iter_mut() {
                 if cake.zoneidx == self.lunazoneidx
                     && cake.shieldleft <= 0.0
                     && self.laserdir.same_way(self.lunax, cake.x)
                 {
-                    cake.hp -= self.balance.laser_power_in_sec * dt;
+                    let was_alive = cake.hp > 0.0;
+                    if one_hit {
+                        cake.hp = 0.0;
+                    } else {
+                        cake.hp -= laser_power * dt;
+                    }
+                    if mana_from_cakes && was_alive && cake.hp <= 0.0 {
+                        mana_gain += self.balance.mana_per_cake;
+                    }
                 }
             }
 
@@ -447,6 +717,7 @@ impl Game {
                 self.state = GameState::Fail(get_text("msg_laserfail"));
             }
 
+            self.mana = (self.mana + mana_gain).min(self.balance.max_mana);
             self.mana -= self.balance.laser_cost_in_sec * dt;
             if self.mana <= 0.0 {
                 self.mana = 0.0;
@@ -470,16 +741,7 @@ impl Game {
             self.jump_celestia_to_best_zone();
         }
 
-        let mut eaten_cake_id = None;
-        for (id, cake) in self.cakes.iter().enumerate() {
-            if cake.zoneidx == self.celestiazoneidx
-                && cake.shieldleft <= 0.0
-                && (self.celestiax - cake.x).abs() < (PONYW / 2.0 + CAKEW / 2.0)
-            {
-                eaten_cake_id = Some(id);
-                break;
-            }
-        }
+        let eaten_cake_id = self.find_eaten_cake_id();
         self.is_celestia_eating = eaten_cake_id.is_some();
 
         if let Some(eaten_cake_id) = eaten_cake_id {
@@ -496,18 +758,7 @@ impl Game {
             cake.hp -= dh;
             self.celestiahp -= dh;
         } else {
-            let mut near_cake_id = None;
-            let mut dist = f32::INFINITY;
-            for (i, cake) in self.cakes.iter().enumerate() {
-                if cake.zoneidx == self.celestiazoneidx && cake.shieldleft <= 0.0 {
-                    let d = (cake.x - self.celestiax).abs();
-                    if d < dist {
-                        near_cake_id = Some(i);
-                        dist = d;
-                    }
-                }
-            }
-            if let Some(near_cake_id) = near_cake_id {
+            if let Some(near_cake_id) = self.find_nearest_cake_id() {
                 let cake = &self.cakes[near_cake_id];
                 self.celestiadir = if (cake.x - self.celestiax) > 0.0 {
                     Direction::Right
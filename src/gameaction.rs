@@ -75,14 +75,20 @@ impl GameAction for GAShield {
     }
 
     fn get_cost(&self, game: &Game) -> f32 {
-        game.get_balance().shield_cost
+        if game.get_shield_charges() > 0 {
+            0.0
+        } else {
+            game.get_balance().shield_cost
+        }
     }
 
     fn apply(&mut self, game: &mut Game, mxy: Vec2) -> bool {
         if !self.is_allowed_at(game, mxy) {
             return false;
         }
-        game.dec_mana(self.get_cost(game));
+        if !game.use_shield_charge() {
+            game.dec_mana(self.get_cost(game));
+        }
         game.set_shield_to_cake_by_xy(mxy)
     }
 }
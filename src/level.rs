@@ -0,0 +1,154 @@
+use crate::{
+    balance::Balance,
+    data::options::Difficulty,
+    game::{Zone, ZONEH, ZONEW},
+};
+use anyhow::Result;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+pub const CAKE_SPRITES_COUNT: usize = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LevelCake {
+    pub zoneidx: usize,
+    pub x: f32,
+    pub spriteidx: usize,
+    pub hp: f32,
+}
+
+/// Описание игрового поля: платформы, кексики, начальные позиции пони и баланс.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    pub zones: Vec<Zone>,
+    pub cakes: Vec<LevelCake>,
+    pub luna_zone_idx: usize,
+    pub celestia_zone_idx: usize,
+    #[serde(default)]
+    pub balance: Balance,
+}
+
+impl Level {
+    /// Стандартное поле из оригинальной игры со случайно раскиданными кексиками.
+    pub fn generate<R: Rng>(difficulty: Difficulty, rng: &mut R) -> Level {
+        #[rustfmt::skip]
+        let zones = vec![
+            Zone { y: 90.0, left: 50.0, right: 50.0 + ZONEW * 11.0 },
+            Zone { y: 90.0 + 1.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
+            Zone { y: 90.0 + 2.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
+            Zone { y: 90.0 + 3.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
+            Zone { y: 90.0 + 4.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
+            Zone { y: 90.0 + 5.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
+            Zone { y: 90.0 + 6.0 * ZONEH, left: 50.0, right: 50.0 + ZONEW * 11.0 },
+        ];
+
+        let mut cakes = Vec::new();
+        for i in 0..zones.len() {
+            for pos in [200.0, 500.0, 800.0] {
+                cakes.push(LevelCake {
+                    x: pos + rng.gen_range(-100..100) as f32,
+                    zoneidx: i,
+                    spriteidx: rng.gen_range(0..CAKE_SPRITES_COUNT),
+                    hp: 1.0,
+                });
+            }
+        }
+
+        Level {
+            zones,
+            cakes,
+            luna_zone_idx: 6,
+            celestia_zone_idx: 0,
+            balance: Balance::new(difficulty),
+        }
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        Some(crate::data::data_dir()?.join("level.json"))
+    }
+
+    pub fn load() -> Result<Level> {
+        let path = match Level::path() {
+            Some(p) => p,
+            None => return Err(anyhow::anyhow!("No data directory")),
+        };
+
+        let serialized = std::fs::read_to_string(path)?;
+        let level: Level = serde_json::from_str(&serialized)?;
+        level.validate()?;
+        Ok(level)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = match Level::path() {
+            Some(p) => p,
+            None => return Err(anyhow::anyhow!("No data directory")),
+        };
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let serialized = serde_json::to_string_pretty(&self)?;
+        std::fs::write(path, &serialized)?;
+        Ok(())
+    }
+
+    /// Проверяет, что на этом поле вообще можно играть.
+    pub fn validate(&self) -> Result<()> {
+        if self.zones.is_empty() {
+            return Err(anyhow::anyhow!("Level has no zones"));
+        }
+        if self.luna_zone_idx >= self.zones.len() {
+            return Err(anyhow::anyhow!("Invalid Luna zone {}", self.luna_zone_idx));
+        }
+        if self.celestia_zone_idx >= self.zones.len() {
+            return Err(anyhow::anyhow!(
+                "Invalid Celestia zone {}",
+                self.celestia_zone_idx
+            ));
+        }
+        for zone in &self.zones {
+            if zone.right - zone.left < ZONEW {
+                return Err(anyhow::anyhow!("Zone at y={} is too narrow", zone.y));
+            }
+        }
+        for cake in &self.cakes {
+            if cake.zoneidx >= self.zones.len() {
+                return Err(anyhow::anyhow!("Invalid cake zone {}", cake.zoneidx));
+            }
+            if cake.spriteidx >= CAKE_SPRITES_COUNT {
+                return Err(anyhow::anyhow!("Invalid cake sprite {}", cake.spriteidx));
+            }
+            if cake.hp <= 0.0 {
+                return Err(anyhow::anyhow!("Cake HP must be positive"));
+            }
+        }
+        if self.balance.celestia_start_hp <= 0.0 || self.balance.max_mana <= 0.0 {
+            return Err(anyhow::anyhow!("Celestia HP and max mana must be positive"));
+        }
+        Ok(())
+    }
+
+    /// Удаляет зону вместе со всеми кексиками на ней. Последнюю зону удалить нельзя.
+    pub fn remove_zone(&mut self, idx: usize) -> bool {
+        if self.zones.len() < 2 || idx >= self.zones.len() {
+            return false;
+        }
+
+        self.zones.remove(idx);
+        self.cakes.retain(|c| c.zoneidx != idx);
+        for cake in self.cakes.iter_mut() {
+            if cake.zoneidx > idx {
+                cake.zoneidx -= 1;
+            }
+        }
+
+        let len = self.zones.len();
+        let fix_idx = |i: usize| if i > idx || i == len { i - 1 } else { i };
+        self.luna_zone_idx = fix_idx(self.luna_zone_idx);
+        self.celestia_zone_idx = fix_idx(self.celestia_zone_idx);
+        true
+    }
+}
@@ -1,11 +1,15 @@
 pub mod achievements;
 pub mod action;
+pub mod adaptive;
 pub mod balance;
 pub mod common_data;
 pub mod data;
+pub mod debug_overlay;
 pub mod dvd;
 pub mod game;
 pub mod gameaction;
+pub mod level;
+pub mod mutator;
 pub mod scene;
 pub mod touchui;
 
@@ -38,6 +42,8 @@ pub fn get_conf() -> Result<Conf> {
         fps_limit: options.get_fps_limit(),
         resizable: true,
         mouse_cursor_visible: cfg!(target_os = "android"),
+        // Для разработки: перезагружать картинки, шрифты, звуки и строки при изменении файлов
+        hot_reload: std::env::var_os("LUNA_HOT_RELOAD").is_some_and(|v| v == "1"),
         ..Default::default()
     })
 }
@@ -1,7 +1,16 @@
 use anyhow::Result;
+use cake_engine::dummy::EventScript;
 use luna_deny_cakes_game::{build_first_scene, data, get_conf};
 
 pub fn main() -> Result<()> {
     data::init()?;
-    cake_engine::dummy::main_dummy(get_conf()?, &build_first_scene)
+
+    // Вторым аргументом (после каталога с ассетами) можно передать JSON-файл со сценарием
+    // событий, чтобы прогнать игру без участия человека
+    let script: EventScript = match std::env::args().nth(2) {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => EventScript::default(),
+    };
+
+    cake_engine::dummy::main_dummy_script(get_conf()?, &build_first_scene, script)
 }
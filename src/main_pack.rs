@@ -0,0 +1,18 @@
+//! Упаковывает каталог с ассетами в один архив для дистрибутива.
+//!
+//! Использование: `luna_deny_cakes_game_pack [каталог] [архив]` (по умолчанию `data` и `data.pak`).
+
+use anyhow::Result;
+
+pub fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let src = args.get(1).map(|x| x.as_str()).unwrap_or("data");
+    let dst = args.get(2).map(|x| x.as_str()).unwrap_or("data.pak");
+
+    cake_engine::pack::write_pack(src, dst)?;
+    // Сразу проверяем, что получившийся архив читается
+    let pack = cake_engine::pack::Pack::open(dst)?;
+    pack.verify()?;
+    println!("{}: {} files", dst, pack.files().len());
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+
+/// Модификаторы правил, которые включаются перед началом партии.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize, Sequence)]
+pub enum Mutator {
+    /// Поле отражается по горизонтали.
+    Mirror,
+    /// Селестия бегает в два раза быстрее.
+    FastCelestia,
+    /// Курицы не падают с края платформы, а разворачиваются.
+    SteadyChickens,
+    /// Мана не восстанавливается сама, зато даётся за уничтоженные лазером кексики.
+    ManaFromCakes,
+    /// Лазер уничтожает кексик с одного касания.
+    OneHitLaser,
+}
+
+impl Mutator {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Mutator::Mirror => "mirror",
+            Mutator::FastCelestia => "fast_celestia",
+            Mutator::SteadyChickens => "steady_chickens",
+            Mutator::ManaFromCakes => "mana_from_cakes",
+            Mutator::OneHitLaser => "one_hit_laser",
+        }
+    }
+}
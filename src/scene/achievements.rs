@@ -148,7 +148,7 @@ impl Scene for SceneAchievements {
             Vec2::new(0.5, 0.5),
         )?;
 
-        self.common_data.draw_fps_counter(ctx)?;
+        self.common_data.draw_overlay(ctx)?;
         self.common_data.draw_cursor(ctx)?;
         Ok(())
     }
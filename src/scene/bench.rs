@@ -7,7 +7,7 @@ use crate::{
 use anyhow::Result;
 use cake_engine::{
     color::Color,
-    context::Context,
+    context::{Context, DrawTextureParams},
     input::{Event, ScanCode},
     label::Label,
     scene::{Scene, SceneResult},
@@ -38,6 +38,9 @@ pub struct SceneBench {
     frame_count: u32,
     frame_tm: Instant,
     fps: f32,
+    fps_batched: f32,
+    // Параметры рисования тортиков для каждой текстуры, чтобы не выделять память каждый кадр
+    batches: [Vec<DrawTextureParams>; 3],
     stage_label: Label,
     cakes_count_label: Label,
 }
@@ -84,6 +87,8 @@ impl SceneBench {
             frame_count: 0,
             frame_tm: Instant::now(),
             fps: 0.0,
+            fps_batched: 0.0,
+            batches: Default::default(),
             stage_label,
             cakes_count_label,
         })
@@ -163,6 +168,13 @@ impl SceneBench {
             let frametime = (Instant::now() - self.frame_tm) / self.frame_count;
             self.fps = 1.0 / frametime.as_secs_f32();
             self.stage = 5;
+            self.stage_label.set_text(get_text("bench_stage5"));
+            self.warmup = true;
+        } else if self.stage == 5 && self.frame_count >= 60 {
+            // Шаг 5: то же самое, но тортики рисуются пачками
+            let frametime = (Instant::now() - self.frame_tm) / self.frame_count;
+            self.fps_batched = 1.0 / frametime.as_secs_f32();
+            self.stage = 6;
         }
     }
 }
@@ -184,10 +196,15 @@ impl Scene for SceneBench {
             return Ok(SceneResult::Switch(Box::new(s)));
         }
 
-        if self.stage >= 5 {
+        if self.stage >= 6 {
             ctx.set_fps_limit(OPTIONS.lock().unwrap().get_fps_limit());
-            let s =
-                SceneBenchResult::new(self.common_data.clone(), ctx, self.cakes.len(), self.fps)?;
+            let s = SceneBenchResult::new(
+                self.common_data.clone(),
+                ctx,
+                self.cakes.len(),
+                self.fps,
+                self.fps_batched,
+            )?;
             return Ok(SceneResult::Switch(Box::new(s)));
         }
 
@@ -199,8 +216,26 @@ impl Scene for SceneBench {
     fn render(&mut self, ctx: &mut dyn Context) -> Result<()> {
         self.common_data.draw_back(ctx)?;
 
-        for (cake_id, pos) in &self.cakes {
-            ctx.draw_texture(&self.cake_textures[*cake_id], *pos, Vec2::new(0.5, 0.5))?;
+        if self.stage == 5 {
+            // Одна пачка на каждую текстуру. Тортики одной текстуры оказываются поверх
+            // тортиков другой, но для теста это не важно
+            for batch in self.batches.iter_mut() {
+                batch.clear();
+            }
+            for (cake_id, pos) in &self.cakes {
+                self.batches[*cake_id].push(DrawTextureParams {
+                    position: *pos,
+                    origin: Vec2::new(0.5, 0.5),
+                    ..Default::default()
+                });
+            }
+            for (texture, batch) in self.cake_textures.iter().zip(self.batches.iter()) {
+                ctx.draw_texture_batch(texture, batch)?;
+            }
+        } else {
+            for (cake_id, pos) in &self.cakes {
+                ctx.draw_texture(&self.cake_textures[*cake_id], *pos, Vec2::new(0.5, 0.5))?;
+            }
         }
 
         self.stage_label.render(ctx)?;
@@ -208,6 +243,7 @@ impl Scene for SceneBench {
 
         self.common_data.button_close.render(ctx)?;
         // рисовать здесь fps_counter наверное плохая идея?
+        self.common_data.draw_overlay(ctx)?;
         self.common_data.draw_cursor(ctx)?;
         Ok(())
     }
@@ -28,6 +28,7 @@ impl SceneBenchResult {
         ctx: &mut dyn Context,
         cakes_count: usize,
         fps: f32,
+        fps_batched: f32,
     ) -> Result<SceneBenchResult> {
         let view = ctx.view().get_or_default();
         let window_size = ctx.get_physical_window_size();
@@ -36,8 +37,13 @@ impl SceneBenchResult {
         let text = format!(
             "{}\n\
             {}x{}\n\
-            {:.3} FPS",
-            text, window_size.0, window_size.1, fps
+            {:.3} FPS\n\
+            {}",
+            text,
+            window_size.0,
+            window_size.1,
+            fps,
+            get_text("bench_result_batched").replace("%FPS%", &format!("{:.3}", fps_batched)),
         );
 
         let mut label = Label::new(common_data.font_big.clone(), Color::WHITE);
@@ -91,7 +97,7 @@ impl Scene for SceneBenchResult {
         self.label.render(ctx)?;
         self.button_back.render(ctx)?;
 
-        self.common_data.draw_fps_counter(ctx)?;
+        self.common_data.draw_overlay(ctx)?;
         self.common_data.draw_cursor(ctx)?;
         Ok(())
     }
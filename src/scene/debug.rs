@@ -20,10 +20,10 @@ use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 
 const MARGIN: f32 = 8.0;
+const BACK_COLOR: Color = Color::new(255, 255, 255, 200);
 
 pub struct SceneDebug {
     common_data: CommonData,
-    prev_scene: Option<Box<dyn Scene>>,
     sys_label: Label,
     size_label: Label,
     events_label: Label,
@@ -106,7 +106,6 @@ impl SceneDebug {
 
         Ok(SceneDebug {
             common_data,
-            prev_scene: None,
             sys_label,
             size_label,
             events_label,
@@ -117,11 +116,6 @@ impl SceneDebug {
 }
 
 impl Scene for SceneDebug {
-    fn start(&mut self, _ctx: &mut dyn Context, prev_scene: Option<Box<dyn Scene>>) -> Result<()> {
-        self.prev_scene = prev_scene;
-        Ok(())
-    }
-
     fn process(&mut self, ctx: &mut dyn Context, dt: f32, events: &[Event]) -> Result<SceneResult> {
         for event in events {
             let event_string = format!("[{:.3}] {:?}", ctx.time().uptime().as_secs_f32(), event);
@@ -146,10 +140,7 @@ impl Scene for SceneDebug {
         if ctx.input().is_key_just_pressed(ScanCode::Escape)
             || self.common_data.button_close.just_clicked()
         {
-            if let Some(s) = self.prev_scene.take() {
-                return Ok(SceneResult::Switch(s));
-            }
-            return Ok(SceneResult::Quit);
+            return Ok(SceneResult::Pop);
         }
 
         let physical_size = ctx.get_physical_window_size();
@@ -192,7 +183,9 @@ impl Scene for SceneDebug {
     fn render(&mut self, ctx: &mut dyn Context) -> Result<()> {
         let v = ctx.view().visible_area();
 
-        self.common_data.draw_back(ctx)?;
+        // Меню под этой сценой остаётся видно сквозь полупрозрачный фон
+        ctx.set_fill_color(BACK_COLOR);
+        ctx.fill_rect(v)?;
 
         self.cake.render(ctx)?;
 
@@ -220,8 +213,12 @@ impl Scene for SceneDebug {
         }
 
         self.common_data.button_close.render(ctx)?;
-        self.common_data.draw_fps_counter(ctx)?;
+        self.common_data.draw_overlay(ctx)?;
         self.common_data.draw_cursor(ctx)?;
         Ok(())
     }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,768 @@
+use crate::{
+    balance::Balance,
+    common_data::CommonData,
+    data::{options::OPTIONS, texts::get_text},
+    game::{Zone, BLOCKH, BLOCKW, CAKEW, CAKE_Y, ZONEH1},
+    level::{Level, LevelCake, CAKE_SPRITES_COUNT},
+    scene::{menu_misc::SceneMenuMisc, play::ScenePlay},
+    utils::{btn, btn_small, spr, tex},
+};
+use anyhow::Result;
+use cake_engine::{
+    button::Button,
+    color::Color,
+    context::Context,
+    input::{Event, MouseButton, ScanCode},
+    label::Label,
+    rect::Rect,
+    scene::{Scene, SceneResult},
+    sprite::Sprite,
+    texture::Texture,
+    vec::Vec2,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::rc::Rc;
+
+const TOP: f32 = 200.0;
+const STEP: f32 = 54.0;
+const BALANCE_TOP: f32 = 110.0;
+const BALANCE_STEP: f32 = 30.0;
+const BUT_Y: f32 = 700.0;
+
+// Насколько близко к краю платформы нужно схватить её, чтобы менять ширину, а не двигать
+const EDGE: f32 = 12.0;
+// Шаг сетки, к которой прилипают платформы и кексики
+const SNAP: f32 = 2.0;
+const HP_STEPS: [f32; 4] = [0.25, 0.5, 0.75, 1.0];
+
+const ZONE_COLOR: Color = Color::new(0, 128, 255, 64);
+const ZONE_ACTIVE_COLOR: Color = Color::new(0, 128, 255, 128);
+const OUTLINE_COLOR: Color = Color::new(255, 255, 255, 160);
+const SELECTED_COLOR: Color = Color::new(255, 0, 255, 255);
+const HP_COLOR: Color = Color::new(0, 255, 0, 255);
+const PANEL_COLOR: Color = Color::new(40, 40, 40, 200);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Tool {
+    Zones,
+    Cakes,
+    Luna,
+    Celestia,
+}
+
+impl Tool {
+    fn code(&self) -> &'static str {
+        match self {
+            Tool::Zones => "zones",
+            Tool::Cakes => "cakes",
+            Tool::Luna => "luna",
+            Tool::Celestia => "celestia",
+        }
+    }
+
+    fn next(&self) -> Tool {
+        match self {
+            Tool::Zones => Tool::Cakes,
+            Tool::Cakes => Tool::Luna,
+            Tool::Luna => Tool::Celestia,
+            Tool::Celestia => Tool::Zones,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Drag {
+    Zone { zoneidx: usize, grab: Vec2 },
+    ZoneLeft(usize),
+    ZoneRight(usize),
+    Cake { cakeidx: usize, grab_x: f32 },
+}
+
+fn snap(v: f32) -> f32 {
+    (v / SNAP).round() * SNAP
+}
+
+fn zone_rect(zone: &Zone) -> Rect {
+    Rect::new(
+        zone.left,
+        zone.y - ZONEH1,
+        zone.right - zone.left,
+        ZONEH1 + BLOCKH,
+    )
+}
+
+pub struct SceneEditor {
+    common_data: CommonData,
+    level: Level,
+    block: Rc<Texture>,
+    cakes: [Rc<Texture>; CAKE_SPRITES_COUNT],
+    luna: Sprite,
+    celestia: Sprite,
+    tool: Tool,
+    drag: Option<Drag>,
+    selected_cake: Option<usize>,
+    cake_spriteidx: usize,
+    cake_hp_idx: usize,
+    menu_opened: bool,
+    balance_opened: bool,
+    balance_row: usize,
+    message: String,
+    status_label: Label,
+    balance_labels: Vec<Label>,
+    button_tool: Button,
+    button_balance: Button,
+    button_new: Button,
+    button_save: Button,
+    button_load: Button,
+    button_play: Button,
+    button_back: Button,
+    button_minus: Button,
+    button_plus: Button,
+    button_balance_back: Button,
+}
+
+impl SceneEditor {
+    pub fn new(common_data: CommonData, ctx: &mut dyn Context) -> Result<SceneEditor> {
+        let mut message = String::new();
+
+        let level = match Level::load() {
+            Ok(level) => level,
+            Err(e) => {
+                if Level::path().is_some_and(|p| p.exists()) {
+                    cake_engine::log::error!("Failed to load level: {:?}", e);
+                    message = format!("{}: {}", get_text("editor_error"), e);
+                }
+                SceneEditor::generate_level()
+            }
+        };
+
+        let mut s = SceneEditor::with_level(common_data, ctx, level)?;
+        s.message = message;
+        Ok(s)
+    }
+
+    pub fn with_level(
+        common_data: CommonData,
+        ctx: &mut dyn Context,
+        level: Level,
+    ) -> Result<SceneEditor> {
+        let view = ctx.view().get_or_default();
+
+        let mut pos = Vec2::new(view.width / 2.0, TOP);
+        let button_tool = btn!(common_data, "", pos);
+
+        pos.y += STEP;
+        let button_balance = btn!(common_data, get_text("menubalance"), pos);
+
+        pos.y += STEP;
+        let button_new = btn!(common_data, get_text("menunew"), pos);
+
+        pos.y += STEP;
+        let button_save = btn!(common_data, get_text("menusave"), pos);
+
+        pos.y += STEP;
+        let button_load = btn!(common_data, get_text("menuload"), pos);
+
+        pos.y += STEP;
+        let button_play = btn!(common_data, get_text("menuplaytest"), pos);
+
+        pos.y += STEP;
+        let button_back = btn!(common_data, get_text("menuback"), pos);
+
+        let bw = common_data.button_small.width() as f32 + 12.0;
+        let mut button_minus =
+            btn_small!(common_data, "-", Vec2::new(view.width / 2.0 - bw, BUT_Y));
+        button_minus.set_origin(Vec2::new(0.5, 0.5));
+        let mut button_plus = btn_small!(common_data, "+", Vec2::new(view.width / 2.0, BUT_Y));
+        button_plus.set_origin(Vec2::new(0.5, 0.5));
+        let mut button_balance_back = btn_small!(
+            common_data,
+            get_text("menuback"),
+            Vec2::new(view.width / 2.0 + bw, BUT_Y)
+        );
+        button_balance_back.set_origin(Vec2::new(0.5, 0.5));
+
+        let balance_labels = Balance::default()
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, _)| {
+                let mut l = Label::new(common_data.font_button.clone(), Color::WHITE);
+                l.set_position(Vec2::new(
+                    view.width / 2.0,
+                    BALANCE_TOP + BALANCE_STEP * (i as f32 + 0.5),
+                ));
+                l.set_origin(Vec2::new(0.5, 0.5));
+                l
+            })
+            .collect();
+
+        let mut status_label = Label::new(common_data.font_small.clone(), Color::WHITE);
+        status_label.set_position(Vec2::new(10.0, 6.0));
+        status_label.set_shadow(Color::BLACK, Vec2::new(1.0, 1.0));
+
+        let mut s = SceneEditor {
+            common_data,
+            level,
+            block: tex!(ctx, "images/block.png"),
+            cakes: [
+                tex!(ctx, "images/cake1.png"),
+                tex!(ctx, "images/cake2.png"),
+                tex!(ctx, "images/cake3.png"),
+            ],
+            luna: spr!(ctx, "images/luna_wait.png", 6.0, grid: (6, 1)),
+            celestia: spr!(ctx, "images/celestia_eat.png", 6.0, grid: (6, 1)),
+            tool: Tool::Zones,
+            drag: None,
+            selected_cake: None,
+            cake_spriteidx: 0,
+            cake_hp_idx: HP_STEPS.len() - 1,
+            menu_opened: false,
+            balance_opened: false,
+            balance_row: 0,
+            message: String::new(),
+            status_label,
+            balance_labels,
+            button_tool,
+            button_balance,
+            button_new,
+            button_save,
+            button_load,
+            button_play,
+            button_back,
+            button_minus,
+            button_plus,
+            button_balance_back,
+        };
+
+        s.luna.set_origin(Vec2::new(0.5, 0.0));
+        s.celestia.set_origin(Vec2::new(0.5, 0.0));
+        s.update_labels();
+
+        Ok(s)
+    }
+
+    fn generate_level() -> Level {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        Level::generate(OPTIONS.lock().unwrap().get_difficulty(), &mut rng)
+    }
+
+    fn update_labels(&mut self) {
+        let tool_name = get_text(&format!("editor_tool_{}", self.tool.code()));
+        self.button_tool
+            .set_text(format!("{}: {}", get_text("editor_tool"), tool_name));
+
+        let hint = match self.tool {
+            Tool::Zones => get_text("editor_hint_zones"),
+            Tool::Cakes => get_text("editor_hint_cakes"),
+            Tool::Luna | Tool::Celestia => get_text("editor_hint_start"),
+        };
+
+        self.status_label.set_text(format!(
+            "{}: {} | {}: {} | {}: {:.2}\n{}\n{}\n{}",
+            get_text("editor_tool"),
+            tool_name,
+            get_text("editor_sprite"),
+            self.cake_spriteidx + 1,
+            get_text("editor_hp"),
+            HP_STEPS[self.cake_hp_idx],
+            hint,
+            get_text("editor_hint_common"),
+            self.message,
+        ));
+
+        for ((name, value), label) in self
+            .level
+            .balance
+            .fields()
+            .iter()
+            .zip(self.balance_labels.iter_mut())
+        {
+            label.set_text(format!("{}: {:.3}", name, value));
+        }
+    }
+
+    fn set_message(&mut self, message: String) {
+        self.message = message;
+        self.update_labels();
+    }
+
+    fn zone_at(&self, p: Vec2) -> Option<usize> {
+        // Перебираем с конца, чтобы выбиралась платформа, нарисованная поверх остальных
+        (0..self.level.zones.len())
+            .rev()
+            .find(|&i| zone_rect(&self.level.zones[i]).contains_point(p))
+    }
+
+    fn cake_rect(&self, cakeidx: usize) -> Rect {
+        let cake = &self.level.cakes[cakeidx];
+        let zone = &self.level.zones[cake.zoneidx];
+        Rect::new(
+            cake.x - CAKEW / 2.0,
+            zone.y - CAKEW / 2.0 - CAKE_Y,
+            CAKEW,
+            CAKEW,
+        )
+    }
+
+    fn cake_at(&self, p: Vec2) -> Option<usize> {
+        (0..self.level.cakes.len())
+            .rev()
+            .find(|&i| self.cake_rect(i).contains_point(p))
+    }
+
+    fn clamp_cakes(&mut self, zoneidx: usize) {
+        let zone = self.level.zones[zoneidx];
+        for cake in self.level.cakes.iter_mut() {
+            if cake.zoneidx == zoneidx {
+                cake.x = cake
+                    .x
+                    .clamp(zone.left + CAKEW / 2.0, zone.right - CAKEW / 2.0);
+            }
+        }
+    }
+
+    fn process_drag(&mut self, mxy: Vec2) {
+        match self.drag {
+            Some(Drag::Zone { zoneidx, grab }) => {
+                let zone = &mut self.level.zones[zoneidx];
+                let dx = snap(mxy.x - grab.x) - zone.left;
+                zone.left += dx;
+                zone.right += dx;
+                zone.y = snap(mxy.y - grab.y);
+                for cake in self.level.cakes.iter_mut() {
+                    if cake.zoneidx == zoneidx {
+                        cake.x += dx;
+                    }
+                }
+            }
+            Some(Drag::ZoneLeft(zoneidx)) => {
+                let zone = &mut self.level.zones[zoneidx];
+                let n = ((zone.right - mxy.x) / BLOCKW).round().max(1.0);
+                zone.left = zone.right - n * BLOCKW;
+                self.clamp_cakes(zoneidx);
+            }
+            Some(Drag::ZoneRight(zoneidx)) => {
+                let zone = &mut self.level.zones[zoneidx];
+                let n = ((mxy.x - zone.left) / BLOCKW).round().max(1.0);
+                zone.right = zone.left + n * BLOCKW;
+                self.clamp_cakes(zoneidx);
+            }
+            Some(Drag::Cake { cakeidx, grab_x }) => {
+                self.level.cakes[cakeidx].x = snap(mxy.x - grab_x);
+                self.clamp_cakes(self.level.cakes[cakeidx].zoneidx);
+            }
+            None => {}
+        }
+    }
+
+    fn process_board(&mut self, ctx: &mut dyn Context) {
+        let input = ctx.input();
+        let mxy = input.get_mouse_position();
+
+        if self.drag.is_some() {
+            if input.is_mouse_button_pressed(MouseButton::Left) {
+                self.process_drag(mxy);
+            } else {
+                self.drag = None;
+            }
+            return;
+        }
+
+        let lmb = input.is_mouse_button_just_pressed(MouseButton::Left);
+        let rmb = input.is_mouse_button_just_pressed(MouseButton::Right);
+
+        match self.tool {
+            Tool::Zones => {
+                if input.is_key_just_pressed(ScanCode::N) {
+                    let left = snap(mxy.x - BLOCKW * 1.5);
+                    self.level.zones.push(Zone {
+                        y: snap(mxy.y),
+                        left,
+                        right: left + BLOCKW * 3.0,
+                    });
+                } else if let Some(zoneidx) = self.zone_at(mxy) {
+                    let zone = self.level.zones[zoneidx];
+                    if lmb {
+                        self.drag = Some(if mxy.x < zone.left + EDGE {
+                            Drag::ZoneLeft(zoneidx)
+                        } else if mxy.x > zone.right - EDGE {
+                            Drag::ZoneRight(zoneidx)
+                        } else {
+                            Drag::Zone {
+                                zoneidx,
+                                grab: Vec2::new(mxy.x - zone.left, mxy.y - zone.y),
+                            }
+                        });
+                    } else if rmb && self.level.remove_zone(zoneidx) {
+                        self.selected_cake = None;
+                    }
+                }
+            }
+            Tool::Cakes => {
+                if let Some(cakeidx) = self.cake_at(mxy) {
+                    if lmb {
+                        self.selected_cake = Some(cakeidx);
+                        self.drag = Some(Drag::Cake {
+                            cakeidx,
+                            grab_x: mxy.x - self.level.cakes[cakeidx].x,
+                        });
+                    } else if rmb {
+                        self.level.cakes.remove(cakeidx);
+                        self.selected_cake = None;
+                    }
+                } else if lmb {
+                    if let Some(zoneidx) = self.zone_at(mxy) {
+                        self.level.cakes.push(LevelCake {
+                            zoneidx,
+                            x: snap(mxy.x),
+                            spriteidx: self.cake_spriteidx,
+                            hp: HP_STEPS[self.cake_hp_idx],
+                        });
+                        self.selected_cake = Some(self.level.cakes.len() - 1);
+                        self.clamp_cakes(zoneidx);
+                    }
+                }
+            }
+            Tool::Luna | Tool::Celestia => {
+                if lmb {
+                    if let Some(zoneidx) = self.zone_at(mxy) {
+                        if self.tool == Tool::Luna {
+                            self.level.luna_zone_idx = zoneidx;
+                        } else {
+                            self.level.celestia_zone_idx = zoneidx;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn process_keys(&mut self, ctx: &mut dyn Context) {
+        let input = ctx.input();
+
+        let tool = if input.is_key_just_pressed(ScanCode::Num1) {
+            Some(Tool::Zones)
+        } else if input.is_key_just_pressed(ScanCode::Num2) {
+            Some(Tool::Cakes)
+        } else if input.is_key_just_pressed(ScanCode::Num3) {
+            Some(Tool::Luna)
+        } else if input.is_key_just_pressed(ScanCode::Num4) {
+            Some(Tool::Celestia)
+        } else {
+            None
+        };
+        if let Some(tool) = tool {
+            self.tool = tool;
+            self.drag = None;
+        }
+
+        if input.is_key_just_pressed(ScanCode::S) {
+            self.cake_spriteidx = (self.cake_spriteidx + 1) % CAKE_SPRITES_COUNT;
+            if let Some(cakeidx) = self.selected_cake {
+                self.level.cakes[cakeidx].spriteidx = self.cake_spriteidx;
+            }
+        }
+
+        if input.is_key_just_pressed(ScanCode::H) {
+            self.cake_hp_idx = (self.cake_hp_idx + 1) % HP_STEPS.len();
+            if let Some(cakeidx) = self.selected_cake {
+                self.level.cakes[cakeidx].hp = HP_STEPS[self.cake_hp_idx];
+            }
+        }
+
+        if ctx.input().is_key_just_pressed(ScanCode::Delete) {
+            if let Some(cakeidx) = self.selected_cake.take() {
+                self.level.cakes.remove(cakeidx);
+            }
+        }
+    }
+
+    fn change_balance(&mut self, sign: f32) {
+        let step = Balance::default().fields()[self.balance_row].1 * 0.1;
+        let (_, value) = &mut self.level.balance.fields_mut()[self.balance_row];
+        // Округляем, чтобы в файле не копились хвосты вида 0.30000001
+        **value = ((**value + step * sign).max(0.0) * 1000.0).round() / 1000.0;
+    }
+
+    fn process_balance(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        self.button_minus.process(ctx)?;
+        self.button_plus.process(ctx)?;
+        self.button_balance_back.process(ctx)?;
+
+        let input = ctx.input();
+        let rows = self.balance_labels.len();
+
+        if input.is_key_just_pressed(ScanCode::Escape) || self.button_balance_back.just_clicked() {
+            self.balance_opened = false;
+            return Ok(());
+        }
+
+        if input.is_key_just_pressed(ScanCode::Up) {
+            self.balance_row = (self.balance_row + rows - 1) % rows;
+        } else if input.is_key_just_pressed(ScanCode::Down) {
+            self.balance_row = (self.balance_row + 1) % rows;
+        }
+
+        if input.is_mouse_button_just_pressed(MouseButton::Left) {
+            let view = ctx.view().get_or_default();
+            let mxy = input.get_mouse_position();
+            for i in 0..rows {
+                let r = Rect::new(
+                    view.width / 4.0,
+                    BALANCE_TOP + BALANCE_STEP * i as f32,
+                    view.width / 2.0,
+                    BALANCE_STEP,
+                );
+                if r.contains_point(mxy) {
+                    self.balance_row = i;
+                }
+            }
+        }
+
+        if input.is_key_just_pressed(ScanCode::Left) || self.button_minus.just_clicked() {
+            self.change_balance(-1.0);
+        } else if input.is_key_just_pressed(ScanCode::Right) || self.button_plus.just_clicked() {
+            self.change_balance(1.0);
+        }
+
+        Ok(())
+    }
+
+    fn process_menu(&mut self, ctx: &mut dyn Context) -> Result<SceneResult> {
+        self.button_tool.process(ctx)?;
+        self.button_balance.process(ctx)?;
+        self.button_new.process(ctx)?;
+        self.button_save.process(ctx)?;
+        self.button_load.process(ctx)?;
+        self.button_play.process(ctx)?;
+        self.button_back.process(ctx)?;
+
+        if ctx.input().is_key_just_pressed(ScanCode::Escape)
+            || ctx.input().is_key_just_pressed(ScanCode::Tab)
+        {
+            self.menu_opened = false;
+        }
+
+        if self.button_back.just_clicked() {
+            let s = SceneMenuMisc::new(self.common_data.clone(), ctx);
+            return Ok(SceneResult::Switch(Box::new(s)));
+        }
+
+        if self.button_tool.just_clicked() {
+            self.tool = self.tool.next();
+        }
+
+        if self.button_balance.just_clicked() {
+            self.menu_opened = false;
+            self.balance_opened = true;
+        }
+
+        if self.button_new.just_clicked() {
+            self.level = SceneEditor::generate_level();
+            self.selected_cake = None;
+            self.menu_opened = false;
+        }
+
+        if self.button_save.just_clicked() {
+            let result = self.level.validate().and_then(|_| self.level.save());
+            match result {
+                Ok(()) => self.set_message(get_text("editor_saved")),
+                Err(e) => {
+                    cake_engine::log::error!("Failed to save level: {:?}", e);
+                    self.set_message(format!("{}: {}", get_text("editor_error"), e));
+                }
+            }
+            self.menu_opened = false;
+        }
+
+        if self.button_load.just_clicked() {
+            match Level::load() {
+                Ok(level) => {
+                    self.level = level;
+                    self.selected_cake = None;
+                    self.set_message(get_text("editor_loaded"));
+                }
+                Err(e) => {
+                    cake_engine::log::error!("Failed to load level: {:?}", e);
+                    self.set_message(format!("{}: {}", get_text("editor_error"), e));
+                }
+            }
+            self.menu_opened = false;
+        }
+
+        if self.button_play.just_clicked() {
+            match self.level.validate() {
+                Ok(()) => {
+                    let s =
+                        ScenePlay::with_level(self.common_data.clone(), ctx, self.level.clone())?;
+                    return Ok(SceneResult::Switch(Box::new(s)));
+                }
+                Err(e) => {
+                    self.set_message(format!("{}: {}", get_text("editor_error"), e));
+                    self.menu_opened = false;
+                }
+            }
+        }
+
+        Ok(SceneResult::Normal)
+    }
+
+    fn render_board(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        let mxy = ctx.input().get_mouse_position();
+        let hovered_zone = match self.drag {
+            Some(Drag::Zone { zoneidx, .. })
+            | Some(Drag::ZoneLeft(zoneidx))
+            | Some(Drag::ZoneRight(zoneidx)) => Some(zoneidx),
+            _ => self.zone_at(mxy),
+        };
+
+        for (i, zone) in self.level.zones.iter().enumerate() {
+            let n = (zone.right - zone.left).round() as i32 / BLOCKW as i32;
+            for j in 0..n {
+                ctx.draw_texture(
+                    &self.block,
+                    Vec2::new(zone.left + j as f32 * BLOCKW, zone.y),
+                    Vec2::new(0.0, 0.0),
+                )?;
+            }
+
+            let rect = zone_rect(zone);
+            ctx.set_fill_color(if hovered_zone == Some(i) {
+                ZONE_ACTIVE_COLOR
+            } else {
+                ZONE_COLOR
+            });
+            ctx.fill_rect(rect)?;
+            ctx.set_fill_color(OUTLINE_COLOR);
+            ctx.draw_rect_outline(rect, 1.0)?;
+        }
+
+        let celestia_zone = self.level.zones[self.level.celestia_zone_idx];
+        self.celestia.set_position(Vec2::new(
+            (celestia_zone.left + celestia_zone.right) / 2.0,
+            celestia_zone.y - 128.0,
+        ));
+        self.celestia.render(ctx)?;
+
+        let luna_zone = self.level.zones[self.level.luna_zone_idx];
+        self.luna.set_position(Vec2::new(
+            (luna_zone.left + luna_zone.right) / 2.0,
+            luna_zone.y - 126.0,
+        ));
+        self.luna.render(ctx)?;
+
+        for (i, cake) in self.level.cakes.iter().enumerate() {
+            let zone = &self.level.zones[cake.zoneidx];
+            ctx.draw_texture(
+                &self.cakes[cake.spriteidx],
+                Vec2::new(cake.x, zone.y - CAKE_Y),
+                Vec2::new(0.5, 0.5),
+            )?;
+
+            if cake.hp < 1.0 {
+                ctx.set_fill_color(HP_COLOR);
+                ctx.fill_rect(Rect::new(
+                    cake.x - CAKEW / 2.0,
+                    zone.y,
+                    CAKEW * cake.hp,
+                    8.0,
+                ))?;
+            }
+
+            if self.selected_cake == Some(i) {
+                ctx.set_fill_color(SELECTED_COLOR);
+                ctx.draw_rect_outline(self.cake_rect(i), 1.0)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Scene for SceneEditor {
+    fn process(
+        &mut self,
+        ctx: &mut dyn Context,
+        dt: f32,
+        _events: &[Event],
+    ) -> Result<SceneResult> {
+        if ctx.input().is_quit_requested() {
+            return Ok(SceneResult::Quit);
+        }
+
+        self.common_data.process(ctx)?;
+
+        if self.balance_opened {
+            self.process_balance(ctx)?;
+        } else if self.menu_opened {
+            let result = self.process_menu(ctx)?;
+            if !matches!(result, SceneResult::Normal) {
+                return Ok(result);
+            }
+        } else if ctx.input().is_key_just_pressed(ScanCode::Escape)
+            || ctx.input().is_key_just_pressed(ScanCode::Tab)
+        {
+            self.menu_opened = true;
+            self.drag = None;
+        } else {
+            self.process_keys(ctx);
+            self.process_board(ctx);
+        }
+
+        self.luna.process(dt);
+        self.celestia.process(dt);
+        self.update_labels();
+
+        Ok(SceneResult::Normal)
+    }
+
+    fn render(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        let view = ctx.view().get_or_default();
+        let area = ctx.view().visible_area();
+
+        self.common_data.draw_back(ctx)?;
+        self.render_board(ctx)?;
+        self.status_label.render(ctx)?;
+
+        if self.menu_opened || self.balance_opened {
+            ctx.set_fill_color(PANEL_COLOR);
+            ctx.fill_rect(area)?;
+        }
+
+        if self.menu_opened {
+            self.button_tool.render(ctx)?;
+            self.button_balance.render(ctx)?;
+            self.button_new.render(ctx)?;
+            self.button_save.render(ctx)?;
+            self.button_load.render(ctx)?;
+            self.button_play.render(ctx)?;
+            self.button_back.render(ctx)?;
+        }
+
+        if self.balance_opened {
+            ctx.set_fill_color(Color::new(255, 255, 255, 48));
+            ctx.fill_rect(Rect::new(
+                view.width / 4.0,
+                BALANCE_TOP + BALANCE_STEP * self.balance_row as f32,
+                view.width / 2.0,
+                BALANCE_STEP,
+            ))?;
+            for label in self.balance_labels.iter_mut() {
+                label.render(ctx)?;
+            }
+            self.button_minus.render(ctx)?;
+            self.button_plus.render(ctx)?;
+            self.button_balance_back.render(ctx)?;
+        }
+
+        self.common_data.draw_overlay(ctx)?;
+        self.common_data.draw_cursor(ctx)?;
+        Ok(())
+    }
+}
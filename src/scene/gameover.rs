@@ -1,7 +1,9 @@
 use crate::{
     common_data::CommonData,
     data::texts::get_text,
-    scene::{menu::SceneMenu, play::ScenePlay},
+    level::Level,
+    mutator::Mutator,
+    scene::{editor::SceneEditor, menu::SceneMenu, play::ScenePlay},
     utils::btn_small,
 };
 use anyhow::Result;
@@ -13,16 +15,23 @@ use cake_engine::{
     label::Label,
     rect::Rect,
     scene::{Scene, SceneResult},
+    tween::{Animation, Easing, Sequence, Track, Tween},
     vec::Vec2,
 };
 
 const BUT_Y: f32 = 730.0;
+// Насколько ниже своего места кнопки начинают выезжать
+const BUT_SLIDE: f32 = 200.0;
 
 pub struct SceneGameOver {
     common_data: CommonData,
     label: Label,
+    info_label: Label,
     button_restart: Button,
     button_menu: Button,
+    label_anim: Track<Label, Vec2>,
+    button_anims: [Sequence<Button>; 2],
+    level: Option<Level>,
 }
 
 impl SceneGameOver {
@@ -54,6 +63,13 @@ impl SceneGameOver {
             msg,
         ));
 
+        let mut info_label = Label::new(
+            common_data.font_main.clone(),
+            Color::new(255, 255, 255, 255),
+        );
+        info_label.set_text_align(0.5);
+        info_label.set_origin(Vec2::new(0.5, 0.0));
+
         let mut button_restart = btn_small!(
             common_data,
             get_text("menurestart"),
@@ -74,12 +90,53 @@ impl SceneGameOver {
         );
         button_menu.set_origin(Vec2::new(1.0, 0.5));
 
+        // Надпись выпрыгивает, а кнопки по очереди выезжают снизу
+        let label_anim = Tween::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0), 0.4)
+            .with_easing(Easing::BackOut)
+            .bind(Label::set_scale);
+        label.set_scale(Vec2::new(0.0, 0.0));
+
+        let button_anims = [
+            slide_in(&mut button_restart, 0.2),
+            slide_in(&mut button_menu, 0.3),
+        ];
+
         SceneGameOver {
             common_data,
             label,
+            info_label,
             button_restart,
             button_menu,
+            label_anim,
+            button_anims,
+            level: None,
+        }
+    }
+
+    /// Задаёт уровень из редактора, чтобы перезапуск играл его же, а выход вёл в редактор.
+    pub fn set_level(&mut self, level: Option<Level>) {
+        self.level = level;
+    }
+
+    /// Показывает, с какими модификаторами шла партия и была ли включена адаптивная
+    /// сложность.
+    pub fn set_mutators(&mut self, mutators: &[Mutator], adaptive: bool) {
+        let mut lines = Vec::new();
+        if !mutators.is_empty() {
+            let names: Vec<_> = mutators
+                .iter()
+                .map(|m| get_text(&format!("mutator_{}", m.code())))
+                .collect();
+            lines.push(format!(
+                "{}: {}",
+                get_text("text_mutators"),
+                names.join(", ")
+            ));
         }
+        if adaptive {
+            lines.push(get_text("menuadaptive"));
+        }
+        self.info_label.set_text(lines.join("\n"));
     }
 }
 
@@ -87,21 +144,32 @@ impl Scene for SceneGameOver {
     fn process(
         &mut self,
         ctx: &mut dyn Context,
-        _dt: f32,
+        dt: f32,
         _events: &[Event],
     ) -> Result<SceneResult> {
         self.common_data.process(ctx)?;
 
+        self.label_anim.update(&mut self.label, dt);
+        self.button_anims[0].update(&mut self.button_restart, dt);
+        self.button_anims[1].update(&mut self.button_menu, dt);
+
         self.button_restart.process(ctx)?;
         self.button_menu.process(ctx)?;
 
         if ctx.input().is_key_just_pressed(ScanCode::Escape) || self.button_menu.just_clicked() {
+            if let Some(level) = self.level.take() {
+                let s = SceneEditor::with_level(self.common_data.clone(), ctx, level)?;
+                return Ok(SceneResult::Switch(Box::new(s)));
+            }
             let s = SceneMenu::new(self.common_data.clone(), ctx)?;
             return Ok(SceneResult::Switch(Box::new(s)));
         }
 
         if self.button_restart.just_clicked() {
-            let s = ScenePlay::new(self.common_data.clone(), ctx)?;
+            let s = match self.level.take() {
+                Some(level) => ScenePlay::with_level(self.common_data.clone(), ctx, level)?,
+                None => ScenePlay::new(self.common_data.clone(), ctx)?,
+            };
             return Ok(SceneResult::Switch(Box::new(s)));
         }
 
@@ -120,11 +188,29 @@ impl Scene for SceneGameOver {
         self.label.set_position(Vec2::new(view.width / 2.0, 150.0));
         self.label.render(ctx)?;
 
+        self.info_label.set_max_width(view.width - 440.0);
+        self.info_label
+            .set_position(Vec2::new(view.width / 2.0, 420.0));
+        self.info_label.render(ctx)?;
+
         self.button_restart.render(ctx)?;
         self.button_menu.render(ctx)?;
 
-        self.common_data.draw_fps_counter(ctx)?;
+        self.common_data.draw_overlay(ctx)?;
         self.common_data.draw_cursor(ctx)?;
         Ok(())
     }
 }
+
+// Прячет кнопку под её место и возвращает анимацию, которая выкатит её обратно
+fn slide_in(button: &mut Button, delay: f32) -> Sequence<Button> {
+    let to = button.position();
+    let from = Vec2::new(to.x, to.y + BUT_SLIDE);
+    button.set_position(from);
+
+    Sequence::new().then_delay(delay).then(
+        Tween::new(from, to, 0.35)
+            .with_easing(Easing::CubicOut)
+            .bind(Button::set_position),
+    )
+}
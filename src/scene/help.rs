@@ -85,7 +85,7 @@ impl Scene for SceneHelp {
         self.label.render(ctx)?;
         self.button_back.render(ctx)?;
 
-        self.common_data.draw_fps_counter(ctx)?;
+        self.common_data.draw_overlay(ctx)?;
         self.common_data.draw_cursor(ctx)?;
         Ok(())
     }
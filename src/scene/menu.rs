@@ -3,7 +3,7 @@ use crate::{
     data::{options::OPTIONS, texts::get_text},
     scene::{
         achievements::SceneAchievements, help::SceneHelp, menu_misc::SceneMenuMisc,
-        menu_settings::SceneMenuSettings, play::ScenePlay,
+        menu_settings::SceneMenuSettings, mutators::SceneMutators,
     },
     utils::{btn, tex_lang},
 };
@@ -25,6 +25,7 @@ pub struct SceneMenu {
     common_data: CommonData,
     button_start: Button,
     button_diff: Button,
+    button_adaptive: Button,
     button_achievments: Button,
     button_help: Button,
     button_settings: Button,
@@ -44,6 +45,9 @@ impl SceneMenu {
         pos.y += STEP;
         let button_diff = btn!(common_data, SceneMenu::get_diff_label(), pos);
 
+        pos.y += STEP;
+        let button_adaptive = btn!(common_data, SceneMenu::get_adaptive_label(), pos);
+
         pos.y += STEP;
         let button_achievments = btn!(
             common_data,
@@ -70,6 +74,7 @@ impl SceneMenu {
             common_data,
             button_start,
             button_diff,
+            button_adaptive,
             button_achievments,
             button_help,
             button_settings,
@@ -86,6 +91,19 @@ impl SceneMenu {
         format!("{} : {}", get_text("menudiff"), diff_label)
     }
 
+    fn get_adaptive_label() -> String {
+        let options = OPTIONS.lock().unwrap();
+        format!(
+            "{} : {}",
+            get_text("menuadaptive"),
+            get_text(if options.get_adaptive_difficulty() {
+                "text_on"
+            } else {
+                "text_off"
+            }),
+        )
+    }
+
     fn get_achievements_label(c: &CommonData) -> String {
         format!(
             "{} ({}/{})",
@@ -115,9 +133,11 @@ impl Scene for SceneMenu {
         }
 
         self.common_data.process(ctx)?;
+        self.common_data.play_music(ctx, "menu")?;
 
         self.button_start.process(ctx)?;
         self.button_diff.process(ctx)?;
+        self.button_adaptive.process(ctx)?;
         self.button_achievments.process(ctx)?;
         self.button_help.process(ctx)?;
         self.button_settings.process(ctx)?;
@@ -132,7 +152,7 @@ impl Scene for SceneMenu {
         let mut options_changed = false;
 
         if self.button_start.just_clicked() {
-            let s = ScenePlay::new(self.common_data.clone(), ctx)?;
+            let s = SceneMutators::new(self.common_data.clone(), ctx);
             return Ok(SceneResult::Switch(Box::new(s)));
         }
 
@@ -177,6 +197,13 @@ impl Scene for SceneMenu {
             options_changed = true;
         }
 
+        if self.button_adaptive.just_clicked() {
+            OPTIONS.lock().unwrap().invert_adaptive_difficulty();
+            self.button_adaptive
+                .set_text(SceneMenu::get_adaptive_label());
+            options_changed = true;
+        }
+
         if options_changed {
             if let Err(e) = OPTIONS.lock().unwrap().save() {
                 cake_engine::log::error!("Failed to save options: {:?}", e);
@@ -193,6 +220,7 @@ impl Scene for SceneMenu {
 
         self.button_start.render(ctx)?;
         self.button_diff.render(ctx)?;
+        self.button_adaptive.render(ctx)?;
         self.button_achievments.render(ctx)?;
         self.button_help.render(ctx)?;
         self.button_settings.render(ctx)?;
@@ -215,7 +243,7 @@ impl Scene for SceneMenu {
             Vec2::new(0.5, 0.5),
         )?;
 
-        self.common_data.draw_fps_counter(ctx)?;
+        self.common_data.draw_overlay(ctx)?;
         self.common_data.draw_cursor(ctx)?;
         Ok(())
     }
@@ -227,7 +227,7 @@ impl Scene for SceneMenuCtrl {
             Vec2::new(0.5, 0.5),
         )?;
 
-        self.common_data.draw_fps_counter(ctx)?;
+        self.common_data.draw_overlay(ctx)?;
         if self.changing_action.is_none() {
             self.common_data.draw_cursor(ctx)?;
         }
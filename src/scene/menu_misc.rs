@@ -1,7 +1,10 @@
 use crate::{
     common_data::CommonData,
     data::texts::get_text,
-    scene::{about::SceneAbout, bench::SceneBench, debug::SceneDebug, menu::SceneMenu},
+    scene::{
+        about::SceneAbout, bench::SceneBench, debug::SceneDebug, editor::SceneEditor,
+        menu::SceneMenu, mods::SceneMods,
+    },
     utils::btn,
 };
 use anyhow::Result;
@@ -20,6 +23,8 @@ pub struct SceneMenuMisc {
     common_data: CommonData,
     button_bench: Button,
     button_debug: Button,
+    button_editor: Button,
+    button_mods: Button,
     button_about: Button,
     button_back: Button,
 }
@@ -34,6 +39,12 @@ impl SceneMenuMisc {
         pos.y += STEP;
         let button_debug = btn!(common_data, get_text("menudebug"), pos);
 
+        pos.y += STEP;
+        let button_editor = btn!(common_data, get_text("menueditor"), pos);
+
+        pos.y += STEP;
+        let button_mods = btn!(common_data, get_text("menumods"), pos);
+
         pos.y += STEP;
         let button_about = btn!(common_data, get_text("menuabout"), pos);
 
@@ -44,6 +55,8 @@ impl SceneMenuMisc {
             common_data,
             button_bench,
             button_debug,
+            button_editor,
+            button_mods,
             button_about,
             button_back,
         }
@@ -65,6 +78,8 @@ impl Scene for SceneMenuMisc {
 
         self.button_bench.process(ctx)?;
         self.button_debug.process(ctx)?;
+        self.button_editor.process(ctx)?;
+        self.button_mods.process(ctx)?;
         self.button_about.process(ctx)?;
         self.button_back.process(ctx)?;
 
@@ -80,6 +95,16 @@ impl Scene for SceneMenuMisc {
 
         if self.button_debug.just_clicked() {
             let s = SceneDebug::new(self.common_data.clone(), ctx)?;
+            return Ok(SceneResult::Push(Box::new(s)));
+        }
+
+        if self.button_editor.just_clicked() {
+            let s = SceneEditor::new(self.common_data.clone(), ctx)?;
+            return Ok(SceneResult::Switch(Box::new(s)));
+        }
+
+        if self.button_mods.just_clicked() {
+            let s = SceneMods::new(self.common_data.clone(), ctx);
             return Ok(SceneResult::Switch(Box::new(s)));
         }
 
@@ -98,6 +123,8 @@ impl Scene for SceneMenuMisc {
 
         self.button_bench.render(ctx)?;
         self.button_debug.render(ctx)?;
+        self.button_editor.render(ctx)?;
+        self.button_mods.render(ctx)?;
         self.button_about.render(ctx)?;
         self.button_back.render(ctx)?;
 
@@ -107,7 +134,7 @@ impl Scene for SceneMenuMisc {
             Vec2::new(0.5, 0.5),
         )?;
 
-        self.common_data.draw_fps_counter(ctx)?;
+        self.common_data.draw_overlay(ctx)?;
         self.common_data.draw_cursor(ctx)?;
         Ok(())
     }
@@ -2,25 +2,36 @@ use crate::{
     common_data::CommonData,
     data::{options::OPTIONS, texts::get_text},
     scene::{menu::SceneMenu, menu_ctrl::SceneMenuCtrl},
-    utils::btn,
+    utils::{btn, btn_small},
 };
 use anyhow::Result;
 use cake_engine::{
+    audio::Sound,
     button::Button,
+    color::Color,
     context::Context,
     input::{Event, ScanCode},
+    label::Label,
     scene::{Scene, SceneResult},
+    slider::Slider,
     vec::Vec2,
 };
+use std::{path::Path, rc::Rc};
 
-const TOP: f32 = 240.0;
-const STEP: f32 = 54.0;
+const TOP: f32 = 220.0;
+const STEP: f32 = 52.0;
+const SLIDER_SIZE: Vec2 = Vec2::new(180.0, 24.0);
 
 pub struct SceneMenuSettings {
     common_data: CommonData,
     button_ctrl: Button,
+    volume_label: Label,
+    slider_master: Slider,
     button_sound: Button,
+    slider_sound: Slider,
     button_music: Button,
+    slider_music: Slider,
+    preview_sound: Option<Rc<Sound>>,
     button_fullscreen: Button,
     button_vsync: Option<Button>,
     button_fps_limit: Button,
@@ -36,11 +47,48 @@ impl SceneMenuSettings {
         let mut pos = Vec2::new(view.width / 2.0, TOP);
         let button_ctrl = btn!(common_data, get_text("menuctrl"), pos);
 
+        // Строки громкости: слева надпись или переключатель, справа ползунок
+        let left = view.width / 2.0 - common_data.button.width() as f32 / 2.0;
+        let right = view.width / 2.0 + common_data.button.width() as f32 / 2.0;
+        let (master_volume, sound_volume, music_volume) = {
+            let options = OPTIONS.lock().unwrap();
+            (
+                options.get_master_volume(),
+                options.get_sound_volume(),
+                options.get_music_volume(),
+            )
+        };
+
         pos.y += STEP;
-        let button_sound = btn!(common_data, SceneMenuSettings::get_sound_label(), pos);
+        let mut volume_label = Label::new(common_data.font_button.clone(), Color::WHITE);
+        volume_label.set_origin(Vec2::new(0.5, 0.6));
+        volume_label.set_position(Vec2::new(
+            left + common_data.button_small.width() as f32 / 2.0,
+            pos.y,
+        ));
+        volume_label.set_text(get_text("menuvolume"));
+        let slider_master =
+            SceneMenuSettings::volume_slider(&common_data, right, pos.y, master_volume);
 
         pos.y += STEP;
-        let button_music = btn!(common_data, SceneMenuSettings::get_music_label(), pos);
+        let mut button_sound = btn_small!(
+            common_data,
+            SceneMenuSettings::get_sound_label(),
+            Vec2::new(left, pos.y)
+        );
+        button_sound.set_origin(Vec2::new(0.0, 0.5));
+        let slider_sound =
+            SceneMenuSettings::volume_slider(&common_data, right, pos.y, sound_volume);
+
+        pos.y += STEP;
+        let mut button_music = btn_small!(
+            common_data,
+            SceneMenuSettings::get_music_label(),
+            Vec2::new(left, pos.y)
+        );
+        button_music.set_origin(Vec2::new(0.0, 0.5));
+        let slider_music =
+            SceneMenuSettings::volume_slider(&common_data, right, pos.y, music_volume);
 
         pos.y += STEP;
         let button_fullscreen = btn!(common_data, SceneMenuSettings::get_fullscreen_label(), pos);
@@ -67,8 +115,13 @@ impl SceneMenuSettings {
         SceneMenuSettings {
             common_data,
             button_ctrl,
+            volume_label,
+            slider_master,
             button_sound,
+            slider_sound,
             button_music,
+            slider_music,
+            preview_sound: None,
             button_fullscreen,
             button_vsync,
             button_fps_limit,
@@ -78,6 +131,37 @@ impl SceneMenuSettings {
         }
     }
 
+    fn volume_slider(common_data: &CommonData, right: f32, y: f32, value: f32) -> Slider {
+        let mut slider = Slider::new(Vec2::new(right, y), SLIDER_SIZE);
+        slider.set_origin(Vec2::new(1.0, 0.5));
+        slider.set_value(value);
+        slider.set_fill_color(common_data.color_norm);
+        slider.set_knob_colors(common_data.color_norm, common_data.color_over);
+        slider
+    }
+
+    // Новая громкость музыки применяется сразу, пока ползунок тащат
+    fn update_music_volume(&self, ctx: &mut dyn Context) -> Result<()> {
+        let volume = OPTIONS.lock().unwrap().get_effective_music_volume();
+        self.common_data.music.borrow_mut().set_volume(ctx, volume)
+    }
+
+    // А громкость звуков проверяем, проигрывая звук, когда ползунок отпустили
+    fn play_preview_sound(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        let (soundon, volume) = {
+            let options = OPTIONS.lock().unwrap();
+            (options.get_soundon(), options.get_effective_sound_volume())
+        };
+        if !soundon {
+            return Ok(());
+        }
+        if self.preview_sound.is_none() {
+            self.preview_sound = Some(ctx.load_sound_file(Path::new("sounds/chicken.ogg"))?);
+        }
+        ctx.play_sound(self.preview_sound.as_ref().unwrap(), volume, false)?;
+        Ok(())
+    }
+
     fn get_sound_label() -> String {
         let options = OPTIONS.lock().unwrap();
         format!(
@@ -183,9 +267,14 @@ impl Scene for SceneMenuSettings {
 
         self.common_data.process(ctx)?;
 
+        let was_dragging = self.slider_master.is_dragging() || self.slider_sound.is_dragging();
+
         self.button_ctrl.process(ctx)?;
+        self.slider_master.process(ctx)?;
         self.button_sound.process(ctx)?;
+        self.slider_sound.process(ctx)?;
         self.button_music.process(ctx)?;
+        self.slider_music.process(ctx)?;
         self.button_fullscreen.process(ctx)?;
         if let Some(b) = self.button_vsync.as_mut() {
             b.process(ctx)?;
@@ -205,6 +294,33 @@ impl Scene for SceneMenuSettings {
             return Ok(SceneResult::Switch(Box::new(s)));
         }
 
+        if self.slider_master.just_changed() {
+            OPTIONS
+                .lock()
+                .unwrap()
+                .set_master_volume(self.slider_master.value());
+            self.update_music_volume(ctx)?;
+        }
+
+        if self.slider_sound.just_changed() {
+            OPTIONS
+                .lock()
+                .unwrap()
+                .set_sound_volume(self.slider_sound.value());
+        }
+
+        if self.slider_music.just_changed() {
+            OPTIONS
+                .lock()
+                .unwrap()
+                .set_music_volume(self.slider_music.value());
+            self.update_music_volume(ctx)?;
+        }
+
+        if was_dragging && !self.slider_master.is_dragging() && !self.slider_sound.is_dragging() {
+            self.play_preview_sound(ctx)?;
+        }
+
         if self.button_sound.just_clicked() {
             OPTIONS.lock().unwrap().invert_soundon();
             self.button_sound
@@ -213,11 +329,11 @@ impl Scene for SceneMenuSettings {
 
         if self.button_music.just_clicked() {
             let musicon = OPTIONS.lock().unwrap().invert_musicon();
-            if musicon {
-                self.common_data.play_music(ctx)?; // это подвисает в macroquad
-            } else {
-                ctx.stop_music()?;
-            }
+            // Включение подвисает в macroquad
+            self.common_data
+                .music
+                .borrow_mut()
+                .set_enabled(ctx, musicon)?;
             self.button_music
                 .set_text(SceneMenuSettings::get_music_label());
         }
@@ -266,8 +382,12 @@ impl Scene for SceneMenuSettings {
         self.common_data.draw_back(ctx)?;
 
         self.button_ctrl.render(ctx)?;
+        self.volume_label.render(ctx)?;
+        self.slider_master.render(ctx)?;
         self.button_sound.render(ctx)?;
+        self.slider_sound.render(ctx)?;
         self.button_music.render(ctx)?;
+        self.slider_music.render(ctx)?;
         self.button_fullscreen.render(ctx)?;
         if let Some(b) = self.button_vsync.as_mut() {
             b.render(ctx)?;
@@ -283,7 +403,7 @@ impl Scene for SceneMenuSettings {
             Vec2::new(0.5, 0.5),
         )?;
 
-        self.common_data.draw_fps_counter(ctx)?;
+        self.common_data.draw_overlay(ctx)?;
         self.common_data.draw_cursor(ctx)?;
         Ok(())
     }
@@ -3,10 +3,13 @@ pub mod achievements;
 pub mod bench;
 pub mod bench_result;
 pub mod debug;
+pub mod editor;
 pub mod gameover;
 pub mod help;
 pub mod menu;
 pub mod menu_ctrl;
 pub mod menu_misc;
 pub mod menu_settings;
+pub mod mods;
+pub mod mutators;
 pub mod play;
@@ -0,0 +1,266 @@
+use crate::{
+    common_data::CommonData,
+    data::{mods, options::OPTIONS, texts::get_text},
+    scene::menu_misc::SceneMenuMisc,
+    utils::{btn, btn_small},
+};
+use anyhow::Result;
+use cake_engine::{
+    button::Button,
+    color::Color,
+    context::Context,
+    input::{Event, ScanCode},
+    label::Label,
+    scene::{Scene, SceneResult},
+    vec::Vec2,
+};
+
+const TOP: f32 = 240.0;
+const STEP: f32 = 54.0;
+const MAX_ROWS: usize = 6;
+// Насколько кнопка включения мода сдвинута влево, чтобы справа поместилась кнопка «выше»
+const ROW_SHIFT: f32 = 100.0;
+
+struct ModRow {
+    name: String,
+    button_toggle: Button,
+    button_up: Option<Button>,
+}
+
+/// Экран управления модами: включение, выключение и порядок загрузки.
+pub struct SceneMods {
+    common_data: CommonData,
+    // Сначала включённые моды в порядке загрузки, потом выключенные по алфавиту
+    mods: Vec<String>,
+    page: usize,
+    rows: Vec<ModRow>,
+    empty_label: Option<Label>,
+    button_back: Button,
+    button_more: Option<Button>,
+    mods_changed: bool,
+}
+
+impl SceneMods {
+    pub fn new(common_data: CommonData, ctx: &mut dyn Context) -> SceneMods {
+        let view = ctx.view().get_or_default();
+        let installed = mods::find_mods();
+
+        let empty_label = if installed.is_empty() {
+            let mut label = Label::new(common_data.font_help.clone(), Color::WHITE);
+            let dir = match mods::mods_dir() {
+                Some(d) => d.to_string_lossy().to_string(),
+                None => "-".to_string(),
+            };
+            label.set_text(format!("{}\n{}", get_text("mods_none"), dir));
+            label.set_position(Vec2::new(view.width / 2.0, TOP));
+            label.set_origin(Vec2::new(0.5, 0.0));
+            Some(label)
+        } else {
+            None
+        };
+
+        let bottom = TOP + STEP * MAX_ROWS as f32 + 8.0;
+
+        let mut pos = Vec2::new(
+            view.width / 2.0 - common_data.button.width() as f32 / 2.0,
+            bottom,
+        );
+        let mut button_back = btn_small!(common_data, get_text("menuback"), pos);
+        button_back.set_origin(Vec2::new(0.0, 0.5));
+
+        let button_more = if installed.len() > MAX_ROWS {
+            pos.x = view.width / 2.0 + common_data.button.width() as f32 / 2.0;
+            let mut b = btn_small!(common_data, get_text("mods_more"), pos);
+            b.set_origin(Vec2::new(1.0, 0.5));
+            Some(b)
+        } else {
+            None
+        };
+
+        let mut scene = SceneMods {
+            common_data,
+            mods: installed,
+            page: 0,
+            rows: Vec::new(),
+            empty_label,
+            button_back,
+            button_more,
+            mods_changed: false,
+        };
+        scene.build_rows(ctx);
+        scene
+    }
+
+    fn build_rows(&mut self, ctx: &mut dyn Context) {
+        let view = ctx.view().get_or_default();
+
+        {
+            let options = OPTIONS.lock().unwrap();
+            let mut sorted: Vec<String> = options
+                .get_mods()
+                .iter()
+                .filter(|m| self.mods.contains(m))
+                .cloned()
+                .collect();
+            let mut disabled: Vec<String> = self
+                .mods
+                .iter()
+                .filter(|m| !options.is_mod_enabled(m))
+                .cloned()
+                .collect();
+            disabled.sort();
+            sorted.extend(disabled);
+            self.mods = sorted;
+        }
+
+        let pages = self.mods.len().div_ceil(MAX_ROWS);
+        if self.page >= pages {
+            self.page = 0;
+        }
+
+        self.rows.clear();
+        let mut pos = Vec2::new(view.width / 2.0 - ROW_SHIFT, TOP);
+        for (idx, name) in self
+            .mods
+            .iter()
+            .enumerate()
+            .skip(self.page * MAX_ROWS)
+            .take(MAX_ROWS)
+        {
+            let (label, can_move_up) = {
+                let options = OPTIONS.lock().unwrap();
+                let enabled = options.is_mod_enabled(name);
+                let label = format!(
+                    "{} : {}",
+                    name,
+                    get_text(if enabled { "text_on" } else { "text_off" })
+                );
+                // Первый мод в списке и так загружается первым
+                (label, enabled && idx > 0)
+            };
+
+            let button_toggle = btn!(self.common_data, label, pos);
+            let button_up = if can_move_up {
+                let up_pos = Vec2::new(
+                    pos.x + self.common_data.button.width() as f32 / 2.0 + 8.0,
+                    pos.y,
+                );
+                let mut b = btn_small!(self.common_data, get_text("mods_up"), up_pos);
+                b.set_origin(Vec2::new(0.0, 0.5));
+                Some(b)
+            } else {
+                None
+            };
+
+            self.rows.push(ModRow {
+                name: name.clone(),
+                button_toggle,
+                button_up,
+            });
+            pos.y += STEP;
+        }
+    }
+}
+
+impl Scene for SceneMods {
+    fn process(
+        &mut self,
+        ctx: &mut dyn Context,
+        _dt: f32,
+        _events: &[Event],
+    ) -> Result<SceneResult> {
+        if ctx.input().is_quit_requested() {
+            return Ok(SceneResult::Quit);
+        }
+
+        self.common_data.process(ctx)?;
+
+        for row in self.rows.iter_mut() {
+            row.button_toggle.process(ctx)?;
+            if let Some(b) = row.button_up.as_mut() {
+                b.process(ctx)?;
+            }
+        }
+        self.button_back.process(ctx)?;
+        if let Some(b) = self.button_more.as_mut() {
+            b.process(ctx)?;
+        }
+
+        if ctx.input().is_key_just_pressed(ScanCode::Escape) || self.button_back.just_clicked() {
+            if self.mods_changed {
+                // Моды могут заменить что угодно, так что перезагружаем вообще все ресурсы
+                {
+                    let options = OPTIONS.lock().unwrap();
+                    mods::mount_mods(&options);
+                    crate::data::reload_lang(&options)?;
+                }
+                ctx.reload_lang_resources()?;
+                self.common_data.reload(ctx)?;
+            }
+            let s = SceneMenuMisc::new(self.common_data.clone(), ctx);
+            return Ok(SceneResult::Switch(Box::new(s)));
+        }
+
+        if let Some(b) = self.button_more.as_ref() {
+            if b.just_clicked() {
+                self.page += 1;
+                self.build_rows(ctx);
+                return Ok(SceneResult::Normal);
+            }
+        }
+
+        let mut changed = false;
+        for row in self.rows.iter() {
+            if row.button_toggle.just_clicked() {
+                OPTIONS.lock().unwrap().invert_mod(&row.name);
+                changed = true;
+            }
+            if row.button_up.as_ref().map(|b| b.just_clicked()) == Some(true) {
+                OPTIONS.lock().unwrap().move_mod_up(&row.name);
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.mods_changed = true;
+            self.build_rows(ctx);
+            if let Err(e) = OPTIONS.lock().unwrap().save() {
+                cake_engine::log::error!("Failed to save options: {:?}", e);
+            }
+        }
+
+        Ok(SceneResult::Normal)
+    }
+
+    fn render(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        let view = ctx.view().get_or_default();
+
+        self.common_data.draw_back(ctx)?;
+
+        if let Some(label) = self.empty_label.as_mut() {
+            label.render(ctx)?;
+        }
+
+        for row in self.rows.iter_mut() {
+            row.button_toggle.render(ctx)?;
+            if let Some(b) = row.button_up.as_mut() {
+                b.render(ctx)?;
+            }
+        }
+
+        self.button_back.render(ctx)?;
+        if let Some(b) = self.button_more.as_mut() {
+            b.render(ctx)?;
+        }
+
+        ctx.draw_texture(
+            &self.common_data.logo,
+            Vec2::new(view.width / 2.0, 100.0),
+            Vec2::new(0.5, 0.5),
+        )?;
+
+        self.common_data.draw_overlay(ctx)?;
+        self.common_data.draw_cursor(ctx)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    common_data::CommonData,
+    data::{options::OPTIONS, texts::get_text},
+    mutator::Mutator,
+    scene::{menu::SceneMenu, play::ScenePlay},
+    utils::{btn, btn_small},
+};
+use anyhow::Result;
+use cake_engine::{
+    button::Button,
+    color::Color,
+    context::Context,
+    input::{Event, ScanCode},
+    scene::{Scene, SceneResult},
+    transition::Transition,
+    vec::Vec2,
+};
+
+const TOP: f32 = 240.0;
+const STEP: f32 = 54.0;
+
+const START_TRANSITION: Transition = Transition::Fade {
+    color: Color::BLACK,
+    duration: 0.6,
+};
+
+/// Экран перед началом игры, на котором выбираются модификаторы.
+pub struct SceneMutators {
+    common_data: CommonData,
+    mutator_buttons: Vec<(Mutator, Button)>,
+    button_back: Button,
+    button_start: Button,
+}
+
+impl SceneMutators {
+    pub fn new(common_data: CommonData, ctx: &mut dyn Context) -> SceneMutators {
+        let view = ctx.view().get_or_default();
+
+        let mut pos = Vec2::new(view.width / 2.0, TOP);
+        let mut mutator_buttons = Vec::new();
+        for mutator in enum_iterator::all::<Mutator>() {
+            let b = btn!(common_data, SceneMutators::get_mutator_label(mutator), pos);
+            mutator_buttons.push((mutator, b));
+            pos.y += STEP;
+        }
+
+        pos.y += 8.0;
+
+        pos.x = view.width / 2.0 - common_data.button.width() as f32 / 2.0;
+        let mut button_back = btn_small!(common_data, get_text("menuback"), pos);
+        button_back.set_origin(Vec2::new(0.0, 0.5));
+
+        pos.x = view.width / 2.0 + common_data.button.width() as f32 / 2.0;
+        let mut button_start = btn_small!(common_data, get_text("menustart"), pos);
+        button_start.set_origin(Vec2::new(1.0, 0.5));
+
+        SceneMutators {
+            common_data,
+            mutator_buttons,
+            button_back,
+            button_start,
+        }
+    }
+
+    fn get_mutator_label(mutator: Mutator) -> String {
+        let options = OPTIONS.lock().unwrap();
+        format!(
+            "{} : {}",
+            get_text(&format!("mutator_{}", mutator.code())),
+            get_text(if options.is_mutator_enabled(mutator) {
+                "text_on"
+            } else {
+                "text_off"
+            }),
+        )
+    }
+}
+
+impl Scene for SceneMutators {
+    fn process(
+        &mut self,
+        ctx: &mut dyn Context,
+        _dt: f32,
+        _events: &[Event],
+    ) -> Result<SceneResult> {
+        if ctx.input().is_quit_requested() {
+            return Ok(SceneResult::Quit);
+        }
+
+        self.common_data.process(ctx)?;
+
+        for (_, button) in self.mutator_buttons.iter_mut() {
+            button.process(ctx)?;
+        }
+        self.button_back.process(ctx)?;
+        self.button_start.process(ctx)?;
+
+        if ctx.input().is_key_just_pressed(ScanCode::Escape) || self.button_back.just_clicked() {
+            let s = SceneMenu::new(self.common_data.clone(), ctx)?;
+            return Ok(SceneResult::Switch(Box::new(s)));
+        }
+
+        if self.button_start.just_clicked() {
+            let s = ScenePlay::new(self.common_data.clone(), ctx)?;
+            return Ok(SceneResult::SwitchWith(Box::new(s), START_TRANSITION));
+        }
+
+        let mut options_changed = false;
+
+        for (mutator, button) in self.mutator_buttons.iter_mut() {
+            if button.just_clicked() {
+                OPTIONS.lock().unwrap().invert_mutator(*mutator);
+                button.set_text(SceneMutators::get_mutator_label(*mutator));
+                options_changed = true;
+            }
+        }
+
+        if options_changed {
+            if let Err(e) = OPTIONS.lock().unwrap().save() {
+                cake_engine::log::error!("Failed to save options: {:?}", e);
+            }
+        }
+
+        Ok(SceneResult::Normal)
+    }
+
+    fn render(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        let view = ctx.view().get_or_default();
+
+        self.common_data.draw_back(ctx)?;
+
+        for (_, button) in self.mutator_buttons.iter_mut() {
+            button.render(ctx)?;
+        }
+
+        self.button_back.render(ctx)?;
+        self.button_start.render(ctx)?;
+
+        ctx.draw_texture(
+            &self.common_data.logo,
+            Vec2::new(view.width / 2.0, 100.0),
+            Vec2::new(0.5, 0.5),
+        )?;
+
+        self.common_data.draw_overlay(ctx)?;
+        self.common_data.draw_cursor(ctx)?;
+        Ok(())
+    }
+}
@@ -1,27 +1,39 @@
 use crate::{
     action::Action,
+    adaptive::AdaptiveDifficulty,
     common_data::CommonData,
-    data::options::OPTIONS,
+    data::{
+        options::{Difficulty, OPTIONS},
+        results::{self, SessionResult},
+    },
+    debug_overlay::DebugOverlay,
     game,
-    game::{Direction, Game, GameState},
+    game::{Direction, Game, GameState, PickupKind},
     gameaction::{GAChicken, GAJump, GALaser, GAShield, GameAction},
-    scene::{gameover::SceneGameOver, menu::SceneMenu},
+    level::Level,
+    scene::{editor::SceneEditor, gameover::SceneGameOver, menu::SceneMenu},
     touchui::TouchUi,
     utils::{spr, tex},
 };
 use anyhow::Result;
 use cake_engine::{
-    audio::Sound,
+    audio::{Sound, Voice},
     color::Color,
-    context::{Context, DrawTextureParams},
+    context::{Context, DrawTextureParams, PlaySoundParams},
     input::{Actions, Event, ScanCode},
     label::Label,
+    particles::{ParticleEmitter, ParticleParams},
     rect::Rect,
     scene::{Scene, SceneResult},
     sprite::Sprite,
-    texture::Texture,
+    texture::{Texture, TextureOptions},
+    transform::Transform,
+    transition::Transition,
+    tween::Easing,
     vec::Vec2,
 };
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use std::{path::Path, rc::Rc};
 
 const MANA_COLOR: Color = Color::new(35, 20, 250, 255);
@@ -31,6 +43,24 @@ const INDICATOR_H: f32 = 8.0;
 const LASER_Y: f32 = 80.0;
 
 const CHICKEN_Y: f32 = 30.0;
+const PICKUP_Y: f32 = 20.0;
+// За сколько секунд до исчезновения бонус начинает мигать
+const PICKUP_BLINK_TIME: f32 = 2.0;
+// Больше стольких бесплатных щитов на индикаторе не рисуем
+const MAX_SHIELD_ICONS: u32 = 5;
+// Тряска экрана, когда лазер уничтожает тортик: длительность в секундах и размах в пикселях
+const SHAKE_TIME: f32 = 0.25;
+const SHAKE_AMPLITUDE: f32 = 4.0;
+
+// Насколько сильно звуки у края экрана смещаются в сторону (1.0 — целиком в одну колонку)
+const SOUND_PAN: f32 = 0.8;
+// Разброс высоты тона кудахтанья, чтобы курицы не звучали одинаково
+const CHICKEN_PITCH: (f32, f32) = (0.9, 1.15);
+
+// Ниже этого здоровья Селестии включается напряжённая музыка
+const DANGER_HP_PERCENT: f32 = 25.0;
+
+const GAMEOVER_TRANSITION: Transition = Transition::Crossfade { duration: 0.5 };
 
 const COLORSET: [Color; 4] = [
     Color::new(255, 0, 0, 255),
@@ -44,8 +74,57 @@ const ACT_LASER: usize = 1;
 const ACT_CHICKEN: usize = 2;
 const ACT_SHIELD: usize = 3;
 
+// Клавиша, включающая отладочный слой
+const DEBUG_OVERLAY_KEY: ScanCode = ScanCode::F3;
+
 const GRAVITY: f32 = 800.0;
 
+// Сколько крошек остаётся от кексика и сколько дыма от появившейся курицы
+const CRUMBS_COUNT: usize = 24;
+const PUFF_COUNT: usize = 16;
+
+const CRUMBS_PARAMS: ParticleParams = ParticleParams {
+    rate: 0.0,
+    max_particles: 256,
+    lifetime: (0.4, 0.8),
+    offset: (Vec2::new(-20.0, -15.0), Vec2::new(20.0, 15.0)),
+    velocity: (Vec2::new(-120.0, -220.0), Vec2::new(120.0, -60.0)),
+    gravity: (Vec2::new(0.0, 900.0), Vec2::new(0.0, 900.0)),
+    rotation: (0.0, 360.0),
+    angular_velocity: (-360.0, 360.0),
+    color: (Color::new(250, 210, 160, 255), Color::new(200, 120, 80, 0)),
+    scale: (1.0, 0.6),
+    easing: Easing::QuadIn,
+};
+
+const SPARKS_PARAMS: ParticleParams = ParticleParams {
+    rate: 60.0,
+    max_particles: 64,
+    lifetime: (0.15, 0.35),
+    offset: (Vec2::new(-4.0, -4.0), Vec2::new(4.0, 4.0)),
+    velocity: (Vec2::new(-80.0, -140.0), Vec2::new(80.0, 40.0)),
+    gravity: (Vec2::new(0.0, 600.0), Vec2::new(0.0, 600.0)),
+    rotation: (0.0, 0.0),
+    angular_velocity: (0.0, 0.0),
+    color: (Color::new(255, 255, 220, 255), Color::new(255, 120, 0, 0)),
+    scale: (0.8, 0.2),
+    easing: Easing::Linear,
+};
+
+const PUFF_PARAMS: ParticleParams = ParticleParams {
+    rate: 0.0,
+    max_particles: 128,
+    lifetime: (0.4, 0.7),
+    offset: (Vec2::new(-15.0, -10.0), Vec2::new(15.0, 10.0)),
+    velocity: (Vec2::new(-60.0, -60.0), Vec2::new(60.0, 10.0)),
+    gravity: (Vec2::new(0.0, -40.0), Vec2::new(0.0, -40.0)),
+    rotation: (0.0, 0.0),
+    angular_velocity: (0.0, 0.0),
+    color: (Color::new(255, 255, 255, 200), Color::new(255, 255, 255, 0)),
+    scale: (1.5, 3.0),
+    easing: Easing::QuadOut,
+};
+
 struct FallingCelestia {
     pos: Vec2,
     vel: Vec2,
@@ -55,10 +134,10 @@ struct FallingCelestia {
 
 struct Sounds {
     snd_galop: Rc<Sound>,
-    snd_galop2: Rc<Sound>,
     snd_laser: Rc<Sound>,
     snd_teleport: Rc<Sound>,
     snd_chicken: Rc<Sound>,
+    volume: f32,
 }
 
 pub struct ScenePlay {
@@ -66,7 +145,11 @@ pub struct ScenePlay {
     touchui: Option<TouchUi>,
     started: bool,
     block: Rc<Texture>,
+    // Платформы не меняются, поэтому рисуются в текстуру один раз (вместе с текстурой
+    // запоминается, сколько её пикселей приходится на единицу координат игры)
+    platforms: Option<(Rc<Texture>, Vec2)>,
     chicken: Rc<Texture>,
+    pickup_mana: Rc<Texture>,
     cakes: [Rc<Texture>; 3],
     celestia_walk: Sprite,
     celestia_eat: Sprite,
@@ -74,12 +157,19 @@ pub struct ScenePlay {
     luna_wait: Sprite,
     laser: Sprite,
     shield: Sprite,
+    crumbs: ParticleEmitter,
+    sparks: ParticleEmitter,
+    puffs: ParticleEmitter,
     deny: Rc<Texture>,
     sounds: Option<Sounds>,
     game: Game,
     islunawalk: bool,
     iscelestiawalk: bool,
+    luna_galop: Option<Voice>,
+    celestia_galop: Option<Voice>,
     islaseron: bool,
+    laser_voice: Option<Voice>,
+    sound_rng: ChaCha8Rng,
     oldcelestiazoneidx: usize,
     gameactions: [Box<dyn GameAction>; 4],
     action_textures: [Rc<Texture>; 4],
@@ -90,13 +180,67 @@ pub struct ScenePlay {
     input_actions: Actions<Action>,
     apply_after_select: bool,
     falling_celestia: Option<FallingCelestia>,
+    debug_overlay: DebugOverlay,
+    level: Option<Level>,
+    difficulty: Difficulty,
+    adaptive: Option<AdaptiveDifficulty>,
+    elapsed: f32,
+    // Сколько ещё трясти экран
+    shake: f32,
 }
 
 impl ScenePlay {
     pub fn new(common_data: CommonData, ctx: &mut dyn Context) -> Result<ScenePlay> {
+        let game = Game::new(OPTIONS.lock().unwrap().get_difficulty());
+        ScenePlay::with_game(common_data, ctx, game, None)
+    }
+
+    /// Запускает обычную игру с заданным зерном случайных чисел (например, для тестов).
+    pub fn with_seed(
+        common_data: CommonData,
+        ctx: &mut dyn Context,
+        seed: u64,
+    ) -> Result<ScenePlay> {
+        let game = Game::with_seed(OPTIONS.lock().unwrap().get_difficulty(), seed);
+        ScenePlay::with_game(common_data, ctx, game, None)
+    }
+
+    /// Запускает игру на поле из редактора уровней. После выхода из игры возвращает в редактор.
+    pub fn with_level(
+        common_data: CommonData,
+        ctx: &mut dyn Context,
+        level: Level,
+    ) -> Result<ScenePlay> {
+        let game = Game::with_level(&level);
+        ScenePlay::with_game(common_data, ctx, game, Some(level))
+    }
+
+    /// Запускает уже подготовленную игру. Если указан уровень, то после выхода из игры
+    /// возвращает в редактор.
+    pub fn with_game(
+        common_data: CommonData,
+        ctx: &mut dyn Context,
+        mut game: Game,
+        level: Option<Level>,
+    ) -> Result<ScenePlay> {
         let options = OPTIONS.lock().unwrap();
 
-        let game = Game::new(options.get_difficulty());
+        // Модификаторы выбираются перед обычной игрой, на уровни из редактора не влияют
+        if level.is_none() {
+            game.apply_mutators(options.get_mutators());
+        }
+
+        let difficulty = options.get_difficulty();
+        let adaptive = if options.get_adaptive_difficulty() && level.is_none() {
+            let recent = results::load_results().unwrap_or_else(|e| {
+                cake_engine::log::error!("Failed to load results: {:?}", e);
+                Vec::new()
+            });
+            Some(AdaptiveDifficulty::new(&mut game, difficulty, &recent))
+        } else {
+            None
+        };
+
         let oldcelestiazoneidx = game.get_celestia_zone_idx();
 
         let mut mana_label = Label::new(common_data.font_main.clone(), MANA_COLOR);
@@ -108,10 +252,10 @@ impl ScenePlay {
         let sounds = if options.get_soundon() {
             Some(Sounds {
                 snd_galop: ctx.load_sound_file(Path::new("sounds/galop.ogg"))?,
-                snd_galop2: ctx.load_sound_file(Path::new("sounds/galop.ogg"))?,
                 snd_laser: ctx.load_sound_file(Path::new("sounds/laser.ogg"))?,
                 snd_teleport: ctx.load_sound_file(Path::new("sounds/teleport.ogg"))?,
                 snd_chicken: ctx.load_sound_file(Path::new("sounds/chicken.ogg"))?,
+                volume: options.get_effective_sound_volume(),
             })
         } else {
             None
@@ -163,12 +307,27 @@ impl ScenePlay {
             None
         };
 
+        let debug_overlay = DebugOverlay::new(common_data.font_small.clone());
+
+        let particle = tex!(ctx, "images/particle.png");
+        let mut crumbs = ParticleEmitter::new(particle.clone(), CRUMBS_PARAMS);
+        let mut sparks = ParticleEmitter::new(particle.clone(), SPARKS_PARAMS);
+        let mut puffs = ParticleEmitter::new(particle, PUFF_PARAMS);
+        // Зерно берём из игры, чтобы частицы повторялись вместе с ней
+        crumbs.set_seed(game.get_seed());
+        sparks.set_seed(game.get_seed().wrapping_add(1));
+        puffs.set_seed(game.get_seed().wrapping_add(2));
+        sparks.set_emitting(false);
+        let sound_rng = ChaCha8Rng::seed_from_u64(game.get_seed().wrapping_add(3));
+
         let mut s = ScenePlay {
             common_data,
             touchui,
             started: false,
             block: tex!(ctx, "images/block.png"),
+            platforms: None,
             chicken: tex!(ctx, "images/chicken.png"),
+            pickup_mana: tex!(ctx, "images/pickup_mana.png"),
             cakes: [
                 tex!(ctx, "images/cake1.png"),
                 tex!(ctx, "images/cake2.png"),
@@ -180,12 +339,19 @@ impl ScenePlay {
             luna_wait: spr!(ctx, "images/luna_wait.png", 6.0, grid: (6, 1)),
             laser: spr!(ctx, "images/laser.png", 16.0, grid: (8, 1)),
             shield: spr!(ctx, "images/shield.png", 14.0, frame: (80, 80)),
+            crumbs,
+            sparks,
+            puffs,
             deny,
             sounds,
             game,
             islunawalk: false,
             iscelestiawalk: false,
+            luna_galop: None,
+            celestia_galop: None,
             islaseron: false,
+            laser_voice: None,
+            sound_rng,
             oldcelestiazoneidx,
             gameactions,
             action_textures,
@@ -196,6 +362,12 @@ impl ScenePlay {
             input_actions,
             apply_after_select: options.get_apply_after_select() && !options.get_touchui(),
             falling_celestia: None,
+            debug_overlay,
+            level,
+            difficulty,
+            adaptive,
+            elapsed: 0.0,
+            shake: 0.0,
         };
 
         s.celestia_walk.set_origin(Vec2::new(0.5, 0.0));
@@ -238,6 +410,237 @@ impl ScenePlay {
         Ok(())
     }
 
+    // Все блоки платформ вместе с их позициями
+    fn platform_blocks(&self) -> Vec<Vec2> {
+        let mut blocks = Vec::new();
+        for zone in self.game.zones() {
+            let n = (zone.right - zone.left).round() as i32 / game::BLOCKW as i32;
+            for j in 0..n {
+                blocks.push(Vec2::new(zone.left + j as f32 * game::BLOCKW, zone.y));
+            }
+        }
+        blocks
+    }
+
+    fn draw_platforms(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        let blocks = self.platform_blocks();
+        if blocks.is_empty() {
+            return Ok(());
+        }
+        let block_size = self.block.size_vec();
+        let left = blocks.iter().map(|b| b.x).fold(f32::INFINITY, f32::min);
+        let top = blocks.iter().map(|b| b.y).fold(f32::INFINITY, f32::min);
+        let right = blocks.iter().map(|b| b.x).fold(f32::NEG_INFINITY, f32::max) + block_size.x;
+        let bottom = blocks.iter().map(|b| b.y).fold(f32::NEG_INFINITY, f32::max) + block_size.y;
+
+        // Текстура покрывает все платформы, даже не попавшие в экран, и её пиксели совпадают
+        // с пикселями окна, чтобы картинка не размывалась
+        let view_scale = ctx.view().get_scale();
+        let dpi = ctx.get_dpi_scale();
+        let scale = Vec2::new(view_scale.x * dpi.x, view_scale.y * dpi.y);
+
+        let valid = match self.platforms.as_ref() {
+            Some((t, s)) => *s == scale && ctx.is_texture_valid(t),
+            None => false,
+        };
+        if !valid {
+            let platforms = ctx.create_render_target(
+                ((right - left) * scale.x).ceil().max(1.0) as u32,
+                ((bottom - top) * scale.y).ceil().max(1.0) as u32,
+                TextureOptions::PIXELATED,
+            )?;
+            ctx.begin_target(&platforms)?;
+            // Даже если что-то не нарисовалось, рисование в текстуру надо завершить
+            let drawn = blocks.iter().try_for_each(|block| {
+                ctx.draw_texture_ex(
+                    &self.block,
+                    DrawTextureParams {
+                        position: Vec2::new((block.x - left) * scale.x, (block.y - top) * scale.y),
+                        scale,
+                        ..Default::default()
+                    },
+                )
+            });
+            ctx.end_target()?;
+            drawn?;
+            self.platforms = Some((platforms, scale));
+        }
+
+        if let Some((platforms, _)) = self.platforms.as_ref() {
+            ctx.draw_texture_ex(
+                platforms,
+                DrawTextureParams {
+                    position: Vec2::new(left, top),
+                    scale: Vec2::new(1.0 / scale.x, 1.0 / scale.y),
+                    ..Default::default()
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    // Игровое поле: платформы, пони, курицы, бонусы, лазер и частицы
+    fn draw_field(&mut self, ctx: &mut dyn Context, area: Rect) -> Result<()> {
+        self.draw_platforms(ctx)?;
+
+        for falling_chicken in self.game.falling_chickens() {
+            let mut src = self.chicken.rect();
+            if falling_chicken.vel.x < 0.0 {
+                src.flip_x();
+            }
+            ctx.draw_texture_ex(
+                &self.chicken,
+                DrawTextureParams {
+                    src: Some(src),
+                    origin: Vec2::new(0.5, 0.5),
+                    position: Vec2::new(falling_chicken.pos.x, falling_chicken.pos.y - CHICKEN_Y),
+                    rotation: falling_chicken.rotation,
+                    ..Default::default()
+                },
+            )?;
+        }
+
+        for chicken in self.game.chickens() {
+            let zone = self.game.zones()[chicken.zoneidx];
+            let mut src = self.chicken.rect();
+            if chicken.vx < 0.0 {
+                src.flip_x();
+            }
+            ctx.draw_texture_ex(
+                &self.chicken,
+                DrawTextureParams {
+                    src: Some(src),
+                    origin: Vec2::new(0.5, 0.5),
+                    position: Vec2::new(chicken.x, zone.y - CHICKEN_Y),
+                    ..Default::default()
+                },
+            )?;
+        }
+        self.puffs.render(ctx)?;
+
+        for pickup in self.game.pickups() {
+            if pickup.lifetime < PICKUP_BLINK_TIME && (pickup.lifetime * 8.0) as i32 % 2 == 0 {
+                continue;
+            }
+            let zone = self.game.zones()[pickup.zoneidx];
+            let pos = Vec2::new(pickup.x, zone.y - PICKUP_Y);
+            match pickup.kind {
+                PickupKind::Mana => {
+                    ctx.draw_texture(&self.pickup_mana, pos, Vec2::new(0.5, 0.5))?;
+                }
+                PickupKind::Shield => {
+                    ctx.draw_texture(&self.action_textures[ACT_SHIELD], pos, Vec2::new(0.5, 0.5))?;
+                }
+                PickupKind::LaserBoost => {
+                    ctx.draw_texture(&self.action_textures[ACT_LASER], pos, Vec2::new(0.5, 0.5))?;
+                }
+            }
+        }
+
+        if let Some(falling_celestia) = self.falling_celestia.as_ref() {
+            let celestia = if falling_celestia.eating {
+                &mut self.celestia_eat
+            } else {
+                &mut self.celestia_walk
+            };
+            let mut p = falling_celestia.pos;
+            p.y -= 128.0;
+            celestia.set_position(p);
+            celestia.set_flip_x(falling_celestia.dir == Direction::Left);
+            celestia.render(ctx)?;
+        } else {
+            let celestia = if self.game.is_celestia_eating()
+                || self.game.get_celestia_dir() == Direction::No
+            {
+                &mut self.celestia_eat
+            } else {
+                &mut self.celestia_walk
+            };
+            let mut p = self.game.get_celestia_pos();
+            p.y -= 128.0;
+            celestia.set_position(p);
+            celestia.set_flip_x(self.game.get_celestia_dir() == Direction::Left);
+            celestia.render(ctx)?;
+        }
+
+        let luna = if self.islunawalk {
+            &mut self.luna_walk
+        } else {
+            &mut self.luna_wait
+        };
+        let mut p = self.game.get_luna_pos();
+        p.y -= 126.0;
+        luna.set_position(p);
+        luna.set_flip_x(self.game.get_luna_dir() == Direction::Left);
+        luna.render(ctx)?;
+
+        let boost_left = self.game.get_laser_boost_left();
+        if boost_left > 0.0 {
+            let luna_pos = self.game.get_luna_pos();
+            self.draw_indicator(
+                ctx,
+                luna_pos.x - INDICATOR_W / 2.0,
+                luna_pos.y - 136.0,
+                INDICATOR_W,
+                INDICATOR_H,
+                boost_left / self.game.get_balance().laser_boost_time,
+                &COLORSET,
+            )?;
+        }
+
+        for cake in self.game.cakes() {
+            let zone = &self.game.zones()[cake.zoneidx];
+            let cake_pos = Vec2::new(cake.x, zone.y - game::CAKE_Y);
+            ctx.draw_texture(&self.cakes[cake.spriteidx], cake_pos, Vec2::new(0.5, 0.5))?;
+
+            if cake.shieldleft > 0.0 {
+                self.shield.set_position(cake_pos);
+                self.shield.render(ctx)?;
+            }
+
+            if cake.hp < 1.0 {
+                self.draw_indicator(
+                    ctx,
+                    cake.x - INDICATOR_W / 2.0,
+                    zone.y,
+                    INDICATOR_W,
+                    INDICATOR_H,
+                    cake.hp,
+                    &COLORSET,
+                )?;
+            }
+        }
+        self.crumbs.render(ctx)?;
+
+        let luna_pos = self.game.get_luna_pos();
+        let laser_width = self.laser.get_absolute_size().x;
+        match self.game.get_laser_dir() {
+            Direction::Right => {
+                let mut start = luna_pos.x + 30.0;
+                while start < area.x + area.width {
+                    self.laser
+                        .set_position(Vec2::new(start, luna_pos.y - LASER_Y));
+                    self.laser.render(ctx)?;
+                    start += laser_width;
+                }
+            }
+            Direction::Left => {
+                let mut start = luna_pos.x - 30.0 - laser_width;
+                while start > area.x - laser_width {
+                    self.laser
+                        .set_position(Vec2::new(start, luna_pos.y - LASER_Y));
+                    self.laser.render(ctx)?;
+                    start -= laser_width;
+                }
+            }
+            Direction::No => {}
+        }
+        self.sparks.render(ctx)?;
+
+        self.debug_overlay.render(ctx, &self.game)?;
+        Ok(())
+    }
+
     fn apply_action(&mut self, ctx: &mut dyn Context, pressed: bool) -> Result<()> {
         let action = &mut self.gameactions[self.current_action_id];
         if pressed {
@@ -289,12 +692,63 @@ impl ScenePlay {
         Ok(())
     }
 
+    fn save_result(&self, win: bool, msg: &str) {
+        // Проверки уровней из редактора в статистику не попадают
+        if self.level.is_some() {
+            return;
+        }
+
+        let result = SessionResult {
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            difficulty: self.difficulty,
+            win,
+            message: msg.to_string(),
+            seed: self.game.get_seed(),
+            duration: self.elapsed,
+            celestia_hp_percent: self.game.get_celestia_hp_percent(),
+            adaptive: self.adaptive.as_ref().map(|a| a.report().clone()),
+            mutators: self.game.mutators().to_vec(),
+        };
+
+        if let Err(e) = results::save_result(result) {
+            cake_engine::log::error!("Failed to save result: {:?}", e);
+        }
+    }
+
     pub fn handle_applied_action(&mut self, ctx: &mut dyn Context) -> Result<()> {
         if let Some(sounds) = self.sounds.as_ref() {
             if self.current_action_id == ACT_TELEPORT {
-                ctx.play_sound(&sounds.snd_teleport, 1.0, false)?;
+                let pan = sound_pan(ctx, self.game.get_celestia_pos().x);
+                ctx.play_sound_ex(
+                    &sounds.snd_teleport,
+                    PlaySoundParams {
+                        volume: sounds.volume,
+                        pan,
+                        ..Default::default()
+                    },
+                )?;
             } else if self.current_action_id == ACT_CHICKEN {
-                ctx.play_sound(&sounds.snd_chicken, 1.0, false)?;
+                let pitch = self.sound_rng.gen_range(CHICKEN_PITCH.0..CHICKEN_PITCH.1);
+                ctx.play_sound_ex(
+                    &sounds.snd_chicken,
+                    PlaySoundParams {
+                        volume: sounds.volume,
+                        pitch,
+                        ..Default::default()
+                    },
+                )?;
+            }
+        }
+
+        if self.current_action_id == ACT_CHICKEN {
+            if let Some(chicken) = self.game.chickens().last() {
+                let zone = self.game.zones()[chicken.zoneidx];
+                self.puffs
+                    .set_position(Vec2::new(chicken.x, zone.y - CHICKEN_Y));
+                self.puffs.burst(PUFF_COUNT);
             }
         }
         Ok(())
@@ -389,29 +843,54 @@ impl ScenePlay {
             newlunawalk = self.game.send_luna_right(dt);
         }
 
+        let luna_pan = sound_pan(ctx, self.game.get_luna_pos().x);
         if let Some(sounds) = self.sounds.as_ref() {
             if newlunawalk && !self.islunawalk {
-                ctx.play_sound(&sounds.snd_galop, 1.0, true)?;
+                self.luna_galop = Some(ctx.play_sound_ex(
+                    &sounds.snd_galop,
+                    PlaySoundParams {
+                        volume: sounds.volume,
+                        looping: true,
+                        pan: luna_pan,
+                        ..Default::default()
+                    },
+                )?);
             } else if !newlunawalk && self.islunawalk {
-                ctx.stop_sound(&sounds.snd_galop)?;
+                if let Some(voice) = self.luna_galop.take() {
+                    ctx.stop_voice(voice)?;
+                }
             }
         }
+        if let Some(voice) = self.luna_galop {
+            ctx.set_voice_pan(voice, luna_pan)?;
+        }
         self.islunawalk = newlunawalk;
 
         let newcelestiawalk =
             !self.game.is_celestia_eating() && self.game.get_celestia_dir() != Direction::No;
         if let Some(sounds) = self.sounds.as_ref() {
             if newcelestiawalk && !self.iscelestiawalk {
-                ctx.play_sound(&sounds.snd_galop2, 1.0, true)?;
+                self.celestia_galop =
+                    Some(ctx.play_sound(&sounds.snd_galop, sounds.volume, true)?);
             } else if !newcelestiawalk && self.iscelestiawalk {
-                ctx.stop_sound(&sounds.snd_galop2)?;
+                if let Some(voice) = self.celestia_galop.take() {
+                    ctx.stop_voice(voice)?;
+                }
             }
         }
         self.iscelestiawalk = newcelestiawalk;
 
         if self.oldcelestiazoneidx != self.game.get_celestia_zone_idx() {
             if let Some(sounds) = self.sounds.as_ref() {
-                ctx.play_sound(&sounds.snd_teleport, 1.0, false)?;
+                let pan = sound_pan(ctx, self.game.get_celestia_pos().x);
+                ctx.play_sound_ex(
+                    &sounds.snd_teleport,
+                    PlaySoundParams {
+                        volume: sounds.volume,
+                        pan,
+                        ..Default::default()
+                    },
+                )?;
             }
             self.oldcelestiazoneidx = self.game.get_celestia_zone_idx();
         }
@@ -419,18 +898,65 @@ impl ScenePlay {
         let newlaseron = self.game.get_laser_dir() != Direction::No;
         if let Some(sounds) = self.sounds.as_ref() {
             if newlaseron && !self.islaseron {
-                ctx.play_sound(&sounds.snd_laser, 1.0, true)?;
+                self.laser_voice = Some(ctx.play_sound_ex(
+                    &sounds.snd_laser,
+                    PlaySoundParams {
+                        volume: sounds.volume,
+                        looping: true,
+                        pan: luna_pan,
+                        ..Default::default()
+                    },
+                )?);
             } else if !newlaseron && self.islaseron {
-                ctx.stop_sound(&sounds.snd_laser)?;
+                if let Some(voice) = self.laser_voice.take() {
+                    ctx.stop_voice(voice)?;
+                }
             }
         }
+        if let Some(voice) = self.laser_voice {
+            ctx.set_voice_pan(voice, luna_pan)?;
+        }
         self.islaseron = newlaseron;
 
         match self.game.get_state() {
             GameState::Normal => {
+                // Запоминаем кексики под лазером, чтобы потом осыпать крошками исчезнувшие
+                let lasered: Vec<(usize, f32)> = match self.game.get_laser_range() {
+                    Some((zoneidx, left, right)) => self
+                        .game
+                        .cakes()
+                        .iter()
+                        .filter(|c| c.zoneidx == zoneidx && c.x >= left && c.x <= right)
+                        .map(|c| (c.zoneidx, c.x))
+                        .collect(),
+                    None => Vec::new(),
+                };
+
                 self.game.update(dt);
-                if let Err(e) = self.common_data.achievements.update(&self.game) {
-                    cake_engine::log::error!("Failed to update achievements: {:?}", e);
+                self.elapsed += dt;
+
+                for (zoneidx, x) in lasered {
+                    let cakes = self.game.cakes();
+                    if !cakes.iter().any(|c| c.zoneidx == zoneidx && c.x == x) {
+                        let zone = self.game.zones()[zoneidx];
+                        self.crumbs
+                            .set_position(Vec2::new(x, zone.y - game::CAKE_Y));
+                        self.crumbs.burst(CRUMBS_COUNT);
+                        self.shake = SHAKE_TIME;
+                    }
+                }
+                if let Some(adaptive) = self.adaptive.as_mut() {
+                    adaptive.update(&mut self.game, dt);
+                }
+                // На самодельных уровнях, с модификаторами и с адаптивной сложностью
+                // достижения не засчитываем
+                if self.level.is_none()
+                    && self.game.mutators().is_empty()
+                    && self.adaptive.is_none()
+                {
+                    if let Err(e) = self.common_data.achievements.update(&self.game) {
+                        cake_engine::log::error!("Failed to update achievements: {:?}", e);
+                    }
                 }
             }
             GameState::Win(msg) => {
@@ -442,7 +968,7 @@ impl ScenePlay {
                     falling_celestia.pos.y += falling_celestia.vel.y * dt;
                     falling_celestia.vel.y += GRAVITY * dt;
 
-                    let bottom = self.game.zones().last().unwrap().y + game::BLOCKH + game::PONYW;
+                    let bottom = self.game.get_bottom() + game::PONYW;
                     if falling_celestia.pos.y > bottom {
                         return Ok(GameState::Fail(msg.clone()));
                     }
@@ -475,25 +1001,46 @@ impl Scene for ScenePlay {
         }
 
         self.common_data.process(ctx)?;
+        // Отдельной тревожной музыки в игре нет, но её может добавить мод
+        let has_danger = self.common_data.music.borrow().playlist("danger").is_some();
+        if has_danger && self.game.get_celestia_hp_percent() < DANGER_HP_PERCENT {
+            self.common_data.play_music(ctx, "danger")?;
+        } else {
+            self.common_data.play_music(ctx, "game")?;
+        }
 
         if ctx.input().is_key_just_pressed(ScanCode::Escape)
             || self.touchui.is_some() && self.common_data.button_close.just_clicked()
         {
+            if let Some(level) = self.level.take() {
+                let s = SceneEditor::with_level(self.common_data.clone(), ctx, level)?;
+                return Ok(SceneResult::Switch(Box::new(s)));
+            }
             let menu_scene = SceneMenu::new(self.common_data.clone(), ctx)?;
             return Ok(SceneResult::Switch(Box::new(menu_scene)));
         }
 
+        if ctx.input().is_key_just_pressed(DEBUG_OVERLAY_KEY) {
+            self.debug_overlay.toggle();
+        }
+
         let prev_action_id = self.current_action_id;
 
         match self.process_game(ctx, dt, events)? {
             GameState::Normal => {}
             GameState::Win(msg) => {
-                let s = SceneGameOver::new(self.common_data.clone(), ctx, true, msg.clone());
-                return Ok(SceneResult::Switch(Box::new(s)));
+                self.save_result(true, &msg);
+                let mut s = SceneGameOver::new(self.common_data.clone(), ctx, true, msg.clone());
+                s.set_level(self.level.take());
+                s.set_mutators(self.game.mutators(), self.adaptive.is_some());
+                return Ok(SceneResult::SwitchWith(Box::new(s), GAMEOVER_TRANSITION));
             }
             GameState::Fail(msg) => {
-                let s = SceneGameOver::new(self.common_data.clone(), ctx, false, msg.clone());
-                return Ok(SceneResult::Switch(Box::new(s)));
+                self.save_result(false, &msg);
+                let mut s = SceneGameOver::new(self.common_data.clone(), ctx, false, msg.clone());
+                s.set_level(self.level.take());
+                s.set_mutators(self.game.mutators(), self.adaptive.is_some());
+                return Ok(SceneResult::SwitchWith(Box::new(s), GAMEOVER_TRANSITION));
             }
         }
 
@@ -504,10 +1051,29 @@ impl Scene for ScenePlay {
         self.laser.process(dt);
         self.shield.process(dt);
 
+        let luna_pos = self.game.get_luna_pos();
+        let laser_y = luna_pos.y - LASER_Y + self.laser.get_absolute_size().y / 2.0;
+        match self.game.get_laser_dir() {
+            Direction::Right => self
+                .sparks
+                .set_position(Vec2::new(luna_pos.x + 30.0, laser_y)),
+            Direction::Left => self
+                .sparks
+                .set_position(Vec2::new(luna_pos.x - 30.0, laser_y)),
+            Direction::No => {}
+        }
+        self.sparks
+            .set_emitting(self.game.get_laser_dir() != Direction::No);
+        self.crumbs.process(dt);
+        self.sparks.process(dt);
+        self.puffs.process(dt);
+        self.shake = (self.shake - dt).max(0.0);
+
         self.mana_label
             .set_text(self.game.get_mana().floor() as i32);
         self.hp_label
             .set_text(self.game.get_celestia_hp_percent().floor() as i32);
+        self.debug_overlay.update(&self.game);
 
         if self.current_action_id != prev_action_id {
             if let Some(touchui) = self.touchui.as_mut() {
@@ -524,134 +1090,17 @@ impl Scene for ScenePlay {
 
         self.common_data.draw_back(ctx)?;
 
-        for zone in self.game.zones() {
-            let n = (zone.right - zone.left).round() as i32 / game::BLOCKW as i32;
-            for j in 0..n {
-                ctx.draw_texture(
-                    &self.block,
-                    Vec2::new(zone.left + j as f32 * game::BLOCKW, zone.y),
-                    Vec2::new(0.0, 0.0),
-                )?;
-            }
-        }
-
-        for falling_chicken in self.game.falling_chickens() {
-            let mut src = self.chicken.rect();
-            if falling_chicken.vel.x < 0.0 {
-                src.flip_x();
-            }
-            ctx.draw_texture_ex(
-                &self.chicken,
-                DrawTextureParams {
-                    src: Some(src),
-                    origin: Vec2::new(0.5, 0.5),
-                    position: Vec2::new(falling_chicken.pos.x, falling_chicken.pos.y - CHICKEN_Y),
-                    rotation: falling_chicken.rotation,
-                    ..Default::default()
-                },
-            )?;
-        }
+        // Трясётся только игровое поле, индикаторы остаются на месте
+        let shake_k = self.shake / SHAKE_TIME * SHAKE_AMPLITUDE;
+        ctx.push_transform(Transform::translation(Vec2::new(
+            (self.shake * 90.0).sin() * shake_k,
+            (self.shake * 70.0).cos() * shake_k,
+        )));
 
-        for chicken in self.game.chickens() {
-            let zone = self.game.zones()[chicken.zoneidx];
-            let mut src = self.chicken.rect();
-            if chicken.vx < 0.0 {
-                src.flip_x();
-            }
-            ctx.draw_texture_ex(
-                &self.chicken,
-                DrawTextureParams {
-                    src: Some(src),
-                    origin: Vec2::new(0.5, 0.5),
-                    position: Vec2::new(chicken.x, zone.y - CHICKEN_Y),
-                    ..Default::default()
-                },
-            )?;
-        }
-
-        if let Some(falling_celestia) = self.falling_celestia.as_ref() {
-            let celestia = if falling_celestia.eating {
-                &mut self.celestia_eat
-            } else {
-                &mut self.celestia_walk
-            };
-            let mut p = falling_celestia.pos;
-            p.y -= 128.0;
-            celestia.set_position(p);
-            celestia.set_flip_x(falling_celestia.dir == Direction::Left);
-            celestia.render(ctx)?;
-        } else {
-            let celestia = if self.game.is_celestia_eating()
-                || self.game.get_celestia_dir() == Direction::No
-            {
-                &mut self.celestia_eat
-            } else {
-                &mut self.celestia_walk
-            };
-            let mut p = self.game.get_celestia_pos();
-            p.y -= 128.0;
-            celestia.set_position(p);
-            celestia.set_flip_x(self.game.get_celestia_dir() == Direction::Left);
-            celestia.render(ctx)?;
-        }
-
-        let luna = if self.islunawalk {
-            &mut self.luna_walk
-        } else {
-            &mut self.luna_wait
-        };
-        let mut p = self.game.get_luna_pos();
-        p.y -= 126.0;
-        luna.set_position(p);
-        luna.set_flip_x(self.game.get_luna_dir() == Direction::Left);
-        luna.render(ctx)?;
-
-        for cake in self.game.cakes() {
-            let zone = &self.game.zones()[cake.zoneidx];
-            let cake_pos = Vec2::new(cake.x, zone.y - game::CAKE_Y);
-            ctx.draw_texture(&self.cakes[cake.spriteidx], cake_pos, Vec2::new(0.5, 0.5))?;
-
-            if cake.shieldleft > 0.0 {
-                self.shield.set_position(cake_pos);
-                self.shield.render(ctx)?;
-            }
-
-            if cake.hp < 1.0 {
-                self.draw_indicator(
-                    ctx,
-                    cake.x - INDICATOR_W / 2.0,
-                    zone.y,
-                    INDICATOR_W,
-                    INDICATOR_H,
-                    cake.hp,
-                    &COLORSET,
-                )?;
-            }
-        }
-
-        let luna_pos = self.game.get_luna_pos();
-        let laser_width = self.laser.get_absolute_size().x;
-        match self.game.get_laser_dir() {
-            Direction::Right => {
-                let mut start = luna_pos.x + 30.0;
-                while start < area.x + area.width {
-                    self.laser
-                        .set_position(Vec2::new(start, luna_pos.y - LASER_Y));
-                    self.laser.render(ctx)?;
-                    start += laser_width;
-                }
-            }
-            Direction::Left => {
-                let mut start = luna_pos.x - 30.0 - laser_width;
-                while start > area.x - laser_width {
-                    self.laser
-                        .set_position(Vec2::new(start, luna_pos.y - LASER_Y));
-                    self.laser.render(ctx)?;
-                    start -= laser_width;
-                }
-            }
-            Direction::No => {}
-        }
+        // Снимаем преобразование, даже если рисование поля не удалось
+        let drawn = self.draw_field(ctx, area);
+        ctx.pop_transform()?;
+        drawn?;
 
         // Резервирование места под кнопку закрытия в правом верхнем углу
         let indic_y = if self.touchui.is_some()
@@ -670,6 +1119,14 @@ impl Scene for ScenePlay {
         self.hp_label.set_position(Vec2::new(25.0, indic_y + 5.0));
         self.hp_label.render(ctx)?;
 
+        for i in 0..self.game.get_shield_charges().min(MAX_SHIELD_ICONS) {
+            ctx.draw_texture(
+                &self.action_textures[ACT_SHIELD],
+                Vec2::new(view.width - 25.0, indic_y + 60.0 + i as f32 * 36.0),
+                Vec2::new(0.5, 0.5),
+            )?;
+        }
+
         let mana_height =
             indic_height * self.game.get_mana().floor() / self.game.get_balance().max_mana;
         ctx.set_fill_color(MANA_COLOR);
@@ -689,7 +1146,7 @@ impl Scene for ScenePlay {
             self.common_data.button_close.render(ctx)?;
         }
 
-        self.common_data.draw_fps_counter(ctx)?;
+        self.common_data.draw_overlay(ctx)?;
 
         if self.touchui.is_some() && self.common_data.button_close.is_hovered() {
             self.common_data.draw_cursor(ctx)?;
@@ -709,3 +1166,13 @@ impl Scene for ScenePlay {
         Ok(())
     }
 }
+
+/// Положение звука в стерео по его координате x на экране.
+fn sound_pan(ctx: &dyn Context, x: f32) -> f32 {
+    let area = ctx.view().visible_area();
+    if area.width <= 0.0 {
+        return 0.0;
+    }
+    let center = area.x + area.width / 2.0;
+    ((x - center) / (area.width / 2.0)).clamp(-1.0, 1.0) * SOUND_PAN
+}
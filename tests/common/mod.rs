@@ -0,0 +1,34 @@
+//! Общая подготовка окружения для интеграционных тестов.
+
+use anyhow::Result;
+use cake_engine::{conf::Conf, rect::Rect, vec::Vec2};
+use luna_deny_cakes_game::data::{
+    self,
+    options::{Options, OPTIONS},
+};
+use std::path::PathBuf;
+
+/// Загружает ассеты из каталога `data` и сбрасывает настройки на значения по умолчанию.
+pub fn init_data() -> Result<()> {
+    cake_engine::fs::set_assets_directory(PathBuf::from("data"));
+
+    // Настройки пользователя не читаем, чтобы результат не зависел от машины
+    let mut options = OPTIONS.lock().unwrap();
+    *options = Options::default();
+    options.set_available_languages(&["en".to_string(), "ru".to_string()]);
+    options.set_current_language("en".to_string());
+    options.set_soundon(false);
+    options.set_musicon(false);
+    data::reload_lang(&options)
+}
+
+// Используется не всеми тестами
+#[allow(dead_code)]
+pub fn get_conf() -> Conf {
+    let view = Rect::new(0.0, 0.0, 1024.0, 768.0);
+    Conf {
+        logical_size: Vec2::new(view.width, view.height),
+        view: Some(view),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,160 @@
+//! Проверки горячей перезагрузки ассетов на бэкенде-пустышке.
+
+use anyhow::Result;
+use cake_engine::{
+    color::Color,
+    conf::Conf,
+    context::Context,
+    dummy::{DummyContext, EventScript},
+    input::Event,
+    scene::{Scene, SceneResult},
+    texture::{TextureOptions, TextureSource},
+    transition::Transition,
+};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+// Каталог ассетов общий для всего процесса, так что тесты с ним идут по очереди
+static ASSETS_LOCK: Mutex<()> = Mutex::new(());
+// Что видела сцена после перехода: изменился ли strings.json в каждом её кадре
+static SEEN: Mutex<Vec<bool>> = Mutex::new(Vec::new());
+
+fn touch(path: &Path, seconds_later: u64) {
+    let mtime = SystemTime::now() + Duration::from_secs(seconds_later);
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+}
+
+fn poll(ctx: &mut DummyContext) {
+    ctx.time_mut().tick_by(1.0);
+    ctx.reload_changed_resources().unwrap();
+}
+
+fn transition_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("cake_hot_reload_transition_{}", std::process::id()))
+}
+
+// Меняет strings.json и сразу переключается на следующую сцену с переходом
+struct TouchingScene;
+
+impl Scene for TouchingScene {
+    fn process(
+        &mut self,
+        _ctx: &mut dyn Context,
+        _dt: f32,
+        _events: &[Event],
+    ) -> Result<SceneResult> {
+        touch(&transition_dir().join("strings.json"), 10);
+        Ok(SceneResult::SwitchWith(
+            Box::new(WatchingScene),
+            Transition::Fade {
+                color: Color::BLACK,
+                duration: 1.0,
+            },
+        ))
+    }
+}
+
+struct WatchingScene;
+
+impl Scene for WatchingScene {
+    fn process(
+        &mut self,
+        ctx: &mut dyn Context,
+        _dt: f32,
+        _events: &[Event],
+    ) -> Result<SceneResult> {
+        let mut seen = SEEN.lock().unwrap();
+        seen.push(ctx.hot_reload().is_changed(Path::new("strings.json")));
+        if seen.len() >= 2 {
+            return Ok(SceneResult::Quit);
+        }
+        Ok(SceneResult::Normal)
+    }
+}
+
+fn build_touching_scene(ctx: &mut dyn Context) -> Result<Box<dyn Scene>> {
+    ctx.hot_reload_mut().watch("strings.json");
+    Ok(Box::new(TouchingScene))
+}
+
+#[test]
+fn changed_files_are_reported() {
+    let _lock = ASSETS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = std::env::temp_dir().join(format!("cake_hot_reload_test_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("images")).unwrap();
+    std::fs::write(dir.join("images/cake.png"), b"cake").unwrap();
+    std::fs::write(dir.join("strings.en.json"), b"{}").unwrap();
+    std::fs::write(dir.join("strings.ru.json"), b"{}").unwrap();
+    cake_engine::fs::set_assets_directory(dir.clone());
+    cake_engine::fs::set_lang_suffixes(vec!["ru".to_string(), "en".to_string()]);
+
+    let mut ctx = DummyContext::new(&Conf {
+        hot_reload: true,
+        ..Default::default()
+    });
+    let _cake = ctx
+        .load_texture(
+            TextureSource::File(PathBuf::from("images/cake.png")),
+            TextureOptions::PIXELATED,
+        )
+        .unwrap();
+    ctx.hot_reload_mut().watch_lang("strings.json");
+
+    poll(&mut ctx);
+    assert!(ctx.hot_reload().changed_assets().is_empty());
+
+    touch(&dir.join("images/cake.png"), 10);
+    touch(&dir.join("strings.en.json"), 10);
+    poll(&mut ctx);
+    assert!(ctx.hot_reload().is_changed(Path::new("images/cake.png")));
+    assert!(ctx.hot_reload().is_changed(Path::new("strings.json")));
+
+    // Без вызова process у сцены изменения не забываются
+    poll(&mut ctx);
+    assert!(ctx.hot_reload().is_changed(Path::new("strings.json")));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn changes_during_transition_are_kept() {
+    let _lock = ASSETS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = transition_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("strings.json"), b"{}").unwrap();
+    cake_engine::fs::set_assets_directory(dir.clone());
+
+    let conf = Conf {
+        hot_reload: true,
+        ..Default::default()
+    };
+    let script = EventScript {
+        dt: Some(0.1),
+        max_frames: Some(100),
+        ..Default::default()
+    };
+    cake_engine::dummy::main_dummy_script(conf, &build_touching_scene, script).unwrap();
+
+    // Изменение нашлось во время перехода, но сцена всё равно его увидела, причём один раз
+    assert_eq!(*SEEN.lock().unwrap(), vec![true, false]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut ctx = DummyContext::new(&Conf::default());
+    ctx.hot_reload_mut().watch("languages.json");
+    poll(&mut ctx);
+    assert!(!ctx.hot_reload().is_enabled());
+    assert!(ctx.hot_reload().changed_assets().is_empty());
+}
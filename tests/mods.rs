@@ -0,0 +1,60 @@
+//! Проверки объединения ассетов модов с ассетами игры.
+
+mod common;
+
+use cake_engine::fs::{self, Mount};
+use luna_deny_cakes_game::data::{
+    self,
+    options::{Options, OPTIONS},
+    texts::get_text,
+};
+
+#[test]
+fn mod_strings_extend_and_override() {
+    common::init_data().unwrap();
+
+    let dir = std::env::temp_dir().join(format!("cake_mods_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("strings.en.json"),
+        r#"{"menuback": "Go back", "mod_greeting": "Hello from mod"}"#,
+    )
+    .unwrap();
+    std::fs::write(dir.join("strings.ru.json"), r#"{"menuback": "Назад!"}"#).unwrap();
+
+    fs::mount(Mount::Directory(dir.clone()));
+    data::reload_lang(&OPTIONS.lock().unwrap()).unwrap();
+    assert_eq!(get_text("menuback"), "Go back");
+    assert_eq!(get_text("mod_greeting"), "Hello from mod");
+    assert_eq!(get_text("menustart"), "Start");
+
+    // Русский из игры важнее английского из мода, но русский из мода важнее всего
+    {
+        let mut options = OPTIONS.lock().unwrap();
+        options.set_current_language("ru".to_string());
+        data::reload_lang(&options).unwrap();
+    }
+    assert_eq!(get_text("menuback"), "Назад!");
+    assert_eq!(get_text("menustart"), "Начать");
+    assert_eq!(get_text("mod_greeting"), "Hello from mod");
+
+    fs::unmount_all();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn mod_order_in_options() {
+    let mut options = Options::default();
+    assert!(options.invert_mod("ponies"));
+    assert!(options.invert_mod("music"));
+    assert!(options.invert_mod("cakes"));
+    assert_eq!(options.get_mods(), ["ponies", "music", "cakes"]);
+
+    assert!(options.move_mod_up("cakes"));
+    assert!(!options.move_mod_up("ponies"));
+    assert_eq!(options.get_mods(), ["ponies", "cakes", "music"]);
+
+    assert!(!options.invert_mod("cakes"));
+    assert!(!options.is_mod_enabled("cakes"));
+    assert_eq!(options.get_mods(), ["ponies", "music"]);
+}
@@ -0,0 +1,93 @@
+//! Проверки переходов между плейлистами на бэкенде-пустышке.
+
+use cake_engine::{
+    conf::Conf,
+    context::Context,
+    dummy::DummyContext,
+    music::{MusicManager, Playlist},
+    recording::{RecordedCommand, RecordingContext},
+};
+use std::path::PathBuf;
+
+fn make_manager() -> MusicManager {
+    let mut music = MusicManager::new();
+    music.add_playlist(
+        "calm",
+        Playlist {
+            tracks: vec![PathBuf::from("music/calm.ogg")],
+            ..Default::default()
+        },
+    );
+    music.add_playlist(
+        "tense",
+        Playlist {
+            tracks: vec![PathBuf::from("music/tense.ogg")],
+            ..Default::default()
+        },
+    );
+    music
+}
+
+fn wait(ctx: &mut RecordingContext<DummyContext>, music: &mut MusicManager, seconds: f32) {
+    let mut left = seconds;
+    while left > 0.0 {
+        ctx.time_mut().tick_by(0.1);
+        music.process(ctx).unwrap();
+        left -= 0.1;
+    }
+}
+
+#[test]
+fn crossfade_keeps_previous_track_until_faded_out() {
+    let mut ctx = RecordingContext::new(DummyContext::new(&Conf::default()));
+    let mut music = make_manager();
+
+    music.play(&mut ctx, "calm", 0.0).unwrap();
+    music.process(&mut ctx).unwrap();
+    assert!(matches!(
+        ctx.commands().first(),
+        Some(RecordedCommand::PlayMusic { looping: true, .. })
+    ));
+    ctx.clear_commands();
+
+    music.play(&mut ctx, "tense", 1.0).unwrap();
+    assert!(matches!(
+        ctx.commands().first(),
+        Some(RecordedCommand::PlayMusicKeepingPrevious { volume, .. }) if *volume == 0.0
+    ));
+
+    wait(&mut ctx, &mut music, 0.5);
+    assert!(!ctx
+        .commands()
+        .iter()
+        .any(|c| matches!(c, RecordedCommand::StopPreviousMusic)));
+
+    wait(&mut ctx, &mut music, 1.0);
+    assert!(ctx
+        .commands()
+        .iter()
+        .any(|c| matches!(c, RecordedCommand::StopPreviousMusic)));
+    let last_volume = ctx.commands().iter().rev().find_map(|c| match c {
+        RecordedCommand::SetMusicVolume { volume } => Some(*volume),
+        _ => None,
+    });
+    assert_eq!(last_volume, Some(1.0));
+
+    let playing = ctx.get_playing_music().unwrap().unwrap();
+    assert_eq!(playing.path(), PathBuf::from("music/tense.ogg"));
+}
+
+#[test]
+fn stop_fades_out_and_stops() {
+    let mut ctx = RecordingContext::new(DummyContext::new(&Conf::default()));
+    let mut music = make_manager();
+
+    music.play(&mut ctx, "calm", 0.0).unwrap();
+    music.stop(&mut ctx, 0.5).unwrap();
+    assert_eq!(music.current_playlist(), None);
+    assert!(ctx.get_playing_music().unwrap().is_some());
+
+    wait(&mut ctx, &mut music, 1.0);
+    assert!(ctx.get_playing_music().unwrap().is_none());
+    assert!(music.current_track().is_none());
+}
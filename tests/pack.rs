@@ -0,0 +1,88 @@
+//! Проверки архива ассетов и порядка поиска в смонтированных источниках.
+
+use cake_engine::{
+    fs::{self, Mount},
+    pack::{self, Pack},
+};
+use std::path::PathBuf;
+
+fn make_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cake_pack_test_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("images")).unwrap();
+    dir
+}
+
+#[test]
+fn pack_roundtrip_and_corruption() {
+    let dir = make_dir("roundtrip");
+    let src = dir.join("src");
+    std::fs::create_dir_all(src.join("images")).unwrap();
+    std::fs::write(src.join("languages.json"), b"[\"en\"]").unwrap();
+    std::fs::write(src.join("images/cake.png"), b"not really a png").unwrap();
+
+    let dst = dir.join("data.pak");
+    pack::write_pack(&src, &dst).unwrap();
+
+    let pack = Pack::open(&dst).unwrap();
+    assert_eq!(pack.files(), vec!["images/cake.png", "languages.json"]);
+    assert_eq!(
+        pack.read("images/cake.png").unwrap().unwrap(),
+        b"not really a png"
+    );
+    assert_eq!(pack.read("./languages.json").unwrap().unwrap(), b"[\"en\"]");
+    assert!(pack.read("missing.txt").unwrap().is_none());
+    assert!(pack.read("../languages.json").unwrap().is_none());
+    pack.verify().unwrap();
+
+    // Портим последний байт содержимого
+    let mut data = std::fs::read(&dst).unwrap();
+    *data.last_mut().unwrap() ^= 0xff;
+    std::fs::write(&dst, &data).unwrap();
+    let pack = Pack::open(&dst).unwrap();
+    assert!(pack.verify().is_err());
+
+    // Портим список файлов
+    data[20] ^= 0xff;
+    std::fs::write(&dst, &data).unwrap();
+    assert!(Pack::open(&dst).is_err());
+
+    // Огромное количество файлов отвергается ещё до чтения списка
+    data[20] ^= 0xff;
+    let count = data[12..16].to_vec();
+    data[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+    std::fs::write(&dst, &data).unwrap();
+    assert!(Pack::open(&dst).is_err());
+
+    // Обрезанный архив: файл выходит за его конец
+    data[12..16].copy_from_slice(&count);
+    std::fs::write(&dst, &data[..data.len() - 1]).unwrap();
+    assert!(Pack::open(&dst).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn mounts_are_searched_in_reverse_order() {
+    static EMBEDDED: &[(&str, &[u8])] = &[("a.txt", b"embedded"), ("b.txt", b"embedded")];
+
+    let dir = make_dir("mounts");
+    std::fs::write(dir.join("a.txt"), b"directory").unwrap();
+
+    fs::mount(Mount::Embedded(EMBEDDED));
+    fs::mount(Mount::Directory(dir.clone()));
+
+    assert_eq!(fs::read_asset_to_string("a.txt").unwrap(), "directory");
+    assert_eq!(fs::read_asset_to_string("b.txt").unwrap(), "embedded");
+    assert!(fs::read_asset_to_bytes("c.txt").is_err());
+
+    // Вшитый ассет извлекается во временный файл для бэкендов, которым нужен путь
+    let extracted = fs::asset_file_path("b.txt").unwrap();
+    assert_eq!(std::fs::read(extracted).unwrap(), b"embedded");
+    assert_eq!(fs::asset_file_path("a.txt").unwrap(), dir.join("a.txt"));
+
+    fs::unmount_all();
+    assert!(fs::read_asset_to_bytes("b.txt").is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,155 @@
+//! Проверки того, что рисуют сцены, без настоящей отрисовки.
+
+mod common;
+
+use anyhow::Result;
+use cake_engine::{
+    context::Context,
+    dummy::DummyContext,
+    input::Event,
+    recording::{RecordedCommand, RecordingContext},
+    scene::Scene,
+    vec::Vec2,
+};
+use luna_deny_cakes_game::{
+    common_data::CommonData,
+    data::options::OPTIONS,
+    game::{self, Game, Zone},
+    level::Level,
+    scene::play::ScenePlay,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::path::Path;
+
+const SEED: u64 = 20231015;
+
+fn setup() -> Result<(RecordingContext<DummyContext>, CommonData)> {
+    common::init_data()?;
+
+    let conf = common::get_conf();
+    let mut ctx = RecordingContext::new(DummyContext::new(&conf));
+    ctx.view_mut().set(conf.view);
+
+    let common_data = CommonData::new(&mut ctx)?;
+    Ok((ctx, common_data))
+}
+
+fn move_mouse(ctx: &mut RecordingContext<DummyContext>, point: Vec2) {
+    let input = ctx.inner_mut().input_mut();
+    input.clear();
+    input.handle_events(&[Event::MouseMove {
+        point,
+        touch_id: None,
+    }]);
+}
+
+#[test]
+fn shield_is_drawn_for_each_shielded_cake() {
+    let (mut ctx, common_data) = setup().unwrap();
+
+    let mut game = Game::with_seed(OPTIONS.lock().unwrap().get_difficulty(), SEED);
+    let cake_positions: Vec<Vec2> = game
+        .cakes()
+        .iter()
+        .map(|cake| Vec2::new(cake.x, game.zones()[cake.zoneidx].y - game::CAKE_Y))
+        .collect();
+    assert!(cake_positions.len() >= 3);
+    assert!(game.set_shield_to_cake_by_xy(cake_positions[0]));
+    assert!(game.set_shield_to_cake_by_xy(cake_positions[2]));
+
+    let mut scene = ScenePlay::with_game(common_data, &mut ctx, game, None).unwrap();
+    ctx.clear_commands();
+    scene.render(&mut ctx).unwrap();
+
+    let shields = ctx.texture_draws(Path::new("images/shield.png"));
+    assert_eq!(shields.len(), 2);
+    assert_eq!(shields[0].position, cake_positions[0]);
+    assert_eq!(shields[1].position, cake_positions[2]);
+}
+
+#[test]
+fn deny_icon_is_drawn_where_action_is_not_allowed() {
+    let (mut ctx, common_data) = setup().unwrap();
+    let game = Game::with_seed(OPTIONS.lock().unwrap().get_difficulty(), SEED);
+    let zone = game.zones()[0];
+    let mut scene = ScenePlay::with_game(common_data, &mut ctx, game, None).unwrap();
+    let deny = Path::new("images/deny.png");
+
+    // Прыгать можно только на платформы
+    move_mouse(
+        &mut ctx,
+        Vec2::new((zone.left + zone.right) / 2.0, zone.y - 5.0),
+    );
+    ctx.clear_commands();
+    scene.render(&mut ctx).unwrap();
+    assert!(ctx.texture_draws(deny).is_empty());
+
+    move_mouse(&mut ctx, Vec2::new(zone.left - 5.0, zone.y + 100.0));
+    ctx.clear_commands();
+    scene.render(&mut ctx).unwrap();
+    assert_eq!(ctx.texture_draws(deny).len(), 1);
+}
+
+#[test]
+fn platforms_are_drawn_into_texture_once() {
+    let (mut ctx, common_data) = setup().unwrap();
+    let mut rng = ChaCha8Rng::seed_from_u64(SEED);
+    let mut level = Level::generate(OPTIONS.lock().unwrap().get_difficulty(), &mut rng);
+    // Платформа за нижним краем экрана тоже должна попасть в текстуру
+    level.zones.push(Zone {
+        y: 900.0,
+        left: 50.0,
+        right: 50.0 + game::BLOCKW * 3.0,
+    });
+    let game = Game::with_level(&level);
+    let mut scene = ScenePlay::with_game(common_data, &mut ctx, game, None).unwrap();
+    let block = Path::new("images/block.png");
+
+    ctx.clear_commands();
+    scene.render(&mut ctx).unwrap();
+    assert!(!ctx.texture_draws(block).is_empty());
+
+    // На экран платформы попадают одной текстурой, которая покрывает их все
+    let commands = ctx.commands();
+    let block_size = commands
+        .iter()
+        .find_map(|c| match c {
+            RecordedCommand::DrawTexture {
+                path: Some(p),
+                size,
+                ..
+            } if p == block => Some(*size),
+            _ => None,
+        })
+        .unwrap();
+    let end = commands
+        .iter()
+        .position(|c| matches!(c, RecordedCommand::EndTarget))
+        .unwrap();
+    let (size, params) = commands[end..]
+        .iter()
+        .find_map(|c| match c {
+            RecordedCommand::DrawTexture {
+                path: None,
+                size,
+                params,
+            } => Some((*size, *params)),
+            _ => None,
+        })
+        .unwrap();
+    let top = level
+        .zones
+        .iter()
+        .map(|z| z.y)
+        .fold(f32::INFINITY, f32::min);
+    assert_eq!(params.position, Vec2::new(50.0, top));
+    let bottom = 900.0 + block_size.y;
+    assert!((size.y * params.scale.y - (bottom - top)).abs() < 1.0);
+    let right = 50.0 + game::BLOCKW * 10.0 + block_size.x;
+    assert!((size.x * params.scale.x - (right - 50.0)).abs() < 1.0);
+
+    ctx.clear_commands();
+    scene.render(&mut ctx).unwrap();
+    assert!(ctx.texture_draws(block).is_empty());
+}
@@ -0,0 +1,60 @@
+//! Проверки рисования в текстуру на бэкенде-пустышке.
+
+use cake_engine::{
+    conf::Conf, context::Context, dummy::DummyContext, rect::Rect, texture::TextureOptions,
+};
+
+#[test]
+fn view_is_switched_to_target_and_restored() {
+    let conf = Conf {
+        view: Some(Rect::new(0.0, 0.0, 1024.0, 768.0)),
+        ..Default::default()
+    };
+    let mut ctx = DummyContext::new(&conf);
+    ctx.view_mut().set(conf.view);
+
+    let target = ctx
+        .create_render_target(200, 100, TextureOptions::PIXELATED)
+        .unwrap();
+    assert!(ctx.is_texture_valid(&target));
+
+    ctx.begin_target(&target).unwrap();
+    assert_eq!(
+        ctx.view().get_or_default(),
+        Rect::new(0.0, 0.0, 200.0, 100.0)
+    );
+    // Рисовать в текстуру, в которую уже идёт рисование, нельзя
+    assert!(ctx.begin_target(&target).is_err());
+    ctx.end_target().unwrap();
+
+    assert_eq!(
+        ctx.view().get_or_default(),
+        Rect::new(0.0, 0.0, 1024.0, 768.0)
+    );
+    assert!(ctx.end_target().is_err());
+}
+
+#[test]
+fn nested_target_restores_outer_target() {
+    let mut ctx = DummyContext::new(&Conf::default());
+
+    let outer = ctx
+        .create_render_target(200, 100, TextureOptions::PIXELATED)
+        .unwrap();
+    let inner = ctx
+        .create_render_target(32, 16, TextureOptions::PIXELATED)
+        .unwrap();
+
+    ctx.begin_target(&outer).unwrap();
+    ctx.begin_target(&inner).unwrap();
+    assert_eq!(ctx.view().get_or_default(), Rect::new(0.0, 0.0, 32.0, 16.0));
+    assert!(ctx.begin_target(&outer).is_err());
+    ctx.end_target().unwrap();
+
+    assert_eq!(
+        ctx.view().get_or_default(),
+        Rect::new(0.0, 0.0, 200.0, 100.0)
+    );
+    ctx.end_target().unwrap();
+    assert!(ctx.end_target().is_err());
+}
@@ -0,0 +1,313 @@
+//! Проверки стека сцен на программном бэкенде.
+
+use anyhow::Result;
+use cake_engine::{
+    color::Color,
+    conf::Conf,
+    context::Context,
+    input::Event,
+    rect::Rect,
+    scene::{Scene, SceneResult},
+    snapshot::{run_scene, SnapshotParams},
+    software::SoftwareContext,
+    texture::TextureOptions,
+    transition::{SlideDirection, Transition},
+    vec::Vec2,
+};
+use std::{cell::RefCell, rc::Rc};
+
+type Log = Rc<RefCell<Vec<String>>>;
+type Action = Box<dyn FnOnce(Log) -> SceneResult>;
+
+// Сцена, которая записывает вызовы своих методов в общий журнал и на заданном кадре
+// возвращает заданный результат
+struct LogScene {
+    name: &'static str,
+    log: Log,
+    frame: usize,
+    action: Option<(usize, Action)>,
+    color: Color,
+    rect: Option<Rect>,
+    overlay: bool,
+    // Рисовать через собственную текстуру, как сцены, которые кэшируют часть картинки
+    cached: bool,
+}
+
+impl LogScene {
+    fn new(name: &'static str, log: &Log, color: Color) -> LogScene {
+        LogScene {
+            name,
+            log: log.clone(),
+            frame: 0,
+            action: None,
+            color,
+            rect: None,
+            overlay: false,
+            cached: false,
+        }
+    }
+
+    fn on_frame<F>(mut self, frame: usize, action: F) -> LogScene
+    where
+        F: FnOnce(Log) -> SceneResult + 'static,
+    {
+        self.action = Some((frame, Box::new(action)));
+        self
+    }
+
+    fn write(&self, method: &str) {
+        self.log
+            .borrow_mut()
+            .push(format!("{}.{}", self.name, method));
+    }
+}
+
+impl LogScene {
+    fn fill(&self, ctx: &mut dyn Context) -> Result<()> {
+        ctx.set_fill_color(self.color);
+        match self.rect {
+            Some(rect) => ctx.fill_rect(rect),
+            None => ctx.clear(),
+        }
+    }
+}
+
+impl Scene for LogScene {
+    fn start(&mut self, _ctx: &mut dyn Context, _prev_scene: Option<Box<dyn Scene>>) -> Result<()> {
+        self.write("start");
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _ctx: &mut dyn Context,
+        _dt: f32,
+        _events: &[Event],
+    ) -> Result<SceneResult> {
+        self.write("process");
+        let frame = self.frame;
+        self.frame += 1;
+
+        if self.action.as_ref().is_some_and(|(f, _)| *f == frame) {
+            let (_, action) = self.action.take().unwrap();
+            return Ok(action(self.log.clone()));
+        }
+        Ok(SceneResult::Normal)
+    }
+
+    fn render(&mut self, ctx: &mut dyn Context) -> Result<()> {
+        self.write("render");
+        if self.cached {
+            let cache = ctx.create_render_target(16, 16, TextureOptions::PIXELATED)?;
+            ctx.begin_target(&cache)?;
+            self.fill(ctx)?;
+            ctx.end_target()?;
+            return ctx.draw_texture(&cache, Vec2::new(0.0, 0.0), Vec2::new(0.0, 0.0));
+        }
+        self.fill(ctx)
+    }
+
+    fn stop(&mut self, _ctx: &mut dyn Context) -> Result<()> {
+        self.write("stop");
+        Ok(())
+    }
+
+    fn is_overlay(&self) -> bool {
+        self.overlay
+    }
+}
+
+fn new_ctx() -> SoftwareContext {
+    let conf = Conf {
+        logical_size: Vec2::new(16.0, 16.0),
+        ..Default::default()
+    };
+    SoftwareContext::new(&conf)
+}
+
+fn params(frames: usize) -> SnapshotParams {
+    SnapshotParams {
+        frames,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn push_keeps_lower_scene_and_pop_resumes_it() {
+    let log = Log::default();
+    let mut ctx = new_ctx();
+
+    let scene = LogScene::new("a", &log, Color::RED).on_frame(0, |log| {
+        let b = LogScene::new("b", &log, Color::GREEN).on_frame(1, |_| SceneResult::Pop);
+        SceneResult::Push(Box::new(b))
+    });
+    let image = run_scene(&mut ctx, Box::new(scene), &params(4)).unwrap();
+
+    assert_eq!(
+        *log.borrow(),
+        [
+            "a.start",
+            // Кадр 0: a кладёт b поверх себя, но не останавливается
+            "a.process",
+            "b.start",
+            // Кадр 1: обрабатывается и рендерится только b
+            "b.process",
+            "b.render",
+            // Кадр 2: b убирает себя из стека
+            "b.process",
+            "b.stop",
+            // Кадр 3: управление вернулось к a
+            "a.process",
+            "a.render",
+            "a.stop",
+        ]
+    );
+    assert_eq!(image.get_pixel(8, 8), Color::RED);
+}
+
+#[test]
+fn overlay_renders_scene_below() {
+    let log = Log::default();
+    let mut ctx = new_ctx();
+
+    let scene = LogScene::new("a", &log, Color::RED).on_frame(0, |log| {
+        let mut b = LogScene::new("b", &log, Color::GREEN);
+        b.rect = Some(Rect::new(4.0, 4.0, 8.0, 8.0));
+        b.overlay = true;
+        SceneResult::Push(Box::new(b))
+    });
+    let image = run_scene(&mut ctx, Box::new(scene), &params(2)).unwrap();
+
+    assert_eq!(
+        *log.borrow(),
+        [
+            "a.start",
+            "a.process",
+            "b.start",
+            // Нижняя сцена рендерится, но не обрабатывается
+            "b.process",
+            "a.render",
+            "b.render",
+            "b.stop",
+            "a.stop",
+        ]
+    );
+    assert_eq!(image.get_pixel(0, 0), Color::RED);
+    assert_eq!(image.get_pixel(8, 8), Color::GREEN);
+}
+
+#[test]
+fn opaque_scene_hides_scene_below() {
+    let log = Log::default();
+    let mut ctx = new_ctx();
+
+    let scene = LogScene::new("a", &log, Color::RED).on_frame(0, |log| {
+        let mut b = LogScene::new("b", &log, Color::GREEN);
+        b.rect = Some(Rect::new(4.0, 4.0, 8.0, 8.0));
+        SceneResult::Push(Box::new(b))
+    });
+    let image = run_scene(&mut ctx, Box::new(scene), &params(2)).unwrap();
+
+    assert!(!log.borrow().contains(&"a.render".to_string()));
+    assert_ne!(image.get_pixel(0, 0), Color::RED);
+    assert_eq!(image.get_pixel(8, 8), Color::GREEN);
+}
+
+#[test]
+fn popping_last_scene_quits() {
+    let log = Log::default();
+    let mut ctx = new_ctx();
+
+    let scene = LogScene::new("a", &log, Color::RED).on_frame(0, |_| SceneResult::Pop);
+    run_scene(&mut ctx, Box::new(scene), &params(10)).unwrap();
+
+    // После опустевшего стека сцена больше не обрабатывается и не останавливается повторно
+    assert_eq!(*log.borrow(), ["a.start", "a.process", "a.stop"]);
+}
+
+#[test]
+fn switch_with_renders_old_scene_until_transition_ends() {
+    let log = Log::default();
+    let mut ctx = new_ctx();
+
+    let scene = LogScene::new("a", &log, Color::RED).on_frame(0, |log| {
+        let b = LogScene::new("b", &log, Color::GREEN);
+        SceneResult::SwitchWith(Box::new(b), Transition::Crossfade { duration: 0.5 })
+    });
+    let params = SnapshotParams {
+        frames: 4,
+        dt: 0.25,
+        ..Default::default()
+    };
+    run_scene(&mut ctx, Box::new(scene), &params).unwrap();
+
+    assert_eq!(
+        *log.borrow(),
+        [
+            "a.start",
+            "a.process",
+            "b.start",
+            // Кадр 1: идёт переход, обе сцены только рендерятся
+            "b.render",
+            "a.render",
+            // Кадр 2: переход закончился, старая сцена остановлена
+            "a.stop",
+            "b.render",
+            // Кадр 3: новая сцена работает как обычно
+            "b.process",
+            "b.render",
+            "b.stop",
+        ]
+    );
+}
+
+#[test]
+fn slide_moves_live_old_scene() {
+    let log = Log::default();
+    let mut ctx = new_ctx();
+
+    let scene = LogScene::new("a", &log, Color::RED).on_frame(0, |log| {
+        let b = LogScene::new("b", &log, Color::GREEN);
+        let transition = Transition::Slide {
+            direction: SlideDirection::Left,
+            duration: 0.5,
+        };
+        SceneResult::SwitchWith(Box::new(b), transition)
+    });
+    let params = SnapshotParams {
+        frames: 2,
+        dt: 0.25,
+        ..Default::default()
+    };
+    let image = run_scene(&mut ctx, Box::new(scene), &params).unwrap();
+
+    // На середине перехода старая сцена уехала на полэкрана влево
+    assert_eq!(image.get_pixel(2, 8), Color::RED);
+    assert_eq!(image.get_pixel(13, 8), Color::GREEN);
+}
+
+#[test]
+fn old_scene_can_draw_into_own_target_during_transition() {
+    let log = Log::default();
+    let mut ctx = new_ctx();
+
+    let mut scene = LogScene::new("a", &log, Color::RED).on_frame(0, |log| {
+        let b = LogScene::new("b", &log, Color::GREEN);
+        let transition = Transition::Slide {
+            direction: SlideDirection::Left,
+            duration: 0.5,
+        };
+        SceneResult::SwitchWith(Box::new(b), transition)
+    });
+    scene.cached = true;
+    let params = SnapshotParams {
+        frames: 2,
+        dt: 0.25,
+        ..Default::default()
+    };
+    // Старая сцена рисует в свою текстуру, пока переход рисует её саму в свою
+    let image = run_scene(&mut ctx, Box::new(scene), &params).unwrap();
+
+    assert_eq!(image.get_pixel(2, 8), Color::RED);
+    assert_eq!(image.get_pixel(13, 8), Color::GREEN);
+}
@@ -0,0 +1,107 @@
+//! Снапшот-тесты основных сцен на программном бэкенде:
+//!
+//!     cargo test --features software --test snapshots
+//!
+//! Эталонные картинки лежат в `tests/snapshots`. Чтобы создать или перезаписать эталоны,
+//! запустите тесты с `CAKE_ENGINE_UPDATE_SNAPSHOTS=1`.
+
+mod common;
+
+use anyhow::Result;
+use cake_engine::{
+    context::Context,
+    image::Image,
+    input::Event,
+    scene::Scene,
+    snapshot::{check_snapshot, run_scene, SnapshotParams, UPDATE_ENV},
+    software::SoftwareContext,
+    vec::Vec2,
+};
+use luna_deny_cakes_game::{
+    common_data::CommonData,
+    data::texts::get_text,
+    scene::{gameover::SceneGameOver, menu::SceneMenu, play::ScenePlay},
+};
+use std::path::Path;
+
+const SEED: u64 = 20231015;
+
+fn setup() -> Result<(SoftwareContext, CommonData)> {
+    common::init_data()?;
+
+    let conf = common::get_conf();
+    let mut ctx = SoftwareContext::new(&conf);
+    ctx.view_mut().set(conf.view);
+
+    let common_data = CommonData::new(&mut ctx)?;
+    Ok((ctx, common_data))
+}
+
+fn check(ctx: &mut SoftwareContext, scene: Box<dyn Scene>, params: SnapshotParams, name: &str) {
+    let image = run_scene(ctx, scene, &params).unwrap();
+    let reference = Path::new("tests/snapshots").join(format!("{}.png", name));
+    check_snapshot(&image, &reference, &params).unwrap();
+}
+
+#[test]
+fn menu() {
+    let (mut ctx, common_data) = setup().unwrap();
+    let scene = SceneMenu::new(common_data, &mut ctx).unwrap();
+
+    // Наводим мышь на кнопку «Старт», чтобы заодно проверить подсветку
+    let params = SnapshotParams {
+        frames: 3,
+        events: vec![(
+            1,
+            Event::MouseMove {
+                point: Vec2::new(512.0, 240.0),
+                touch_id: None,
+            },
+        )],
+        ..Default::default()
+    };
+    check(&mut ctx, Box::new(scene), params, "menu");
+}
+
+#[test]
+fn play() {
+    let (mut ctx, common_data) = setup().unwrap();
+    let scene = ScenePlay::with_seed(common_data, &mut ctx, SEED).unwrap();
+
+    let params = SnapshotParams {
+        frames: 120,
+        ..Default::default()
+    };
+    check(&mut ctx, Box::new(scene), params, "play");
+}
+
+#[test]
+fn gameover() {
+    let (mut ctx, common_data) = setup().unwrap();
+    let scene = SceneGameOver::new(common_data, &mut ctx, true, get_text("msg_cakeover"));
+
+    check(
+        &mut ctx,
+        Box::new(scene),
+        SnapshotParams::default(),
+        "gameover",
+    );
+}
+
+#[test]
+fn missing_reference_is_an_error() {
+    if std::env::var(UPDATE_ENV).is_ok_and(|v| v == "1") {
+        return;
+    }
+
+    let dir = std::env::temp_dir().join(format!("cake_snapshot_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let reference = dir.join("missing.png");
+
+    let image = Image::new(4, 4);
+    assert!(check_snapshot(&image, &reference, &SnapshotParams::default()).is_err());
+    assert!(!reference.exists());
+    assert!(dir.join("missing.actual.png").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,412 @@
+//! Проверки растеризации на программном бэкенде.
+
+use cake_engine::{
+    color::Color,
+    conf::Conf,
+    context::{Context, DrawTextureParams},
+    image::Image,
+    rect::Rect,
+    software::SoftwareContext,
+    texture::{Texture, TextureOptions, TextureSource},
+    transform::Transform,
+    vec::Vec2,
+};
+use std::rc::Rc;
+
+const HALF_RED: Color = Color::new(255, 0, 0, 128);
+
+fn new_ctx() -> SoftwareContext {
+    let conf = Conf {
+        logical_size: Vec2::new(16.0, 16.0),
+        ..Default::default()
+    };
+    let mut ctx = SoftwareContext::new(&conf);
+    ctx.set_fill_color(Color::BLACK);
+    ctx.clear().unwrap();
+    ctx
+}
+
+// Цвет пикселя после однократного смешивания с чёрным фоном
+fn blended_once(color: Color) -> Color {
+    let mut image = Image::new(1, 1);
+    image.fill(Color::BLACK);
+    image.blend_pixel(0, 0, color);
+    image.get_pixel(0, 0)
+}
+
+fn covered(image: &Image) -> Vec<(u32, u32)> {
+    let mut result = Vec::new();
+    for y in 0..image.height() {
+        for x in 0..image.width() {
+            if image.get_pixel(x, y) != Color::BLACK {
+                result.push((x, y));
+            }
+        }
+    }
+    result
+}
+
+fn assert_blended_once(image: &Image, color: Color) {
+    let once = blended_once(color);
+    for (x, y) in covered(image) {
+        assert_eq!(image.get_pixel(x, y), once, "pixel ({}, {})", x, y);
+    }
+}
+
+// Текстура 2x2: красный, зелёный / синий, белый
+fn quad_texture(ctx: &mut SoftwareContext) -> Rc<Texture> {
+    let mut image = Image::new(2, 2);
+    image.set_pixel(0, 0, Color::RED);
+    image.set_pixel(1, 0, Color::GREEN);
+    image.set_pixel(0, 1, Color::BLUE);
+    image.set_pixel(1, 1, Color::WHITE);
+    ctx.load_texture(
+        TextureSource::Data(image.to_png_bytes().unwrap()),
+        TextureOptions::PIXELATED,
+    )
+    .unwrap()
+}
+
+fn draw_quad(params: DrawTextureParams) -> Image {
+    let mut ctx = new_ctx();
+    let texture = quad_texture(&mut ctx);
+    ctx.draw_texture_ex(&texture, params).unwrap();
+    ctx.framebuffer().clone()
+}
+
+fn assert_pixels(image: &Image, expected: &[((u32, u32), Color)]) {
+    let mut positions: Vec<(u32, u32)> = expected.iter().map(|(p, _)| *p).collect();
+    positions.sort_by_key(|(x, y)| (*y, *x));
+    assert_eq!(covered(image), positions);
+    for ((x, y), color) in expected {
+        assert_eq!(image.get_pixel(*x, *y), *color, "pixel ({}, {})", x, y);
+    }
+}
+
+#[test]
+fn fill_rect_covers_pixel_centers() {
+    let expected: Vec<(u32, u32)> = (3..5).flat_map(|y| (2..6).map(move |x| (x, y))).collect();
+
+    // Вывернутый прямоугольник закрашивает то же самое
+    for rect in [
+        Rect::new(2.0, 3.0, 4.0, 2.0),
+        Rect::new(6.0, 5.0, -4.0, -2.0),
+    ] {
+        let mut ctx = new_ctx();
+        ctx.set_fill_color(Color::WHITE);
+        ctx.fill_rect(rect).unwrap();
+        assert_eq!(covered(ctx.framebuffer()), expected);
+    }
+}
+
+#[test]
+fn fill_rect_is_clipped_and_blended() {
+    let mut ctx = new_ctx();
+    ctx.set_fill_color(HALF_RED);
+    ctx.fill_rect(Rect::new(-5.0, -5.0, 8.0, 8.0)).unwrap();
+    ctx.fill_rect(Rect::new(14.0, 14.0, 10.0, 10.0)).unwrap();
+
+    let image = ctx.framebuffer();
+    assert_blended_once(image, HALF_RED);
+    let expected: Vec<(u32, u32)> = (0..3)
+        .flat_map(|y| (0..3).map(move |x| (x, y)))
+        .chain((14..16).flat_map(|y| (14..16).map(move |x| (x, y))))
+        .collect();
+    assert_eq!(covered(image), expected);
+}
+
+#[test]
+fn texture_is_drawn_at_position() {
+    let image = draw_quad(DrawTextureParams {
+        position: Vec2::new(4.0, 4.0),
+        ..Default::default()
+    });
+    assert_pixels(
+        &image,
+        &[
+            ((4, 4), Color::RED),
+            ((5, 4), Color::GREEN),
+            ((4, 5), Color::BLUE),
+            ((5, 5), Color::WHITE),
+        ],
+    );
+}
+
+#[test]
+fn texture_src_selects_part() {
+    let image = draw_quad(DrawTextureParams {
+        src: Some(Rect::new(1.0, 0.0, 1.0, 2.0)),
+        position: Vec2::new(4.0, 4.0),
+        ..Default::default()
+    });
+    assert_pixels(&image, &[((4, 4), Color::GREEN), ((4, 5), Color::WHITE)]);
+}
+
+#[test]
+fn texture_origin_shifts_texture() {
+    let image = draw_quad(DrawTextureParams {
+        origin: Vec2::new(1.0, 1.0),
+        position: Vec2::new(4.0, 4.0),
+        ..Default::default()
+    });
+    assert_pixels(
+        &image,
+        &[
+            ((2, 2), Color::RED),
+            ((3, 2), Color::GREEN),
+            ((2, 3), Color::BLUE),
+            ((3, 3), Color::WHITE),
+        ],
+    );
+}
+
+#[test]
+fn texture_is_rotated_clockwise() {
+    let image = draw_quad(DrawTextureParams {
+        position: Vec2::new(8.0, 8.0),
+        rotation: 90.0,
+        ..Default::default()
+    });
+    // Верхний край текстуры теперь идёт вниз от позиции, а левый — влево
+    assert_pixels(
+        &image,
+        &[
+            ((7, 8), Color::RED),
+            ((7, 9), Color::GREEN),
+            ((6, 8), Color::BLUE),
+            ((6, 9), Color::WHITE),
+        ],
+    );
+}
+
+#[test]
+fn texture_is_scaled() {
+    let image = draw_quad(DrawTextureParams {
+        position: Vec2::new(4.0, 4.0),
+        scale: Vec2::new(2.0, 1.0),
+        ..Default::default()
+    });
+    assert_pixels(
+        &image,
+        &[
+            ((4, 4), Color::RED),
+            ((5, 4), Color::RED),
+            ((6, 4), Color::GREEN),
+            ((7, 4), Color::GREEN),
+            ((4, 5), Color::BLUE),
+            ((5, 5), Color::BLUE),
+            ((6, 5), Color::WHITE),
+            ((7, 5), Color::WHITE),
+        ],
+    );
+}
+
+#[test]
+fn texture_is_flipped() {
+    let flipped = [
+        ((4, 4), Color::GREEN),
+        ((5, 4), Color::RED),
+        ((4, 5), Color::WHITE),
+        ((5, 5), Color::BLUE),
+    ];
+
+    // Вывернутый источник переворачивает текстуру на месте
+    let image = draw_quad(DrawTextureParams {
+        src: Some(Rect::new(2.0, 0.0, -2.0, 2.0)),
+        position: Vec2::new(4.0, 4.0),
+        ..Default::default()
+    });
+    assert_pixels(&image, &flipped);
+
+    // Отрицательный масштаб переворачивает её относительно origin
+    let image = draw_quad(DrawTextureParams {
+        origin: Vec2::new(1.0, 0.0),
+        position: Vec2::new(4.0, 4.0),
+        scale: Vec2::new(-1.0, 1.0),
+        ..Default::default()
+    });
+    assert_pixels(&image, &flipped);
+}
+
+#[test]
+fn texture_is_multiplied_by_color() {
+    let image = draw_quad(DrawTextureParams {
+        position: Vec2::new(4.0, 4.0),
+        color: Color::new(255, 0, 255, 255),
+        ..Default::default()
+    });
+    // Зелёный пиксель стал чёрным и слился с фоном
+    assert_pixels(
+        &image,
+        &[
+            ((4, 4), Color::RED),
+            ((4, 5), Color::BLUE),
+            ((5, 5), Color::new(255, 0, 255, 255)),
+        ],
+    );
+
+    let image = draw_quad(DrawTextureParams {
+        position: Vec2::new(4.0, 4.0),
+        color: Color::new(255, 255, 255, 0),
+        ..Default::default()
+    });
+    assert!(covered(&image).is_empty());
+}
+
+#[test]
+fn framebuffer_survives_png_round_trip() {
+    let mut ctx = new_ctx();
+    let texture = quad_texture(&mut ctx);
+    ctx.draw_texture(&texture, Vec2::new(3.0, 5.0), Vec2::new(0.0, 0.0))
+        .unwrap();
+    ctx.set_fill_color(HALF_RED);
+    ctx.fill_rect(Rect::new(8.0, 8.0, 4.0, 4.0)).unwrap();
+    let frame = ctx.framebuffer();
+
+    let decoded = Image::from_png_bytes(&frame.to_png_bytes().unwrap()).unwrap();
+    assert_eq!(decoded.width(), frame.width());
+    assert_eq!(decoded.height(), frame.height());
+    assert_eq!(decoded.pixels(), frame.pixels());
+
+    let path =
+        std::env::temp_dir().join(format!("cake_engine_software_{}.png", std::process::id()));
+    ctx.save_png(&path).unwrap();
+    let loaded = Image::load_png(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap().pixels(), frame.pixels());
+}
+
+#[test]
+fn polygon_covers_pixel_centers() {
+    let square = |x0: f32, y0: f32, x1: f32, y1: f32| {
+        [
+            Vec2::new(x0, y0),
+            Vec2::new(x1, y0),
+            Vec2::new(x1, y1),
+            Vec2::new(x0, y1),
+        ]
+    };
+    let expected: Vec<(u32, u32)> = (2..6).flat_map(|y| (2..6).map(move |x| (x, y))).collect();
+
+    // Границы по краям пикселей и по их центрам: центр на левой/верхней границе
+    // закрашивается, на правой/нижней — нет
+    for points in [square(2.0, 2.0, 6.0, 6.0), square(2.5, 2.5, 6.5, 6.5)] {
+        let mut ctx = new_ctx();
+        ctx.set_fill_color(Color::WHITE);
+        ctx.fill_polygon(&points).unwrap();
+        assert_eq!(covered(ctx.framebuffer()), expected);
+    }
+}
+
+#[test]
+fn shared_edges_are_blended_once() {
+    let quad = [
+        Vec2::new(-2.1000001, 6.1),
+        Vec2::new(8.5, 14.0),
+        Vec2::new(14.1, 16.9),
+        Vec2::new(2.5, 5.5),
+    ];
+
+    let mut whole = new_ctx();
+    whole.set_fill_color(HALF_RED);
+    whole.fill_polygon(&quad).unwrap();
+
+    // Та же фигура из двух треугольников с общей диагональю, обходимой в разные стороны
+    let mut halves = new_ctx();
+    halves.set_fill_color(HALF_RED);
+    halves.fill_polygon(&[quad[0], quad[1], quad[2]]).unwrap();
+    halves.fill_polygon(&[quad[3], quad[2], quad[0]]).unwrap();
+
+    assert_blended_once(halves.framebuffer(), HALF_RED);
+    assert_eq!(halves.framebuffer().pixels(), whole.framebuffer().pixels());
+}
+
+#[test]
+fn circle_segments_are_blended_once() {
+    let mut ctx = new_ctx();
+    ctx.set_fill_color(HALF_RED);
+    ctx.draw_circle(Vec2::new(8.0, 8.0), 7.0, 3.0).unwrap();
+
+    let image = ctx.framebuffer();
+    assert_blended_once(image, HALF_RED);
+    // Середина кольца закрашена, центр — нет
+    assert_eq!(image.get_pixel(2, 8), blended_once(HALF_RED));
+    assert_eq!(image.get_pixel(8, 8), Color::BLACK);
+}
+
+#[test]
+fn thick_circle_is_filled() {
+    let mut filled = new_ctx();
+    filled.set_fill_color(HALF_RED);
+    filled.fill_circle(Vec2::new(8.0, 8.0), 6.5).unwrap();
+
+    let mut ctx = new_ctx();
+    ctx.set_fill_color(HALF_RED);
+    ctx.draw_circle(Vec2::new(8.0, 8.0), 6.5, 100.0).unwrap();
+
+    assert_blended_once(ctx.framebuffer(), HALF_RED);
+    assert_eq!(ctx.framebuffer().pixels(), filled.framebuffer().pixels());
+}
+
+#[test]
+fn rect_outline_thickness_is_clamped() {
+    let rect = Rect::new(2.0, 3.0, 10.0, 6.0);
+
+    let mut filled = new_ctx();
+    filled.set_fill_color(HALF_RED);
+    filled.fill_rect(rect).unwrap();
+
+    let mut ctx = new_ctx();
+    ctx.set_fill_color(HALF_RED);
+    ctx.draw_rect_outline(rect, 100.0).unwrap();
+    assert_blended_once(ctx.framebuffer(), HALF_RED);
+    assert_eq!(ctx.framebuffer().pixels(), filled.framebuffer().pixels());
+
+    // Отрицательная толщина ничего не рисует
+    let mut ctx = new_ctx();
+    ctx.set_fill_color(HALF_RED);
+    ctx.draw_rect_outline(rect, -5.0).unwrap();
+    assert!(covered(ctx.framebuffer()).is_empty());
+}
+
+#[test]
+fn rect_is_transformed() {
+    let mut ctx = new_ctx();
+    ctx.set_fill_color(Color::WHITE);
+    ctx.push_transform(Transform::translation(Vec2::new(8.0, 8.0)));
+    ctx.push_transform(Transform::rotation(90.0));
+    ctx.fill_rect(Rect::new(0.0, 0.0, 4.0, 2.0)).unwrap();
+    ctx.pop_transform().unwrap();
+    ctx.pop_transform().unwrap();
+
+    // После поворота на 90° по часовой стрелке прямоугольник 4x2 стоит вертикально
+    // левее точки сдвига
+    let expected: Vec<(u32, u32)> = (8..12).flat_map(|y| (6..8).map(move |x| (x, y))).collect();
+    assert_eq!(covered(ctx.framebuffer()), expected);
+}
+
+#[test]
+fn texture_is_transformed() {
+    let mut image = Image::new(2, 1);
+    image.set_pixel(0, 0, Color::RED);
+    image.set_pixel(1, 0, Color::GREEN);
+
+    let mut ctx = new_ctx();
+    let texture = ctx
+        .load_texture(
+            TextureSource::Data(image.to_png_bytes().unwrap()),
+            TextureOptions::PIXELATED,
+        )
+        .unwrap();
+    ctx.push_transform(Transform::translation(Vec2::new(4.0, 4.0)));
+    ctx.push_transform(Transform::rotation(90.0));
+    ctx.draw_texture(&texture, Vec2::new(0.0, 0.0), Vec2::new(0.0, 0.0))
+        .unwrap();
+    ctx.pop_transform().unwrap();
+    ctx.pop_transform().unwrap();
+
+    let image = ctx.framebuffer();
+    assert_eq!(covered(image), vec![(3, 4), (3, 5)]);
+    assert_eq!(image.get_pixel(3, 4), Color::RED);
+    assert_eq!(image.get_pixel(3, 5), Color::GREEN);
+}
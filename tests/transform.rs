@@ -0,0 +1,76 @@
+//! Проверки стека преобразований на бэкенде-пустышке.
+
+use cake_engine::{
+    conf::Conf, context::Context, dummy::DummyContext, texture::TextureOptions,
+    transform::Transform, vec::Vec2,
+};
+
+fn assert_near(a: Vec2, b: Vec2) {
+    assert!(
+        (a.x - b.x).abs() < 0.001 && (a.y - b.y).abs() < 0.001,
+        "{:?} != {:?}",
+        a,
+        b
+    );
+}
+
+#[test]
+fn transforms_are_combined_in_stack_order() {
+    let mut ctx = DummyContext::new(&Conf::default());
+    assert_eq!(ctx.get_transform(), Transform::IDENTITY);
+
+    ctx.push_transform(Transform::translation(Vec2::new(100.0, 50.0)));
+    ctx.push_transform(Transform::scaling(Vec2::new(2.0, 2.0)));
+    // Сначала масштаб, потом сдвиг
+    assert_near(
+        ctx.get_transform().apply(Vec2::new(10.0, 10.0)),
+        Vec2::new(120.0, 70.0),
+    );
+
+    ctx.push_transform(Transform::rotation(90.0));
+    // Поворот по часовой стрелке: ось x смотрит вниз
+    assert_near(
+        ctx.get_transform().apply(Vec2::new(10.0, 0.0)),
+        Vec2::new(100.0, 70.0),
+    );
+
+    ctx.pop_transform().unwrap();
+    ctx.pop_transform().unwrap();
+    assert_near(
+        ctx.get_transform().apply(Vec2::new(10.0, 10.0)),
+        Vec2::new(110.0, 60.0),
+    );
+    ctx.pop_transform().unwrap();
+    assert!(ctx.pop_transform().is_err());
+}
+
+#[test]
+fn inverse_and_pivots() {
+    let t = Transform::rotation_around(Vec2::new(50.0, 50.0), 30.0)
+        .combine(&Transform::scaling_around(Vec2::new(10.0, 20.0), Vec2::new(3.0, 0.5)));
+    let p = Vec2::new(7.0, -4.0);
+    assert_near(t.inverse().unwrap().apply(t.apply(p)), p);
+
+    assert_near(
+        Transform::rotation_around(Vec2::new(50.0, 50.0), 30.0).apply(Vec2::new(50.0, 50.0)),
+        Vec2::new(50.0, 50.0),
+    );
+    assert!(Transform::scaling(Vec2::new(0.0, 1.0)).inverse().is_none());
+}
+
+#[test]
+fn render_target_has_own_stack() {
+    let mut ctx = DummyContext::new(&Conf::default());
+    let shake = Transform::translation(Vec2::new(3.0, -2.0));
+    ctx.push_transform(shake);
+
+    let target = ctx
+        .create_render_target(64, 64, TextureOptions::PIXELATED)
+        .unwrap();
+    ctx.begin_target(&target).unwrap();
+    assert_eq!(ctx.get_transform(), Transform::IDENTITY);
+    assert!(ctx.pop_transform().is_err());
+    ctx.end_target().unwrap();
+
+    assert_eq!(ctx.get_transform(), shake);
+}
@@ -0,0 +1,60 @@
+//! Проверки учёта голосов на бэкенде-пустышке.
+
+use cake_engine::{audio::VoiceStealing, conf::Conf, context::Context, dummy::DummyContext};
+use std::path::Path;
+
+fn make_ctx(max_voices: usize, voice_stealing: VoiceStealing) -> DummyContext {
+    DummyContext::new(&Conf {
+        max_voices,
+        voice_stealing,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn same_sound_plays_in_several_voices() {
+    let mut ctx = make_ctx(4, VoiceStealing::Oldest);
+    let galop = ctx.load_sound_file(Path::new("sounds/galop.ogg")).unwrap();
+
+    let luna = ctx.play_sound(&galop, 1.0, true).unwrap();
+    let celestia = ctx.play_sound(&galop, 1.0, true).unwrap();
+    assert_ne!(luna, celestia);
+
+    ctx.stop_voice(luna).unwrap();
+    assert!(!ctx.is_voice_playing(luna));
+    assert!(ctx.is_voice_playing(celestia));
+
+    ctx.stop_sound(&galop).unwrap();
+    assert!(!ctx.is_voice_playing(celestia));
+}
+
+#[test]
+fn voice_stealing_policies() {
+    let mut ctx = make_ctx(2, VoiceStealing::Oldest);
+    let laser = ctx.load_sound_file(Path::new("sounds/laser.ogg")).unwrap();
+    let a = ctx.play_sound(&laser, 1.0, true).unwrap();
+    let b = ctx.play_sound(&laser, 1.0, true).unwrap();
+    let c = ctx.play_sound(&laser, 1.0, true).unwrap();
+    assert!(!ctx.is_voice_playing(a));
+    assert!(ctx.is_voice_playing(b));
+    assert!(ctx.is_voice_playing(c));
+
+    let mut ctx = make_ctx(2, VoiceStealing::Quietest);
+    let laser = ctx.load_sound_file(Path::new("sounds/laser.ogg")).unwrap();
+    let a = ctx.play_sound(&laser, 1.0, true).unwrap();
+    let b = ctx.play_sound(&laser, 1.0, true).unwrap();
+    ctx.set_voice_volume(b, 0.2).unwrap();
+    let c = ctx.play_sound(&laser, 0.5, true).unwrap();
+    assert!(ctx.is_voice_playing(a));
+    assert!(!ctx.is_voice_playing(b));
+    assert!(ctx.is_voice_playing(c));
+
+    let mut ctx = make_ctx(2, VoiceStealing::Skip);
+    let laser = ctx.load_sound_file(Path::new("sounds/laser.ogg")).unwrap();
+    let a = ctx.play_sound(&laser, 1.0, true).unwrap();
+    let b = ctx.play_sound(&laser, 1.0, true).unwrap();
+    let c = ctx.play_sound(&laser, 1.0, true).unwrap();
+    assert!(ctx.is_voice_playing(a));
+    assert!(ctx.is_voice_playing(b));
+    assert!(!ctx.is_voice_playing(c));
+}